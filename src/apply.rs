@@ -0,0 +1,25 @@
+use pupper::diff::Patch;
+
+use std::{convert::TryFrom as _, path::Path};
+
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let patch_path = Path::new(args.value_of("patch").unwrap());
+    let patch = super::read_data_from_path(patch_path).and_then(|x| {
+        Patch::try_from(x.as_slice()).map_err(|err| {
+            format!(
+                "failed to parse patch at '{}': {}",
+                patch_path.display(),
+                err
+            )
+            .into()
+        })
+    })?;
+
+    let source = super::read_pup_from_path(path)?;
+    let target = patch
+        .apply(&source)
+        .map_err(|err| format!("failed to apply patch: {}", err))?;
+
+    let output = args.value_of("output").map_or(path, Path::new);
+    super::write_pup_to_path(&target, output)
+}
@@ -0,0 +1,98 @@
+use pupper::{diff, ParseOptions, Pup, ValidationLevel};
+
+use serde_json::json;
+
+use sha2::Digest as _;
+
+use std::{
+    env,
+    fs::OpenOptions,
+    io::Write as _,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// The file a subcommand modifies or creates, or None if it only reads.
+pub fn output_path<'a>(path: &'a Path, args: &'a clap::ArgMatches) -> Option<&'a Path> {
+    match args.subcommand() {
+        ("apply", Some(args)) => Some(args.value_of("output").map_or(path, Path::new)),
+        ("create", _) | ("from-json", _) | ("pack", _) | ("sort", _) => Some(path),
+        ("repair", Some(args)) => args.value_of("output").map(Path::new),
+        ("segment", Some(args)) => match args.subcommand_name() {
+            Some("insert") | Some("remove") => Some(path),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Runs a subcommand that writes to the output path, then appends a JSON line describing the
+// modification to the log.
+//
+// Nothing is logged if the subcommand fails.
+pub fn record(
+    log_path: &Path,
+    input: &Path,
+    output: &Path,
+    f: impl FnOnce() -> Result<(), crate::Error>,
+) -> Result<(), crate::Error> {
+    // 'create' has no input.
+    let before = std::fs::read(input).ok();
+    f()?;
+    let after = super::read_data_from_path(output)?;
+
+    let segments = touched_segments(before.as_deref(), &after);
+    let entry = json!({
+        "timestamp": SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs()),
+        "command": env::args().collect::<Vec<_>>(),
+        "input": input.display().to_string(),
+        "input-sha256": before.as_deref().map(sha256),
+        "output": output.display().to_string(),
+        "output-sha256": sha256(&after),
+        "segments": segments,
+    });
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .and_then(|mut file| writeln!(file, "{}", entry))
+        .map_err(|err| format!("failed to write to '{}': {}", log_path.display(), err).into())
+}
+
+// The IDs of the segments that were added, removed, or modified, if both sides parse.
+fn touched_segments(before: Option<&[u8]>, after: &[u8]) -> Option<Vec<u64>> {
+    let parse = |data: &[u8]| {
+        Pup::parse(
+            data,
+            &mut ParseOptions {
+                validation: ValidationLevel::None,
+                ..Default::default()
+            },
+        )
+        .ok()
+    };
+
+    let before = match before {
+        Some(x) => parse(x)?,
+        None => Pup::new(Vec::new(), 0),
+    };
+    let after = parse(after)?;
+
+    Some(
+        diff::diff(&before, &after)
+            .segments
+            .iter()
+            .map(|x| x.id.0)
+            .collect(),
+    )
+}
+
+fn sha256(data: &[u8]) -> String {
+    sha2::Sha256::digest(data)
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
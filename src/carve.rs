@@ -0,0 +1,34 @@
+use std::{fs, path::Path};
+
+pub fn execute(args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let dump_path = Path::new(args.value_of("dump").unwrap());
+    let dir = Path::new(args.value_of("dir").unwrap());
+
+    let data = super::read_data_from_path(dump_path)?;
+    let ranges = pupper::carve(&data);
+    if ranges.is_empty() {
+        return Err(format!("no PUPs found in '{}'", dump_path.display()).into());
+    }
+
+    fs::create_dir_all(dir)
+        .map_err(|err| format!("failed to create '{}': {}", dir.display(), err))?;
+
+    for range in ranges {
+        // Named after the offset, which is the only thing that distinguishes otherwise identical
+        // PUPs.
+        let pup_path = dir.join(format!("{:#x}.PUP", range.start));
+        super::write_data_to_path(
+            &data[(range.start as usize)..(range.end as usize)],
+            &pup_path,
+        )?;
+
+        println!(
+            "{:#x}..{:#x}: '{}'",
+            range.start,
+            range.end,
+            pup_path.display()
+        );
+    }
+
+    Ok(())
+}
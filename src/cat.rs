@@ -0,0 +1,51 @@
+use pupper::SegmentNameMap;
+
+use std::io::Write as _;
+
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let segment = args.value_of("segment").unwrap();
+    let id = super::parse_segment(segment, names)?;
+
+    let pup = super::read_pup_from_path(path)?;
+    let seg = pup
+        .segments
+        .iter()
+        .find(|x| x.id == id)
+        .ok_or_else(|| format!("PUP has no segment '{}'", segment))?;
+
+    let text = decode(seg.data());
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(text.as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(|err| format!("failed to write to stdout: {}", err).into())
+}
+
+// Text is assumed to be UTF-8 unless a byte order mark says otherwise.
+pub fn decode(data: &[u8]) -> String {
+    let decode_utf16 = |data: &[u8], decode_unit: fn([u8; 2]) -> u16| {
+        let units = data.chunks(2).map(|x| match *x {
+            [a, b] => decode_unit([a, b]),
+            // A trailing odd byte can't be decoded.
+            _ => char::REPLACEMENT_CHARACTER as u16,
+        });
+
+        char::decode_utf16(units)
+            .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    };
+
+    if let Some(data) = data.strip_prefix(b"\xEF\xBB\xBF") {
+        String::from_utf8_lossy(data).into_owned()
+    } else if let Some(data) = data.strip_prefix(b"\xFE\xFF") {
+        decode_utf16(data, u16::from_be_bytes)
+    } else if let Some(data) = data.strip_prefix(b"\xFF\xFE") {
+        decode_utf16(data, u16::from_le_bytes)
+    } else {
+        String::from_utf8_lossy(data).into_owned()
+    }
+}
@@ -0,0 +1,52 @@
+use pupper::{FirmwareDb, ParseOptions};
+
+use std::{fs, path::Path};
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let db_path = args
+        .value_of_os("db")
+        .map(Path::new)
+        .or(config.db.as_deref())
+        .ok_or(
+            "no firmware database to compare against (pupper doesn't ship one); pass one with \
+             -d or set 'db' in the configuration",
+        )?;
+    let db = read_db_from_path(db_path)?;
+
+    let data = super::read_pup_data(path, |_| true)?;
+    let pup = super::parse_pup(&data, path, &mut ParseOptions::default())?;
+
+    if let Some(release) = db.find(&data) {
+        println!("matches {}", release);
+        return Ok(());
+    }
+
+    let release = db
+        .closest(&pup)
+        .ok_or("PUP doesn't resemble any official release")?;
+
+    println!("closest to {}, but:", release);
+    for deviation in release.compare(&data) {
+        println!("  {}", deviation);
+    }
+
+    Err(format!("PUP doesn't match {}", release).into())
+}
+
+pub fn read_db_from_path(path: &Path) -> Result<FirmwareDb, crate::Error> {
+    let toml = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read from '{}': {}", path.display(), err))?;
+
+    FirmwareDb::from_toml(&toml).map_err(|err| {
+        format!(
+            "failed to parse firmware database at '{}': {}",
+            path.display(),
+            err
+        )
+        .into()
+    })
+}
@@ -0,0 +1,36 @@
+use pupper::{ParseOptions, UpdateList};
+
+use std::path::Path;
+
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let list_path = args.value_of("list").unwrap();
+    let list = UpdateList::parse(&String::from_utf8_lossy(&read_list(list_path)?));
+
+    let data = super::read_pup_data(path, |_| true)?;
+    let pup = super::parse_pup(&data, path, &mut ParseOptions::default())?;
+
+    let entry = list
+        .check(&pup, &data)
+        .map_err(|err| format!("PUP doesn't match '{}': {}", list_path, err))?;
+
+    println!(
+        "matches {}{}",
+        entry.version().unwrap(),
+        entry
+            .dest
+            .as_ref()
+            .map_or_else(String::new, |x| format!(" (Dest={})", x))
+    );
+
+    Ok(())
+}
+
+// Update lists are small, so they're read in full from wherever they are.
+fn read_list(path: &str) -> Result<Vec<u8>, crate::Error> {
+    #[cfg(feature = "net")]
+    if super::net::is_url(path) {
+        return super::net::get(path, None);
+    }
+
+    super::read_data_from_path(Path::new(path))
+}
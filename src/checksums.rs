@@ -0,0 +1,140 @@
+use pupper::{SegmentEntry, SegmentNameMap};
+
+use sha1::Digest;
+
+use std::path::Path;
+
+// Prints one line per segment in the format of 'sha256sum', so that 'sha256sum -c' verifies the
+// output of 'unpack'.
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let algo = args.value_of("algo");
+    let checksums = if crate::stream::streams(path) {
+        stream_checksums(path, names, algo)?
+    } else {
+        let pup = super::read_pup_from_path(path)?;
+        let file_names = super::unpack::file_names(pup.segments.iter().map(|x| x.id), names);
+
+        pup.segments
+            .iter()
+            .zip(file_names)
+            .map(|(seg, file_name)| (hash(seg.data(), algo), file_name))
+            .collect()
+    };
+
+    if let Some(manifest_path) = args.value_of("verify").map(Path::new) {
+        let manifest = read_manifest_from_path(manifest_path)?;
+        return compare_checksums(&checksums, &manifest);
+    }
+
+    for (digest, file_name) in checksums.iter() {
+        println!("{}  {}", digest, file_name);
+    }
+
+    Ok(())
+}
+
+fn hash(data: &[u8], algo: Option<&str>) -> String {
+    let digest = match algo {
+        Some("sha1") => sha1::Sha1::digest(data).to_vec(),
+        _ => sha2::Sha256::digest(data).to_vec(),
+    };
+
+    to_hex(&digest)
+}
+
+// Like the above, but each segment is read from the file a buffer at a time.
+fn stream_checksums(
+    path: &Path,
+    names: &SegmentNameMap,
+    algo: Option<&str>,
+) -> Result<Vec<(String, String)>, crate::Error> {
+    let header = crate::stream::read_header(path)?;
+    let file_names = super::unpack::file_names(header.segment_entries.iter().map(|x| x.id), names);
+
+    header
+        .segment_entries
+        .iter()
+        .zip(file_names)
+        .map(|(entry, file_name)| {
+            let digest = match algo {
+                Some("sha1") => stream_hash::<sha1::Sha1>(path, entry)?,
+                _ => stream_hash::<sha2::Sha256>(path, entry)?,
+            };
+
+            Ok((digest, file_name))
+        })
+        .collect()
+}
+
+fn stream_hash<D: Digest>(path: &Path, entry: &SegmentEntry) -> Result<String, crate::Error> {
+    let mut hasher = D::new();
+    crate::stream::for_each_chunk(path, entry, 1, |chunk| {
+        hasher.update(chunk);
+        Ok(())
+    })?;
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+// Reads the digests and file names from the output of 'checksums'.
+fn read_manifest_from_path(path: &Path) -> Result<Vec<(String, String)>, crate::Error> {
+    let data = super::read_data_from_path(path)?;
+    let text = String::from_utf8(data)
+        .map_err(|_| format!("manifest at '{}' isn't valid UTF-8", path.display()))?;
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            // Like 'sha256sum', a '*' before the file name marks binary mode.
+            let (digest, file_name) = line
+                .split_once(' ')
+                .map(|(digest, rest)| (digest, rest.strip_prefix(|c| c == ' ' || c == '*')))
+                .and_then(|(digest, file_name)| Some((digest, file_name?)))
+                .ok_or_else(|| format!("line {} of the manifest is malformed", i + 1))?;
+
+            Ok((digest.to_lowercase(), file_name.to_string()))
+        })
+        .collect()
+}
+
+fn compare_checksums(
+    checksums: &[(String, String)],
+    manifest: &[(String, String)],
+) -> Result<(), crate::Error> {
+    let mut mismatches = 0;
+    for (expected, file_name) in manifest.iter() {
+        match checksums.iter().find(|(_, x)| x == file_name) {
+            Some((digest, _)) if digest == expected => {}
+            Some(_) => {
+                println!("'{}': digest doesn't match the manifest", file_name);
+                mismatches += 1;
+            }
+            None => {
+                println!("'{}': segment is missing from the PUP", file_name);
+                mismatches += 1;
+            }
+        }
+    }
+
+    for (_, file_name) in checksums.iter() {
+        if !manifest.iter().any(|(_, x)| x == file_name) {
+            println!("'{}': segment isn't in the manifest", file_name);
+            mismatches += 1;
+        }
+    }
+
+    if mismatches == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} segment(s) don't match the manifest", mismatches).into())
+    }
+}
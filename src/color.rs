@@ -0,0 +1,34 @@
+use std::{
+    env,
+    fmt::Display,
+    io::{self, IsTerminal as _},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Whether output is colored, as decided by '--color'.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub const RED: &str = "31";
+pub const YELLOW: &str = "33";
+pub const CYAN: &str = "36";
+
+/// Decides whether to color output: 'always', 'never', or by default, only if stdout is a terminal
+/// and NO_COLOR isn't set.
+pub fn set_choice(choice: Option<&str>) {
+    let enabled = match choice {
+        Some("always") => true,
+        Some("never") => false,
+        _ => env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal(),
+    };
+
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Wraps text in the ANSI escape codes of a color, if output is colored.
+pub fn paint(text: impl Display, color: &str) -> String {
+    if ENABLED.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", color, text)
+    } else {
+        text.to_string()
+    }
+}
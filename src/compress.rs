@@ -0,0 +1,91 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read, Write as _},
+    path::Path,
+};
+
+/// Unwraps a PUP from a gzip, zip, or zstd wrapper, if it has one.
+pub fn decompress(data: Vec<u8>, path: &Path) -> Result<Vec<u8>, crate::Error> {
+    let err =
+        |err: &dyn std::fmt::Display| format!("failed to decompress '{}': {}", path.display(), err);
+
+    if data.starts_with(&[0x1F, 0x8B]) {
+        read_to_end(flate2::read::GzDecoder::new(data.as_slice()), path)
+    } else if data.starts_with(b"PK\x03\x04") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|x| err(&x))?;
+
+        // Archives sometimes hold a readme or two next to the PUP.
+        let i = (0..archive.len())
+            .find(|i| {
+                archive
+                    .by_index(*i)
+                    .is_ok_and(|x| x.name().to_ascii_uppercase().ends_with(".PUP"))
+            })
+            .or(if archive.len() == 1 { Some(0) } else { None })
+            .ok_or_else(|| err(&"archive doesn't contain a PUP"))?;
+
+        let file = archive.by_index(i).map_err(|x| err(&x))?;
+        read_to_end(file, path)
+    } else if data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+        let decoder = zstd::stream::read::Decoder::new(data.as_slice()).map_err(|x| err(&x))?;
+        read_to_end(decoder, path)
+    } else {
+        Ok(data)
+    }
+}
+
+// Decompresses no more than '--max_memory' allows, so that a small archive can't expand past the
+// budget before it is checked.
+fn read_to_end(reader: impl Read, path: &Path) -> Result<Vec<u8>, crate::Error> {
+    let budget = crate::stream::budget();
+    let mut result = Vec::new();
+    reader
+        .take(budget.map_or(u64::MAX, |x| x as u64 + 1))
+        .read_to_end(&mut result)
+        .map_err(|err| format!("failed to decompress '{}': {}", path.display(), err))?;
+
+    match budget {
+        Some(budget) if result.len() > budget => Err(format!(
+            "decompressing '{}' takes more than the {} B --max_memory allows",
+            path.display(),
+            budget
+        )
+        .into()),
+        _ => Ok(result),
+    }
+}
+
+/// Wraps a PUP in gzip or zstd if the path ends in '.gz' or '.zst', respectively.
+///
+/// Fails if the path holds a zip archive: the PUP would replace the whole archive, along with
+/// anything else in it.
+pub fn compress(data: Vec<u8>, path: &Path) -> Result<Vec<u8>, crate::Error> {
+    let err = |err: std::io::Error| format!("failed to compress '{}': {}", path.display(), err);
+
+    let mut magic = [0; 4];
+    if File::open(path)
+        .and_then(|mut x| x.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"PK\x03\x04"
+    {
+        return Err(format!(
+            "'{}' is a zip archive, which can't be modified in place; write the PUP elsewhere or \
+             extract it first",
+            path.display()
+        )
+        .into());
+    }
+
+    match path.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("gz") => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&data).map_err(err)?;
+
+            encoder.finish().map_err(|x| err(x).into())
+        }
+        // A level of zero means the default.
+        Some("zst") => zstd::encode_all(data.as_slice(), 0).map_err(|x| err(x).into()),
+        _ => Ok(data),
+    }
+}
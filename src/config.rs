@@ -0,0 +1,166 @@
+use pupper::Parallelism;
+
+use std::{
+    convert::TryFrom as _,
+    env, fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+
+/// Defaults for command-line options, read from a TOML file:
+///
+/// ```toml
+/// names = "names.toml"
+/// error-format = "json"
+/// db = "/usr/share/pupper/firmware.toml"
+/// audit-log = "audit.jsonl"
+/// threads = 4
+/// max-memory = "64M"
+/// key-file = "hmac.key"
+/// alignment = 16
+/// color = "never"
+///
+/// [lint]
+/// unknown-id = "off"
+/// ```
+///
+/// Options given on the command line take precedence. Relative paths are relative to the
+/// directory of the configuration file.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The default for '--names'.
+    pub names: Option<PathBuf>,
+    /// The default for '--error_format'.
+    pub error_format: Option<String>,
+    /// The default for '--db'.
+    pub db: Option<PathBuf>,
+    /// The default for '--audit_log'.
+    pub audit_log: Option<PathBuf>,
+    /// The default for '--threads'.
+    pub threads: Option<NonZeroUsize>,
+    /// The default for '--max_memory', in bytes.
+    pub max_memory: Option<usize>,
+    /// The default for '--key'.
+    pub key_file: Option<PathBuf>,
+    /// The default for '--alignment'.
+    pub alignment: Option<u64>,
+    /// The default for '--color'.
+    pub color: Option<String>,
+    /// Severities of 'lint' rules, by rule ID, before '--rule'.
+    pub lint: Vec<(String, String)>,
+}
+
+impl Config {
+    /// How many threads parallel operations may use.
+    pub fn parallelism(&self) -> Parallelism {
+        self.threads.map_or(Parallelism::Auto, Parallelism::Threads)
+    }
+
+    /// Reads the configuration file at the given path or, failing that, at the default path.
+    ///
+    /// Only an explicitly given file must exist.
+    pub fn read(path: Option<&str>) -> Result<Self, crate::Error> {
+        let (path, required) = match path {
+            Some(x) => (PathBuf::from(x), true),
+            None => match default_path() {
+                Some(x) => (x, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let toml = match fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(_) if !required && !path.exists() => return Ok(Self::default()),
+            Err(err) => {
+                return Err(format!("failed to read from '{}': {}", path.display(), err).into())
+            }
+        };
+
+        Self::from_toml(&toml, path.parent().unwrap_or_else(|| Path::new(""))).map_err(|err| {
+            format!(
+                "failed to parse configuration at '{}': {}",
+                path.display(),
+                err
+            )
+            .into()
+        })
+    }
+
+    fn from_toml(toml: &str, dir: &Path) -> Result<Self, String> {
+        let table: toml::value::Table =
+            toml::from_str(toml).map_err(|err| format!("failed to parse TOML: {}", err))?;
+
+        let mut config = Self::default();
+        for (key, value) in table.iter() {
+            if key == "lint" {
+                config.lint = parse_lint(value)?;
+                continue;
+            }
+            if key == "threads" {
+                config.threads = value
+                    .as_integer()
+                    .and_then(|x| usize::try_from(x).ok())
+                    .and_then(NonZeroUsize::new)
+                    .map(Some)
+                    .ok_or("'threads' is not a positive integer")?;
+                continue;
+            }
+            if key == "alignment" {
+                config.alignment = value
+                    .as_integer()
+                    .and_then(|x| u64::try_from(x).ok())
+                    .filter(|x| *x != 0)
+                    .map(Some)
+                    .ok_or("'alignment' is not a positive integer")?;
+                continue;
+            }
+
+            let value = value
+                .as_str()
+                .ok_or_else(|| format!("'{}' is not a string", key))?;
+
+            match key.as_str() {
+                "names" => config.names = Some(dir.join(value)),
+                "error-format" => match value {
+                    "human" | "json" => config.error_format = Some(value.into()),
+                    _ => return Err("'error-format' must be 'human' or 'json'".into()),
+                },
+                "db" => config.db = Some(dir.join(value)),
+                "audit-log" => config.audit_log = Some(dir.join(value)),
+                "max-memory" => config.max_memory = Some(crate::seg::parse_size_option(value)?),
+                "key-file" => config.key_file = Some(dir.join(value)),
+                "color" => match value {
+                    "auto" | "always" | "never" => config.color = Some(value.into()),
+                    _ => return Err("'color' must be 'auto', 'always', or 'never'".into()),
+                },
+                _ => return Err(format!("'{}' is not a known option", key)),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_lint(value: &toml::Value) -> Result<Vec<(String, String)>, String> {
+    value
+        .as_table()
+        .ok_or("'lint' is not a table")?
+        .iter()
+        .map(|(rule, severity)| {
+            severity
+                .as_str()
+                .map(|x| (rule.clone(), x.into()))
+                .ok_or_else(|| format!("'lint.{}' is not a string", rule))
+        })
+        .collect()
+}
+
+// $XDG_CONFIG_HOME/pupper/config.toml, or ~/.config/pupper/config.toml.
+fn default_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|x| Path::new(&x).join(".config")))?;
+
+    Some(dir.join("pupper").join("config.toml"))
+}
@@ -1,16 +1,47 @@
-use pupper::Pup;
+use pupper::{Pup, SegmentNameMap};
 
-pub fn execute(path: &std::path::Path, args: &clap::ArgMatches) -> Result<(), String> {
+use std::path::Path;
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
     let img_version = parse_img_version_option(args.value_of("img_version"))?;
-    let pup = Pup::new(Vec::new(), img_version);
+    let mut pup = match args.value_of("template") {
+        Some("standard") => Pup::standard(img_version),
+        _ => Pup::new(Vec::new(), img_version),
+    };
+
+    if let Some(stubs) = args.value_of("stubs").map(Path::new) {
+        fill_from_stubs(&mut pup, stubs, names)?;
+    }
 
     super::write_pup_to_path(&pup, path)
 }
 
-fn parse_img_version_option(img_version: Option<&str>) -> Result<u64, String> {
+pub fn parse_img_version_option(img_version: Option<&str>) -> Result<u64, String> {
     img_version.map_or(Ok(0), |img_version| {
-        img_version
-            .parse()
-            .map_err(|err| format!("failed to parse image version: {}", err))
+        crate::parse_number(img_version, "image version")
     })
 }
+
+// Replaces the data of each segment with the file of the same name in the given directory, if
+// there is one.
+fn fill_from_stubs(pup: &mut Pup, dir: &Path, names: &SegmentNameMap) -> Result<(), crate::Error> {
+    for seg in pup.segments.iter_mut() {
+        let stub_path = match seg.file_name_in(names) {
+            Some(file_name) => dir.join(file_name),
+            None => continue,
+        };
+        if !stub_path.is_file() {
+            continue;
+        }
+
+        let data = super::read_data_from_path(&stub_path)?;
+        seg.set_data(data);
+        seg.sign();
+    }
+
+    Ok(())
+}
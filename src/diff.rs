@@ -0,0 +1,98 @@
+use pupper::{
+    diff::{self, ChangeKind, Patch, PupDelta},
+    SegmentNameMap,
+};
+
+use serde_json::{json, Value};
+
+use std::path::Path;
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let old = super::read_pup_from_path(path)?;
+    let new = super::read_pup_from_path(Path::new(args.value_of("other").unwrap()))?;
+    let delta = diff::diff(&old, &new);
+
+    if let Some(patch_path) = args.value_of("patch").map(Path::new) {
+        let patch =
+            Patch::new(&old, &new).map_err(|err| format!("failed to create patch: {}", err))?;
+        super::write_data_to_path(&Vec::from(&patch), patch_path)?;
+    }
+
+    match args.value_of("format") {
+        Some("json-patch") => println!("{:#}", json_patch(&delta)),
+        _ => print_delta(&delta, names),
+    }
+
+    Ok(())
+}
+
+fn print_delta(delta: &PupDelta, names: &SegmentNameMap) {
+    if let Some((old, new)) = delta.image_version {
+        println!("~ image version: {} -> {}", old, new);
+    }
+    if let Some((old, new)) = delta.package_version {
+        println!("~ package version: {} -> {}", old, new);
+    }
+
+    for change in delta.segments.iter() {
+        let file_name = names.name_of(change.id).unwrap_or("?");
+
+        match (change.old, change.new, change.bytes) {
+            (Some(old), Some(new), Some(bytes)) => println!(
+                "~ {:#06x} {}: {} B -> {} B ({} B changed in {} run(s))",
+                change.id.0, file_name, old.size, new.size, bytes.changed_bytes, bytes.changed_runs
+            ),
+            (Some(old), None, _) => {
+                println!("- {:#06x} {}: {} B", change.id.0, file_name, old.size)
+            }
+            (None, Some(new), _) => {
+                println!("+ {:#06x} {}: {} B", change.id.0, file_name, new.size)
+            }
+            _ => {}
+        }
+    }
+}
+
+// Loosely follows JSON Patch (RFC 6902): each operation names the changed path in the output of
+// 'print', along with both sides of the change.
+fn json_patch(delta: &PupDelta) -> Value {
+    let mut ops = Vec::new();
+
+    for (path, versions) in [
+        ("/image-version", delta.image_version),
+        ("/package-version", delta.package_version),
+    ] {
+        if let Some((old, new)) = versions {
+            ops.push(json!({
+                "op": "replace",
+                "path": path,
+                "old": old,
+                "new": new,
+            }));
+        }
+    }
+
+    for change in delta.segments.iter() {
+        let op = match change.kind() {
+            ChangeKind::Added => "add",
+            ChangeKind::Removed => "remove",
+            ChangeKind::Modified => "replace",
+        };
+
+        ops.push(json!({
+            "op": op,
+            "path": format!("/segments/{:#x}", change.id.0),
+            "id": format!("{:#x}", change.id.0),
+            "old-size": change.old.map(|x| x.size),
+            "new-size": change.new.map(|x| x.size),
+            "old-digest": change.old.map(|x| x.digest.to_string()),
+            "new-digest": change.new.map(|x| x.digest.to_string()),
+        }));
+    }
+
+    ops.into()
+}
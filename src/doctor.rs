@@ -0,0 +1,211 @@
+use pupper::{Finding, Magic, ParseError, PupKind};
+
+use std::path::Path;
+
+// A problem, in plain words, along with what to do about it.
+struct Problem {
+    diagnosis: String,
+    fix: Option<String>,
+}
+
+impl Problem {
+    fn new(diagnosis: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            diagnosis: diagnosis.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+pub fn execute(path: &Path) -> Result<(), crate::Error> {
+    let data = super::read_pup_data(path, |_| true)?;
+    let problems = diagnose(&data);
+
+    if problems.is_empty() {
+        println!("no problems found");
+        return Ok(());
+    }
+
+    for problem in problems.iter() {
+        println!("problem: {}", problem.diagnosis);
+        if let Some(ref fix) = problem.fix {
+            println!("    fix: {}", fix);
+        }
+    }
+
+    Err(format!("found {} problem(s)", problems.len()).into())
+}
+
+fn diagnose(data: &[u8]) -> Vec<Problem> {
+    const REDOWNLOAD: &str = "download the PUP again";
+    let magic = Magic::default().0;
+
+    if data.len() < magic.len() {
+        return vec![Problem::new(
+            format!("file is only {} byte(s), too short to be a PUP", data.len()),
+            REDOWNLOAD,
+        )];
+    }
+
+    if let Some(problem) = diagnose_foreign(data, &magic) {
+        return vec![problem];
+    }
+
+    match pupper::sniff(data) {
+        Some(PupKind::Supported) => {}
+        Some(PupKind::UnsupportedVersion(version)) => {
+            return vec![Problem::new(
+                format!(
+                    "package version is {}, but PS3 PUPs are version 1; this is likely another \
+                     console's PUP (e.g., a PS Vita's)",
+                    version
+                ),
+                "download the update for the PS3",
+            )];
+        }
+        // The magic is there, but the package version isn't.
+        None => {
+            return vec![Problem::new(
+                "file ends inside the header (truncated download)",
+                REDOWNLOAD,
+            )]
+        }
+    }
+
+    let findings = match pupper::validate(data) {
+        Ok(x) => x,
+        Err(ParseError::Undersized) => {
+            return vec![Problem::new(
+                "file ends inside the header (truncated download)",
+                REDOWNLOAD,
+            )]
+        }
+        Err(err) => {
+            return vec![Problem {
+                diagnosis: format!("header is unreadable: {}", err),
+                fix: None,
+            }]
+        }
+    };
+
+    let mut problems = Vec::new();
+    for finding in findings {
+        match finding {
+            Finding::FileSizeMismatch { declared, actual } if actual < declared => {
+                problems.push(Problem::new(
+                    format!(
+                        "file is {} byte(s) short of the {} its header declares (truncated \
+                         download)",
+                        declared - actual,
+                        declared
+                    ),
+                    REDOWNLOAD,
+                ));
+            }
+            // Archives sometimes concatenate PUPs.
+            Finding::FileSizeMismatch { declared, .. }
+                if data
+                    .get((declared as usize)..)
+                    .is_some_and(|x| x.starts_with(&Magic::default().0)) =>
+            {
+                problems.push(Problem::new(
+                    format!(
+                        "file holds another PUP past the {} bytes of the first",
+                        declared
+                    ),
+                    "print every PUP with 'print --all', or split them with 'carve'",
+                ));
+            }
+            Finding::FileSizeMismatch { declared, actual } => {
+                problems.push(Problem::new(
+                    format!(
+                        "file has {} byte(s) of trailing data past the {} its header declares",
+                        actual - declared,
+                        declared
+                    ),
+                    format!("truncate the file to {} bytes", declared),
+                ));
+            }
+            // A truncated file is already reported above, and order is a matter for 'lint'.
+            Finding::SegmentOutOfBounds(_) | Finding::NonCanonicalOrder(_) => {}
+            finding => problems.push(Problem {
+                diagnosis: format!("header is inconsistent: {}", finding),
+                fix: None,
+            }),
+        }
+    }
+
+    problems
+}
+
+// Recognizes files that aren't PS3 PUPs, but commonly get mistaken for (or wrap) one. Wrapped
+// PUPs only get this far without the 'compress' feature.
+fn diagnose_foreign(data: &[u8], magic: &[u8]) -> Option<Problem> {
+    let swapped = |width: usize| {
+        data[..magic.len()]
+            .chunks(width)
+            .flat_map(|x| x.iter().rev())
+            .copied()
+            .eq(magic.iter().copied())
+    };
+
+    let problem = if data.starts_with(b"PK\x03\x04") {
+        Problem::new(
+            "file is a zip archive",
+            "extract PS3UPDAT.PUP from it first",
+        )
+    } else if data.starts_with(b"\x1F\x8B") {
+        Problem::new("file is gzip-compressed", "decompress it first")
+    } else if data.starts_with(b"\x28\xB5\x2F\xFD") {
+        Problem::new("file is zstd-compressed", "decompress it first")
+    } else if data.starts_with(b"\0PBP") {
+        Problem::new(
+            "file is a PSP update (EBOOT.PBP), not a PS3 PUP",
+            "download the update for the PS3",
+        )
+    } else if data.starts_with(b"\x4F\x15\x3D\x1D") {
+        Problem::new(
+            "file is a PS4 PUP, not a PS3 PUP",
+            "download the update for the PS3",
+        )
+    } else if data.starts_with(magic) {
+        return None;
+    } else if swapped(2) {
+        Problem::new(
+            "file is byte-swapped in 16-bit units",
+            "swap every pair of bytes back (e.g., 'dd conv=swab')",
+        )
+    } else if swapped(4) {
+        Problem::new(
+            "file is byte-swapped in 32-bit units",
+            "reverse every group of 4 bytes back",
+        )
+    } else {
+        Problem::new(
+            "file doesn't start with the PUP magic",
+            "make sure the file is PS3UPDAT.PUP",
+        )
+    };
+
+    Some(problem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diagnose;
+
+    use pupper::Pup;
+
+    // Header fields come straight from the file, so they must be diagnosed, not trusted.
+    #[test]
+    fn diagnoses_overflowing_sizes() {
+        let mut data = Vec::<u8>::from(&Pup::default());
+        data[0x28..0x30].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(!diagnose(&data).is_empty());
+
+        data[0x18..0x20].copy_from_slice(&u64::MAX.to_be_bytes());
+        let problems = diagnose(&data);
+        assert_eq!(1, problems.len());
+        assert!(problems[0].diagnosis.contains("truncated"));
+    }
+}
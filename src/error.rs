@@ -0,0 +1,129 @@
+use pupper::Finding;
+
+use serde_json::json;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+pub enum Error {
+    /// A PUP failed to parse.
+    Parse {
+        path: PathBuf,
+        file_size: u64,
+        err: pupper::ParseError,
+    },
+    /// A PUP parsed, but has structural issues.
+    Findings(Vec<Finding>),
+    /// Anything else (e.g., I/O or bad arguments).
+    Other(String),
+}
+
+impl Error {
+    /// Renders this error as one JSON object per problem.
+    pub fn to_json(&self) -> Vec<serde_json::Value> {
+        match self {
+            Self::Parse { err, file_size, .. } => {
+                let (code, offset, segment) = describe_pup_error(err, *file_size);
+
+                vec![json!({
+                    "code": code,
+                    "message": self.to_string(),
+                    "offset": offset,
+                    "segment": segment,
+                })]
+            }
+            Self::Findings(findings) => findings
+                .iter()
+                .map(|finding| {
+                    let (code, segment) = describe_finding(finding);
+
+                    json!({
+                        "code": code,
+                        "message": finding.to_string(),
+                        "offset": null,
+                        "segment": segment,
+                    })
+                })
+                .collect(),
+            Self::Other(message) => vec![json!({
+                "code": "other",
+                "message": message,
+                "offset": null,
+                "segment": null,
+            })],
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Parse { path, err, .. } => {
+                write!(f, "failed to parse PUP at '{}': {}", path.display(), err)
+            }
+            Self::Findings(findings) => {
+                write!(f, "found {} structural issue(s)", findings.len())
+            }
+            Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::Other(message.into())
+    }
+}
+
+// Returns the code, file offset, and segment index of a parse error.
+fn describe_pup_error(
+    err: &pupper::ParseError,
+    file_size: u64,
+) -> (&'static str, Option<u64>, Option<u64>) {
+    use pupper::ParseError as Error;
+
+    match err {
+        Error::Undersized => ("undersized", Some(file_size), None),
+        Error::InvalidMagic(_) => ("invalid-magic", Some(0x00), None),
+        Error::UnsupportedPackageVersion(_) => ("unsupported-package-version", Some(0x08), None),
+        Error::InvalidSignatureKind(_) => ("invalid-signature-kind", None, None),
+        Error::MissingSignature(i) => ("missing-signature", None, Some(*i)),
+        Error::MissingData(i) => ("missing-data", None, Some(*i)),
+        Error::Inconsistent(finding) => {
+            let (code, segment) = describe_finding(finding);
+            (code, None, segment)
+        }
+        Error::InvalidSignature(i) => ("invalid-signature", None, Some(*i)),
+        Error::InvalidHeaderSignature => ("invalid-header-signature", None, None),
+        Error::SegmentCountMismatch { .. } => ("segment-count-mismatch", Some(0x18), None),
+        Error::HeaderSizeTooSmall { .. } => ("header-size-too-small", Some(0x20), None),
+        Error::DataSizeMismatch { .. } => ("data-size-mismatch", Some(0x28), None),
+        Error::Cancelled => ("cancelled", None, None),
+    }
+}
+
+// Returns the code and segment index of a finding.
+fn describe_finding(finding: &Finding) -> (&'static str, Option<u64>) {
+    match finding {
+        Finding::SegmentCountMismatch { .. } => ("segment-count-mismatch", None),
+        Finding::HeaderSizeMismatch { .. } => ("header-size-mismatch", None),
+        Finding::DataSizeMismatch { .. } => ("data-size-mismatch", None),
+        Finding::FileSizeMismatch { .. } => ("file-size-mismatch", None),
+        Finding::SegmentOverlap(i) => ("segment-overlap", Some(*i)),
+        Finding::SegmentOutOfBounds(i) => ("segment-out-of-bounds", Some(*i)),
+        Finding::MissingSignature(i) => ("missing-signature", Some(*i)),
+        Finding::DuplicateSignature(i) => ("duplicate-signature", Some(*i)),
+        Finding::OrphanSignature(i) => ("orphan-signature", Some(*i)),
+        Finding::DuplicateSegmentId { second, .. } => ("duplicate-segment-id", Some(*second)),
+        Finding::MissingCompanion { index, .. } => ("missing-companion", Some(*index)),
+        Finding::NonCanonicalOrder(i) => ("non-canonical-order", Some(*i)),
+    }
+}
@@ -0,0 +1,22 @@
+use pupper::RawHeader;
+
+use std::convert::TryFrom as _;
+
+pub fn execute(path: &std::path::Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let offset: u64 = crate::parse_number(args.value_of("offset").unwrap(), "offset")?;
+
+    // Only the header is needed to know where everything is.
+    let data = super::read_pup_data(path, |_| false)?;
+    let header = RawHeader::try_from(data.as_slice()).map_err(|err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    })?;
+
+    match header.locate(offset) {
+        Some(location) => println!("{:#x}: {}", offset, location),
+        None => println!("{:#x}: outside of the header and every segment", offset),
+    }
+
+    Ok(())
+}
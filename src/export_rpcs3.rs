@@ -0,0 +1,115 @@
+use pupper::{
+    content::{self, Node, PackageDecryptor},
+    SegmentId,
+};
+
+use std::{
+    fs,
+    io::Write as _,
+    path::{Component, Path},
+    process::{Command, Stdio},
+};
+
+// Lays out the dev_flash files of update_files.tar the way RPCS3 expects them, i.e., under
+// '<output>/dev_flash/'.
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let dir = Path::new(args.value_of("dir").unwrap());
+    let decryptor = CommandDecryptor(args.value_of("decryptor").unwrap().to_string());
+
+    let pup = super::read_pup_from_path(path)?;
+    let seg = pup
+        .segments
+        .iter()
+        .find(|x| x.id == SegmentId(0x300))
+        .ok_or("PUP has no update_files.tar")?;
+    let node = content::extract("update_files.tar", seg.data(), &decryptor);
+
+    let mut export = Export::default();
+    export.visit(&node, dir)?;
+
+    if export.encrypted != 0 {
+        println!(
+            "warning: {} package(s) couldn't be decrypted and were skipped",
+            export.encrypted
+        );
+    }
+    if export.files == 0 {
+        return Err("no dev_flash files were found".to_string().into());
+    }
+    println!("exported {} file(s) to '{}'", export.files, dir.display());
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Export {
+    files: usize,
+    encrypted: usize,
+}
+
+impl Export {
+    fn visit(&mut self, node: &Node, dir: &Path) -> Result<(), crate::Error> {
+        // Packages that couldn't be decrypted keep their SCE header.
+        if node.encrypted && node.data.starts_with(b"SCE\0") {
+            self.encrypted += 1;
+            return Ok(());
+        }
+
+        // The decrypted dev_flash packages are tars of files named 'dev_flash/...'.
+        if node.children.is_empty() && node.name.starts_with("dev_flash/") {
+            let relative = Path::new(&node.name);
+            // Member names come from the package, so they mustn't escape the output directory.
+            if !relative
+                .components()
+                .all(|x| matches!(x, Component::Normal(_)))
+            {
+                return Err(format!("'{}' isn't a safe path", node.name).into());
+            }
+
+            let file_path = dir.join(relative);
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("failed to create '{}': {}", parent.display(), err))?;
+            }
+            super::write_data_to_path(&node.data, &file_path)?;
+            self.files += 1;
+        }
+
+        for child in node.children.iter() {
+            self.visit(child, dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Decrypts packages by running a command with the package name as its only argument, the
+// encrypted data on stdin, and the decrypted data on stdout. A non-zero exit status means the
+// package couldn't be decrypted.
+struct CommandDecryptor(String);
+
+impl PackageDecryptor for CommandDecryptor {
+    fn decrypt(&self, name: &str, data: &[u8]) -> Option<Vec<u8>> {
+        let mut child = Command::new(&self.0)
+            .arg(name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+
+        // Writing from another thread keeps a command that starts writing before it has read
+        // everything from blocking on a full pipe.
+        let mut stdin = child.stdin.take()?;
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || stdin.write_all(data));
+            child.wait_with_output()
+        })
+        .ok()?;
+
+        if output.status.success() {
+            Some(output.stdout)
+        } else {
+            None
+        }
+    }
+}
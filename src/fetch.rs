@@ -0,0 +1,128 @@
+use pupper::{Digest, FirmwareDb, ParseOptions, UpdateList, ValidationLevel};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn subcommand() -> clap::App<'static, 'static> {
+    clap::clap_app!(fetch =>
+        (about: "Downloads an official PUP from Sony's update servers")
+        (@arg version: -v --version +required +takes_value "System software version (e.g., 4.91)")
+        (@arg output: -o --output +required +takes_value "Output file path")
+        (@arg region: -r --region +takes_value
+            possible_value[us eu jp kr uk mx au sa tw ru cn br]
+            "Update server region (default: us)")
+        (@arg list_url: --list_url +takes_value "Update list URL (default: the region's list)")
+        (@arg db: -d --db +takes_value
+            "Firmware database (TOML) to verify against (default: from the configuration)")
+        (@arg unverified: --unverified
+            "Accepts the download without a firmware database, checking only the update list")
+    )
+}
+
+pub fn execute(args: &clap::ArgMatches, config: &crate::Config) -> Result<(), crate::Error> {
+    let version = args.value_of("version").unwrap();
+    let output = Path::new(args.value_of("output").unwrap());
+    let region = args.value_of("region").unwrap_or("us");
+
+    // The database is read up front, so that a missing one doesn't waste a download.
+    let db = match args
+        .value_of_os("db")
+        .map(Path::new)
+        .or(config.db.as_deref())
+    {
+        Some(db_path) => Some(super::check_official::read_db_from_path(db_path)?),
+        None if args.is_present("unverified") => None,
+        None => {
+            return Err(
+                "no firmware database to verify the download against (pupper doesn't \
+                 ship one); pass one with -d, set 'db' in the configuration, or pass \
+                 --unverified to check only the update list"
+                    .into(),
+            );
+        }
+    };
+
+    let list_url = args.value_of("list_url").map_or_else(
+        || {
+            format!(
+                "http://d{0}01.ps3.update.playstation.net/update/ps3/list/{0}/ps3-updatelist.txt",
+                region
+            )
+        },
+        Into::into,
+    );
+    let list = super::net::get(&list_url, None)?;
+    let list = UpdateList::parse(&String::from_utf8_lossy(&list));
+    let image = list
+        .image()
+        .ok_or_else(|| format!("update list at '{}' doesn't offer an image", list_url))?;
+    let offered_version = image.version().unwrap();
+    let url = image.url.as_deref().unwrap();
+
+    // The servers only ever offer the latest version.
+    if list.find(version) != Some(image) {
+        return Err(format!(
+            "version {} isn't available; the server offers {}",
+            version, offered_version
+        )
+        .into());
+    }
+
+    // Downloads are kept next to the output until they check out, so an interrupted one resumes
+    // where it left off.
+    let mut part_path = output.as_os_str().to_owned();
+    part_path.push(".part");
+    let part_path = PathBuf::from(part_path);
+
+    println!("downloading '{}'", url);
+    let data = super::net::download(url, &part_path)?;
+
+    // Nothing is written until the download checks out, so a corrupt package can't end up on an
+    // update stick by accident.
+    if let Err(err) = verify(&data, url, &list, db.as_ref()) {
+        // Resuming a corrupt download would only reproduce it.
+        let _ = fs::remove_file(&part_path);
+        return Err(err);
+    }
+
+    super::write_data_to_path(&data, output)?;
+    fs::remove_file(&part_path)
+        .map_err(|err| format!("failed to remove '{}': {}", part_path.display(), err).into())
+}
+
+fn verify(
+    data: &[u8],
+    url: &str,
+    list: &UpdateList,
+    db: Option<&FirmwareDb>,
+) -> Result<(), crate::Error> {
+    let pup = super::parse_pup(
+        data,
+        Path::new(url),
+        &mut ParseOptions {
+            validation: ValidationLevel::Strict,
+            ..Default::default()
+        },
+    )?;
+    list.check(&pup, data)
+        .map_err(|err| format!("download doesn't match the update list: {}", err))?;
+
+    match db {
+        Some(db) => {
+            let release = db
+                .find(data)
+                .ok_or("download doesn't match any release in the firmware database")?;
+
+            println!("matches {}", release);
+        }
+        None => println!(
+            "{}: download wasn't checked against a firmware database (SHA-1 is {})",
+            super::color::paint("warning", super::color::YELLOW),
+            Digest::sha1_of(data)
+        ),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use pupper::{Pup, Segment};
+
+use serde_json::Value;
+
+use std::path::Path;
+
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let json_path = Path::new(args.value_of("json").unwrap());
+    let json: Value = super::read_data_from_path(json_path).and_then(|x| {
+        serde_json::from_slice(&x).map_err(|err| {
+            format!("failed to parse JSON at '{}': {}", json_path.display(), err).into()
+        })
+    })?;
+
+    // Data paths are relative to the JSON file, not the working directory.
+    let base_path = json_path.parent().unwrap_or_else(|| Path::new(""));
+    let pup = parse_pup(&json, base_path)?;
+
+    super::write_pup_to_path(&pup, path)
+}
+
+fn parse_pup(json: &Value, base_path: &Path) -> Result<Pup, crate::Error> {
+    let image_version = json
+        .get("image-version")
+        .and_then(Value::as_u64)
+        .ok_or("'image-version' is missing or invalid")?;
+
+    let segments = json
+        .get("segments")
+        .and_then(Value::as_array)
+        .ok_or("'segments' is missing or invalid")?
+        .iter()
+        .enumerate()
+        .map(|(i, seg)| parse_segment(i, seg, base_path))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Pup::new(segments, image_version))
+}
+
+fn parse_segment(i: usize, json: &Value, base_path: &Path) -> Result<Segment, crate::Error> {
+    let field = |name: &str| {
+        json.get(name)
+            .map(|x| {
+                x.as_str()
+                    .ok_or(format!("'{}' of segment {} is invalid", name, i))
+            })
+            .transpose()
+    };
+
+    let id = json
+        .get("id")
+        .and_then(super::segment_id_from_json)
+        .ok_or_else(|| format!("'id' of segment {} is missing or invalid", i))?;
+
+    // Embedded data takes precedence over data stored in a separate file.
+    let data = if let Some(data) = field("data")? {
+        base64::decode(data)
+            .map_err(|err| format!("failed to decode data of segment {}: {}", i, err))?
+    } else if let Some(data_path) = field("data-path")? {
+        super::read_data_from_path(&base_path.join(data_path))?
+    } else {
+        return Err(format!("segment {} has neither 'data' nor 'data-path'", i).into());
+    };
+
+    let mut seg = Segment::new(id, data);
+
+    // Without a signature, the data was probably edited by hand, so we sign it afresh.
+    match field("signature")? {
+        Some(sig) => seg.set_signature(sig.parse()?),
+        None => seg.sign(),
+    }
+
+    Ok(seg)
+}
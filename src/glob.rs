@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Expands a path pattern into the files it matches, sorted by path.
+///
+/// `*` matches any part of a file name, `?` matches exactly one character, and `**` matches any
+/// number of directories. Paths without wildcards are returned as-is, whether or not they exist.
+pub fn expand(pattern: &str) -> Result<Vec<PathBuf>, crate::Error> {
+    // URLs may contain '?', but they're never patterns.
+    if !is_pattern(pattern) || pattern.contains("://") {
+        return Ok(vec![pattern.into()]);
+    }
+
+    // Everything before the first wildcard is taken literally.
+    let mut base = PathBuf::new();
+    let mut components = Vec::new();
+    for component in Path::new(pattern).components() {
+        match component {
+            Component::Normal(x) if is_pattern(&x.to_string_lossy()) || !components.is_empty() => {
+                components.push(x.to_string_lossy().into_owned());
+            }
+            _ => base.push(component),
+        }
+    }
+
+    // A trailing '**' is shorthand for every file below it.
+    if components.last().map(String::as_str) == Some("**") {
+        components.push("*".into());
+    }
+
+    let mut paths = Vec::new();
+    walk(&base, &components, &mut paths);
+    paths.sort();
+
+    if paths.is_empty() {
+        Err(format!("no files match '{}'", pattern).into())
+    } else {
+        Ok(paths)
+    }
+}
+
+fn is_pattern(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+fn walk(path: &Path, components: &[String], paths: &mut Vec<PathBuf>) {
+    let (first, rest) = match components.split_first() {
+        Some(x) => x,
+        None => {
+            if path.is_file() {
+                paths.push(path.into());
+            }
+
+            return;
+        }
+    };
+
+    // An empty base path means the working directory, but we don't want to prefix results with
+    // './'.
+    let entries = match fs::read_dir(if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    }) {
+        Ok(x) => x.flat_map(Result::ok),
+        // Unreadable directories simply match nothing.
+        Err(_) => return,
+    };
+
+    if first == "**" {
+        // Zero directories...
+        walk(path, rest, paths);
+
+        // ...or one more.
+        for entry in entries {
+            if entry.file_type().is_ok_and(|x| x.is_dir()) {
+                walk(&path.join(entry.file_name()), components, paths);
+            }
+        }
+    } else {
+        for entry in entries {
+            let name: Vec<char> = entry.file_name().to_string_lossy().chars().collect();
+
+            if matches(&first.chars().collect::<Vec<_>>(), &name) {
+                walk(&path.join(entry.file_name()), rest, paths);
+            }
+        }
+    }
+}
+
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some(('*', pattern_rest)), _) => {
+            // Either the star matches nothing, or it eats one more character.
+            matches(pattern_rest, name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some(('?', pattern_rest)), Some((_, name_rest))) => matches(pattern_rest, name_rest),
+        (Some((x, pattern_rest)), Some((y, name_rest))) => {
+            x == y && matches(pattern_rest, name_rest)
+        }
+        _ => false,
+    }
+}
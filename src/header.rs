@@ -0,0 +1,122 @@
+use pupper::{
+    Digest, DigestEntry, FixedSize as _, HeaderMetadata, PupVisitor, RawHeader, SegmentEntry,
+};
+
+use std::{
+    convert::{TryFrom as _, TryInto as _},
+    io::{self, Write as _},
+    path::Path,
+};
+
+// Both tables have 0x20-byte entries.
+const ENTRY_SIZE: u64 = 0x20;
+
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    // Only the header is needed, so no segment data are downloaded.
+    let data = super::read_pup_data(path, |_| false)?;
+    let header = RawHeader::try_from(data.as_slice()).map_err(|err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    })?;
+
+    if args.is_present("raw") {
+        return write_raw(&data, &header, args.value_of("output").map(Path::new));
+    }
+
+    // Parsing succeeded, so the magic and package version are there.
+    println!(
+        "0x0000 magic:           {:?}",
+        String::from_utf8_lossy(&data[0x00..0x08])
+    );
+    println!(
+        "0x0008 package version: {}",
+        u64::from_be_bytes(data[0x08..0x10].try_into().unwrap())
+    );
+
+    header.walk(&mut Printer {
+        segment_count: header.segment_entries.len() as u64,
+    });
+
+    Ok(())
+}
+
+// Writes the header bytes, padding included, as they are in the file.
+fn write_raw(
+    data: &[u8],
+    header: &RawHeader,
+    output_path: Option<&Path>,
+) -> Result<(), crate::Error> {
+    let header_data = usize::try_from(header.metadata.header_size)
+        .ok()
+        .and_then(|x| data.get(..x))
+        .ok_or("header is truncated")?;
+
+    match output_path {
+        Some(output_path) => super::write_data_to_path(header_data, output_path),
+        None => {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(header_data)
+                .and_then(|_| stdout.flush())
+                .map_err(|err| format!("failed to write to stdout: {}", err).into())
+        }
+    }
+}
+
+struct Printer {
+    segment_count: u64,
+}
+
+impl Printer {
+    fn digest_table_offset(&self) -> u64 {
+        (HeaderMetadata::SIZE as u64) + (self.segment_count * ENTRY_SIZE)
+    }
+}
+
+impl PupVisitor for Printer {
+    fn visit_metadata(&mut self, meta: &HeaderMetadata) {
+        println!("0x0010 image version:   {:#x}", meta.image_version);
+        println!("0x0018 segment count:   {}", meta.segment_count);
+        println!("0x0020 header size:     {:#x}", meta.header_size);
+        println!("0x0028 data size:       {:#x}", meta.data_size);
+        println!();
+        println!("segment table:");
+    }
+
+    fn visit_segment_entry(&mut self, index: u64, entry: &SegmentEntry) {
+        println!(
+            "{:#06x} [{}] ID: {:#06x}, offset: {:#x}, size: {:#x}, signature kind: {}",
+            (HeaderMetadata::SIZE as u64) + (index * ENTRY_SIZE),
+            index,
+            entry.id.0,
+            entry.offset,
+            entry.size,
+            entry.signature_kind,
+        );
+    }
+
+    fn visit_digest_entry(&mut self, index: u64, entry: &DigestEntry) {
+        if index == 0 {
+            println!();
+            println!("digest table:");
+        }
+
+        println!(
+            "{:#06x} [{}] segment index: {}, digest: {}",
+            self.digest_table_offset() + (index * ENTRY_SIZE),
+            index,
+            entry.segment_index,
+            entry.digest,
+        );
+    }
+
+    fn visit_header_signature(&mut self, sig: &Digest) {
+        println!();
+        println!(
+            "{:#06x} header signature: {}",
+            self.digest_table_offset() + (self.segment_count * ENTRY_SIZE),
+            sig
+        );
+    }
+}
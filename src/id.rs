@@ -0,0 +1,27 @@
+use pupper::SegmentNameMap;
+
+pub fn execute(args: &clap::ArgMatches, names: &SegmentNameMap) -> Result<(), crate::Error> {
+    if args.is_present("list") {
+        let mut entries: Vec<_> = names.iter().collect();
+        entries.sort_by_key(|(id, _)| id.0);
+
+        for (id, file_name) in entries {
+            println!("{:#06x} {}", id.0, file_name);
+        }
+
+        return Ok(());
+    }
+
+    let segment = args.value_of("segment").unwrap();
+    let id = super::parse_segment(segment, names)?;
+
+    match names.name_of(id) {
+        // A file name translates to its ID...
+        Some(file_name) if file_name == segment => println!("{:#06x}", id.0),
+        // ...and an ID to its file name.
+        Some(file_name) => println!("{}", file_name),
+        None => return Err(format!("segment ID '{:#x}' has no known file name", id.0).into()),
+    }
+
+    Ok(())
+}
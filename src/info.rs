@@ -0,0 +1,34 @@
+pub fn execute(path: &std::path::Path) -> Result<(), crate::Error> {
+    let info = super::read_pup_from_path(path)?.build_info();
+
+    println!(
+        "version:         {}",
+        info.version.as_deref().unwrap_or("?")
+    );
+    println!(
+        "patch build:     {}",
+        info.patch_build.as_deref().unwrap_or("?")
+    );
+    println!(
+        "updater version: {}",
+        info.updater_version
+            .map_or_else(|| "?".to_string(), |x| format!("{:#018x}", x))
+    );
+    println!(
+        "updater type:    {}",
+        info.updater.map_or_else(
+            || "?".to_string(),
+            |x| format!(
+                "{} ({} keys)",
+                x.self_type,
+                if x.is_debug() { "debug" } else { "retail" }
+            )
+        )
+    );
+    println!(
+        "build date:      {}",
+        info.build_date.as_deref().unwrap_or("?")
+    );
+
+    Ok(())
+}
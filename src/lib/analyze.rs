@@ -0,0 +1,65 @@
+use crate::{Pup, SegmentId};
+
+impl Pup {
+    /// Computes the byte distribution of the data of each segment.
+    ///
+    /// Encrypted and compressed data have an entropy close to 8 bits per byte, while plain text
+    /// and code are usually well below that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+    /// pup.segments.push(Segment::new(SegmentId(0x101), (0..=255).collect()));
+    ///
+    /// let analyses = pup.analyze();
+    ///
+    /// assert_eq!(0.0, analyses[0].entropy);
+    /// assert_eq!(8.0, analyses[1].entropy);
+    /// assert_eq!(0x100, analyses[0].histogram[0]);
+    /// ```
+    #[must_use]
+    pub fn analyze(&self) -> Vec<SegmentAnalysis> {
+        self.segments
+            .iter()
+            .map(|seg| {
+                let mut histogram = [0; 0x100];
+                for x in seg.data().iter() {
+                    histogram[*x as usize] += 1;
+                }
+
+                SegmentAnalysis {
+                    id: seg.id,
+                    entropy: entropy(&histogram, seg.data().len() as u64),
+                    histogram,
+                }
+            })
+            .collect()
+    }
+}
+
+fn entropy(histogram: &[u64; 0x100], total: u64) -> f64 {
+    histogram
+        .iter()
+        .filter(|x| **x != 0)
+        .map(|x| {
+            let p = (*x as f64) / (total as f64);
+
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+/// The byte distribution of a single [`Segment`](crate::Segment), returned by [`Pup::analyze`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SegmentAnalysis {
+    /// The ID of the segment.
+    pub id: SegmentId,
+    /// The Shannon entropy of the segment data, from 0 to 8 bits per byte.
+    pub entropy: f64,
+    /// The number of occurrences of each byte value.
+    pub histogram: [u64; 0x100],
+}
@@ -0,0 +1,336 @@
+use crate::{content::tar, FirmwareDb, FirmwareVersion, Pup, SegmentId};
+
+use std::{
+    convert::TryInto as _,
+    fmt::{self, Display, Formatter},
+};
+
+impl Pup {
+    /// Gathers the build metadata scattered across the well-known segments of this [`Pup`].
+    ///
+    /// Each field is [`None`] if the segment it comes from is missing or unrecognizable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    ///
+    /// let info = pup.build_info();
+    ///
+    /// assert_eq!(Some("4.88"), info.version.as_deref());
+    /// assert_eq!(None, info.patch_build);
+    /// ```
+    #[must_use]
+    pub fn build_info(&self) -> BuildInfo {
+        let text_of = |id| {
+            self.segments
+                .iter()
+                .find(|x| x.id == SegmentId(id))
+                .map(|x| String::from_utf8_lossy(x.data()).trim().to_string())
+                .filter(|x| !x.is_empty())
+        };
+        let data_of = |id| {
+            self.segments
+                .iter()
+                .find(|x| x.id == SegmentId(id))
+                .map(|x| x.data())
+        };
+
+        let updater = data_of(0x200).and_then(SelfInfo::parse);
+
+        BuildInfo {
+            version: text_of(0x100),
+            patch_build: text_of(0x104),
+            updater_version: updater.as_ref().map(|x| x.version),
+            updater,
+            build_date: data_of(0x300).and_then(build_date),
+        }
+    }
+
+    /// Cross-checks the version named in `version.txt` against the image version, returning the
+    /// first disagreement.
+    ///
+    /// Image versions can't be derived from the version alone, so without a firmware database,
+    /// only an unset (i.e., zero) image version is reported. With one, the image version must
+    /// match a release of the same version, if the database knows any. Nothing is reported if
+    /// `version.txt` is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{ImageVersionMismatch, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::new(Vec::new(), 0);
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    ///
+    /// assert_eq!(
+    ///     Some(ImageVersionMismatch::Unset {
+    ///         version: "4.88".into(),
+    ///     }),
+    ///     pup.check_image_version(None),
+    /// );
+    ///
+    /// pup.image_version = 0x1234;
+    /// assert_eq!(None, pup.check_image_version(None));
+    ///
+    /// pup.segments[0].set_data(b"release".to_vec());
+    /// assert_eq!(
+    ///     Some(ImageVersionMismatch::UnreadableVersion("release".into())),
+    ///     pup.check_image_version(None),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn check_image_version(&self, db: Option<&FirmwareDb>) -> Option<ImageVersionMismatch> {
+        let version = self.build_info().version?;
+        let parsed = match version.parse::<FirmwareVersion>() {
+            Ok(x) => x,
+            Err(_) => return Some(ImageVersionMismatch::UnreadableVersion(version)),
+        };
+        if self.image_version == 0 {
+            return Some(ImageVersionMismatch::Unset { version });
+        }
+
+        let expected: Vec<u64> = db?
+            .iter()
+            .filter(|x| x.version.parse() == Ok(parsed))
+            .map(|x| x.image_version)
+            .collect();
+        if expected.is_empty() || expected.contains(&self.image_version) {
+            return None;
+        }
+
+        Some(ImageVersionMismatch::Unreleased {
+            version,
+            actual: self.image_version,
+            expected,
+        })
+    }
+}
+
+/// A disagreement between `version.txt` and the image version of a [`Pup`], as found by
+/// [`Pup::check_image_version`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ImageVersionMismatch {
+    /// `version.txt` doesn't hold a [`FirmwareVersion`] (e.g., `4.88`).
+    UnreadableVersion(String),
+    /// The image version is unset, though `version.txt` names a version.
+    Unset {
+        /// The version named in `version.txt`.
+        version: String,
+    },
+    /// No release of the version named in `version.txt` has this image version.
+    Unreleased {
+        /// The version named in `version.txt`.
+        version: String,
+        /// The image version of the [`Pup`].
+        actual: u64,
+        /// The image versions of the releases of that version.
+        expected: Vec<u64>,
+    },
+}
+
+impl Display for ImageVersionMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnreadableVersion(version) => {
+                write!(f, "version.txt holds '{}', which isn't a version", version)
+            }
+            Self::Unset { version } => {
+                write!(f, "image version is 0, but version.txt names {}", version)
+            }
+            Self::Unreleased {
+                version,
+                actual,
+                expected,
+            } => write!(
+                f,
+                "image version is {}, but releases of {} have image version {}",
+                actual,
+                version,
+                expected
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+        }
+    }
+}
+
+/// Build metadata of a [`Pup`], as gathered by [`Pup::build_info`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BuildInfo {
+    /// The system software version, from `version.txt` (e.g., `4.88`).
+    pub version: Option<String>,
+    /// The patch build, from `patch_build.txt`.
+    pub patch_build: Option<String>,
+    /// The version recorded in the SELF header of `ps3swu.self` (e.g., `0x0004_0088_0000_0000`
+    /// for 4.88).
+    pub updater_version: Option<u64>,
+    /// The SELF header of `ps3swu.self`, the updater executable.
+    pub updater: Option<SelfInfo>,
+    /// The build date and time embedded in the member names of `update_files.tar`, formatted as
+    /// `YYYY_MM_DD_HHMMSS`.
+    pub build_date: Option<String>,
+}
+
+impl BuildInfo {
+    /// The system software version, parsed from [`Self::version`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{FirmwareVersion, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    ///
+    /// let installed = FirmwareVersion::new(4, 87);
+    /// let version = pup.build_info().firmware_version().unwrap();
+    ///
+    /// assert!(version.is_newer_than(&installed));
+    /// ```
+    #[must_use]
+    pub fn firmware_version(&self) -> Option<FirmwareVersion> {
+        self.version.as_deref()?.parse().ok()
+    }
+}
+
+/// Fields of the header of a SELF (signed ELF), as found in [`BuildInfo::updater`].
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, Segment, SegmentId, SelfType};
+///
+/// let mut data = vec![0; 0x90];
+/// data[..4].copy_from_slice(b"SCE\0");
+/// data[0x08..0x0A].copy_from_slice(&0x8000u16.to_be_bytes());
+/// // The application info is at 0x70.
+/// data[0x28..0x30].copy_from_slice(&0x70u64.to_be_bytes());
+/// data[0x7C..0x80].copy_from_slice(&4u32.to_be_bytes());
+/// data[0x80..0x88].copy_from_slice(&0x0004_0088_0000_0000u64.to_be_bytes());
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x200), data));
+///
+/// let updater = pup.build_info().updater.unwrap();
+///
+/// assert_eq!(SelfType::Application, updater.self_type);
+/// assert_eq!(0x0004_0088_0000_0000, updater.version);
+/// assert!(updater.is_debug());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SelfInfo {
+    /// The key revision of the SCE header, which selects the keys the SELF is signed with.
+    pub key_revision: u16,
+    /// The authentication ID, which identifies the program to the hypervisor.
+    pub auth_id: u64,
+    /// The vendor ID.
+    pub vendor_id: u32,
+    /// The kind of program.
+    pub self_type: SelfType,
+    /// The application version (e.g., `0x0004_0088_0000_0000` for 4.88).
+    pub version: u64,
+}
+
+impl SelfInfo {
+    /// The key revision of SELFs signed with debug keys.
+    pub const DEBUG_KEY_REVISION: u16 = 0x8000;
+
+    /// Whether the SELF is signed with debug keys, in which case only development consoles run
+    /// it.
+    #[must_use]
+    pub fn is_debug(&self) -> bool {
+        self.key_revision == Self::DEBUG_KEY_REVISION
+    }
+
+    // The application info follows the 0x20-byte SCE header at the offset given by the extended
+    // header, after its 8-byte version.
+    fn parse(data: &[u8]) -> Option<Self> {
+        if !data.starts_with(b"SCE\0") {
+            return None;
+        }
+
+        let read = |offset: usize, len: usize| data.get(offset..offset.checked_add(len)?);
+        let read_u32 = |offset| read(offset, 4).map(|x| u32::from_be_bytes(x.try_into().unwrap()));
+        let read_u64 = |offset| read(offset, 8).map(|x| u64::from_be_bytes(x.try_into().unwrap()));
+        let app_info_offset: usize = read_u64(0x28)?.try_into().ok()?;
+
+        Some(Self {
+            key_revision: read(0x08, 2).map(|x| u16::from_be_bytes(x.try_into().unwrap()))?,
+            auth_id: read_u64(app_info_offset)?,
+            vendor_id: read_u32(app_info_offset.checked_add(0x08)?)?,
+            self_type: read_u32(app_info_offset.checked_add(0x0C)?)?.into(),
+            version: read_u64(app_info_offset.checked_add(0x10)?)?,
+        })
+    }
+}
+
+/// The kind of program in a SELF, from its application info.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SelfType {
+    /// A level 0 loader.
+    Lv0,
+    /// The hypervisor.
+    Lv1,
+    /// The GameOS kernel.
+    Lv2,
+    /// An application, such as the updater.
+    Application,
+    /// An isolated SPU module.
+    IsolatedSpuModule,
+    /// A secure loader.
+    SecureLoader,
+    /// An NPDRM application.
+    Npdrm,
+    /// A type unknown to this crate.
+    Unknown(u32),
+}
+
+impl From<u32> for SelfType {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Lv0,
+            2 => Self::Lv1,
+            3 => Self::Lv2,
+            4 => Self::Application,
+            5 => Self::IsolatedSpuModule,
+            6 => Self::SecureLoader,
+            8 => Self::Npdrm,
+            x => Self::Unknown(x),
+        }
+    }
+}
+
+impl Display for SelfType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Lv0 => write!(f, "lv0"),
+            Self::Lv1 => write!(f, "lv1"),
+            Self::Lv2 => write!(f, "lv2"),
+            Self::Application => write!(f, "application"),
+            Self::IsolatedSpuModule => write!(f, "isolated SPU module"),
+            Self::SecureLoader => write!(f, "secure loader"),
+            Self::Npdrm => write!(f, "NPDRM application"),
+            Self::Unknown(x) => write!(f, "unknown ({:#x})", x),
+        }
+    }
+}
+
+// Official packages name the parts of dev_flash like 'dev_flash_000.tar.aa.2010_11_27_051337'.
+fn build_date(data: &[u8]) -> Option<String> {
+    tar::members(data).ok()?.iter().find_map(|x| {
+        let date = x.name.rsplit('.').next()?;
+        let is_date = date.len() == 17
+            && date.bytes().enumerate().all(|(i, x)| match i {
+                4 | 7 | 10 => x == b'_',
+                _ => x.is_ascii_digit(),
+            });
+
+        Some(date.to_string()).filter(|_| is_date)
+    })
+}
@@ -0,0 +1,474 @@
+//! Formats found inside PUP segments.
+//!
+//! [`identify`] recognizes and parses the contents of a file, and [`extract`] descends into a
+//! segment as far as this module understands its contents (e.g., the members of
+//! `update_files.tar` and the components of `CORE_OS_PACKAGE.pkg`). Some of those contents are
+//! encrypted packages; this crate doesn't ship any keys, but a [`PackageDecryptor`] can be
+//! supplied to descend into them too.
+//!
+//! Other formats can be plugged in as [`ContentHandler`]s, which [`extract_with`] consults first.
+
+pub mod core_os;
+mod handler;
+pub mod revoke;
+pub mod tar;
+
+pub use handler::{ContentHandler, HandlerRegistry};
+
+use crate::{Pup, Segment};
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// The magic of an encrypted SCE container (e.g., a `.pkg` or SELF).
+const SCE_MAGIC: &[u8; 4] = b"SCE\0";
+
+/// The kind of data in a file, as guessed by [`sniff`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ContentType {
+    /// A tar archive.
+    Tar,
+    /// A signed ELF (i.e., an SCE container holding an executable).
+    SignedElf,
+    /// A package (i.e., an SCE container holding a `.pkg`).
+    Package,
+    /// Any other SCE container (e.g., a revoke list).
+    Sce,
+    /// An XML document.
+    Xml,
+    /// Plain text.
+    Text,
+    /// Anything else.
+    Unknown,
+}
+
+impl ContentType {
+    /// The conventional file extension for this type of content, if any.
+    #[must_use]
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::Tar => Some("tar"),
+            Self::SignedElf => Some("self"),
+            Self::Package => Some("pkg"),
+            Self::Xml => Some("xml"),
+            Self::Text => Some("txt"),
+            Self::Sce | Self::Unknown => None,
+        }
+    }
+}
+
+impl Display for ContentType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Tar => write!(f, "tar"),
+            Self::SignedElf => write!(f, "SELF"),
+            Self::Package => write!(f, "package"),
+            Self::Sce => write!(f, "SCE"),
+            Self::Xml => write!(f, "XML"),
+            Self::Text => write!(f, "text"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Guesses the kind of data in a file from its first bytes, regardless of its name or segment
+/// ID.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::{self, ContentType};
+///
+/// assert_eq!(ContentType::Text, content::sniff(b"4.88\n"));
+/// assert_eq!(ContentType::Xml, content::sniff(b"<?xml version=\"1.0\"?>"));
+/// assert_eq!(ContentType::SignedElf, content::sniff(b"SCE\0\0\0\0\x02\0\0\0\x01"));
+/// assert_eq!(ContentType::Unknown, content::sniff(&[0xFF; 0x10]));
+/// ```
+#[must_use]
+pub fn sniff(data: &[u8]) -> ContentType {
+    /// How much of the data is checked to be text.
+    const TEXT_SNIFF_SIZE: usize = 0x200;
+
+    if data.starts_with(SCE_MAGIC) {
+        // The header type follows the magic, the SCE version, and the key revision.
+        return match data.get(0x0A..0x0C) {
+            Some([0, 1]) => ContentType::SignedElf,
+            Some([0, 3]) => ContentType::Package,
+            _ => ContentType::Sce,
+        };
+    }
+    if tar::is_tar(data) {
+        return ContentType::Tar;
+    }
+
+    // Skip a UTF-8 BOM, if any.
+    let text = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let text = &text[..text.len().min(TEXT_SNIFF_SIZE)];
+    // The cut-off may fall in the middle of a character.
+    let valid = match std::str::from_utf8(text) {
+        Ok(x) => x,
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&text[..err.valid_up_to()]).unwrap()
+        }
+        Err(_) => "",
+    };
+    let is_text = !valid.is_empty() && valid.chars().all(|x| !x.is_control() || x.is_whitespace());
+
+    if !is_text {
+        ContentType::Unknown
+    } else if valid.trim_start().starts_with("<?xml") {
+        ContentType::Xml
+    } else {
+        ContentType::Text
+    }
+}
+
+impl Segment {
+    /// The conventional file extension for the data of this segment, as guessed by [`sniff`], if
+    /// any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Segment, SegmentId};
+    ///
+    /// let text = Segment::new(SegmentId(0x9999), b"4.88\n".to_vec());
+    /// let unknown = Segment::new(SegmentId(0x9999), vec![0xFF; 0x10]);
+    ///
+    /// assert_eq!(Some("txt"), text.suggested_extension());
+    /// assert_eq!(None, unknown.suggested_extension());
+    /// ```
+    #[must_use]
+    pub fn suggested_extension(&self) -> Option<&'static str> {
+        sniff(self.data()).extension()
+    }
+}
+
+impl Pup {
+    /// Groups the segments of this [`Pup`] by the kind of their data, as guessed by [`sniff`].
+    ///
+    /// Within each group, segments keep their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{content::ContentType, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x101), b"1\n".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x102), vec![0xFF; 4]));
+    ///
+    /// let groups = pup.segments_by_kind();
+    /// let ids: Vec<_> = groups[&ContentType::Text].iter().map(|x| x.id.0).collect();
+    ///
+    /// assert_eq!(vec![0x100, 0x101], ids);
+    /// assert_eq!(1, groups[&ContentType::Unknown].len());
+    /// assert!(!groups.contains_key(&ContentType::Tar));
+    /// ```
+    #[must_use]
+    pub fn segments_by_kind(&self) -> BTreeMap<ContentType, Vec<&Segment>> {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for seg in self.segments.iter() {
+            groups.entry(sniff(seg.data())).or_default().push(seg);
+        }
+
+        groups
+    }
+
+    /// Partitions the segments of this [`Pup`] by the kind of their data, for bulk transformations
+    /// alongside [`Self::retain_segments`] and [`Self::map_segments`].
+    ///
+    /// This is the same as [`Self::segments_by_kind`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{content::ContentType, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x102), vec![0xFF; 4]));
+    ///
+    /// let text: Vec<_> = pup.partition_by_kind()[&ContentType::Text]
+    ///     .iter()
+    ///     .map(|x| x.id)
+    ///     .collect();
+    /// pup.retain_segments(|x| !text.contains(&x.id));
+    ///
+    /// assert_eq!(SegmentId(0x102), pup.segments[0].id);
+    /// assert_eq!(1, pup.segments.len());
+    /// ```
+    #[must_use]
+    pub fn partition_by_kind(&self) -> BTreeMap<ContentType, Vec<&Segment>> {
+        self.segments_by_kind()
+    }
+
+    /// The first segment whose data are of a specific kind, as guessed by [`sniff`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{content::ContentType, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    ///
+    /// assert_eq!(Some(SegmentId(0x100)), pup.first_of(ContentType::Text).map(|x| x.id));
+    /// assert!(pup.first_of(ContentType::SignedElf).is_none());
+    /// ```
+    #[must_use]
+    pub fn first_of(&self, kind: ContentType) -> Option<&Segment> {
+        self.segments.iter().find(|x| sniff(x.data()) == kind)
+    }
+}
+
+/// Decrypts the encrypted packages found while extracting.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::{self, PackageDecryptor};
+///
+/// struct StripMagic;
+///
+/// impl PackageDecryptor for StripMagic {
+///     fn decrypt(&self, _: &str, data: &[u8]) -> Option<Vec<u8>> {
+///         Some(data[4..].to_vec())
+///     }
+/// }
+///
+/// let node = content::extract("CORE_OS_PACKAGE.pkg", b"SCE\0foo", &StripMagic);
+///
+/// assert!(node.encrypted);
+/// assert_eq!(b"foo", &*node.data);
+/// ```
+pub trait PackageDecryptor {
+    /// Decrypts a package with the given name, returning [`None`] if that isn't possible.
+    fn decrypt(&self, name: &str, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// A [`PackageDecryptor`] that never decrypts anything.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NoDecryptor;
+
+impl PackageDecryptor for NoDecryptor {
+    fn decrypt(&self, _: &str, _: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The parsed contents of a file, as recognized by [`identify`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Content<'a> {
+    /// A tar archive (e.g., `update_files.tar`).
+    Tar(Vec<tar::Member<'a>>),
+    /// A decrypted `CORE_OS_PACKAGE.pkg`.
+    CoreOs(Vec<core_os::Entry<'a>>),
+    /// A decrypted revoke list.
+    RevokeList(revoke::RevokeList),
+}
+
+impl<'a> Content<'a> {
+    /// The files inside this one, as name-data pairs.
+    #[must_use]
+    pub fn files(&self) -> Vec<(&str, &'a [u8])> {
+        match self {
+            Self::Tar(members) => members.iter().map(|x| (x.name.as_str(), x.data)).collect(),
+            Self::CoreOs(entries) => entries.iter().map(|x| (x.name.as_str(), x.data)).collect(),
+            Self::RevokeList(_) => Vec::new(),
+        }
+    }
+}
+
+/// Recognizes and parses the contents of a file with the given name, if possible.
+///
+/// Encrypted files aren't recognized; decrypt them first.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::{self, Content};
+///
+/// let mut data = vec![0; 0x20];
+/// data[0x08..0x10].copy_from_slice(&42u64.to_be_bytes());
+///
+/// match content::identify("RL_FOR_PROGRAM.img", &data) {
+///     Some(Content::RevokeList(list)) => assert_eq!(42, list.version),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[must_use]
+pub fn identify<'a>(name: &str, data: &'a [u8]) -> Option<Content<'a>> {
+    if tar::is_tar(data) {
+        tar::members(data).ok().map(Content::Tar)
+    } else if name.ends_with(core_os::FILE_NAME) {
+        core_os::entries(data).ok().map(Content::CoreOs)
+    } else if name.ends_with(revoke::PACKAGE_FILE_NAME) || name.ends_with(revoke::PROGRAM_FILE_NAME)
+    {
+        revoke::RevokeList::parse(data)
+            .ok()
+            .map(Content::RevokeList)
+    } else {
+        None
+    }
+}
+
+/// A file found by [`extract`], along with whatever was found inside it.
+///
+/// Files are borrowed from the extracted data where possible; only decrypted files, the files
+/// a [`ContentHandler`] finds, and whatever is inside them are owned.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Node<'a> {
+    /// The name of the file.
+    pub name: String,
+    /// The contents of the file, decrypted if it was encrypted and could be decrypted.
+    pub data: Cow<'a, [u8]>,
+    /// Whether the file was encrypted.
+    pub encrypted: bool,
+    /// The description given by the [`ContentHandler`] that recognized the file, if any.
+    pub description: Option<String>,
+    /// The files inside this one.
+    pub children: Vec<Node<'a>>,
+}
+
+impl Node<'_> {
+    /// The parsed contents of this file, if recognized.
+    #[must_use]
+    pub fn content(&self) -> Option<Content<'_>> {
+        identify(&self.name, &self.data)
+    }
+
+    /// Copies whatever this [`Node`] and the files inside it borrow.
+    #[must_use]
+    pub fn into_owned(self) -> Node<'static> {
+        Node {
+            name: self.name,
+            data: Cow::Owned(self.data.into_owned()),
+            encrypted: self.encrypted,
+            description: self.description,
+            children: self.children.into_iter().map(Node::into_owned).collect(),
+        }
+    }
+}
+
+/// Recursively extracts the given data, handing encrypted packages to a [`PackageDecryptor`].
+///
+/// Data that can't be descended into (including encrypted packages that can't be decrypted)
+/// become leaves. So do packages that are still encrypted after decryption, rather than being
+/// decrypted over and over.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::{self, tar, NoDecryptor};
+///
+/// let dir = std::env::temp_dir().join("pupper-extract-example");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("UPL.xz"), b"foo").unwrap();
+///
+/// let data = tar::build_update_files(&dir).unwrap();
+/// let node = content::extract("update_files.tar", &data, &NoDecryptor);
+///
+/// assert_eq!("UPL.xz", node.children[0].name);
+/// ```
+///
+/// A decryptor that passes packages through unchanged doesn't loop forever:
+///
+/// ```
+/// use pupper::content::{self, PackageDecryptor};
+///
+/// struct PassThrough;
+///
+/// impl PackageDecryptor for PassThrough {
+///     fn decrypt(&self, _: &str, data: &[u8]) -> Option<Vec<u8>> {
+///         Some(data.to_vec())
+///     }
+/// }
+///
+/// let node = content::extract("CORE_OS_PACKAGE.pkg", b"SCE\0foo", &PassThrough);
+///
+/// assert!(node.encrypted);
+/// assert!(node.children.is_empty());
+/// ```
+#[must_use]
+pub fn extract<'a>(name: &str, data: &'a [u8], decryptor: &dyn PackageDecryptor) -> Node<'a> {
+    extract_with(name, data, decryptor, &HandlerRegistry::new())
+}
+
+/// Like [`extract`], but consults the given [`ContentHandler`]s before the built-in formats, at
+/// every level.
+#[must_use]
+pub fn extract_with<'a>(
+    name: &str,
+    data: &'a [u8],
+    decryptor: &dyn PackageDecryptor,
+    handlers: &HandlerRegistry,
+) -> Node<'a> {
+    if let Some(handler) = handlers.find(name, data) {
+        // The files are owned by the handler's result, so whatever is inside them is copied.
+        let children = handler
+            .parse(name, data)
+            .unwrap_or_default()
+            .iter()
+            .map(|(name, data)| extract_with(name, data, decryptor, handlers).into_owned())
+            .collect();
+
+        return Node {
+            name: name.to_string(),
+            data: Cow::Borrowed(data),
+            encrypted: false,
+            description: Some(handler.describe(name, data)),
+            children,
+        };
+    }
+
+    if data.starts_with(SCE_MAGIC) {
+        return match decryptor.decrypt(name, data) {
+            Some(plain) if !plain.starts_with(SCE_MAGIC) => {
+                // The files inside are copied out of the decrypted data, which this node then owns.
+                let Node {
+                    description,
+                    children,
+                    ..
+                } = extract_with(name, &plain, decryptor, handlers);
+                let children = children.into_iter().map(Node::into_owned).collect();
+
+                Node {
+                    name: name.to_string(),
+                    data: Cow::Owned(plain),
+                    encrypted: true,
+                    description,
+                    children,
+                }
+            }
+            _ => Node {
+                name: name.to_string(),
+                data: Cow::Borrowed(data),
+                encrypted: true,
+                description: None,
+                children: Vec::new(),
+            },
+        };
+    }
+
+    let children = identify(name, data)
+        .map(|x| {
+            x.files()
+                .into_iter()
+                .map(|(name, data)| extract_with(name, data, decryptor, handlers))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Node {
+        name: name.to_string(),
+        data: Cow::Borrowed(data),
+        encrypted: false,
+        description: None,
+        children,
+    }
+}
@@ -0,0 +1,95 @@
+//! The contents of a decrypted `CORE_OS_PACKAGE.pkg`.
+//!
+//! The package holds the components of the CoreOS (e.g., `lv0`, `lv1.self`, and
+//! `lv2_kernel.self`) behind a header and an entry table:
+//!
+//! | Offset | Size   | Field                                        |
+//! |--------|--------|----------------------------------------------|
+//! | `0x00` | `0x08` | Unknown                                      |
+//! | `0x08` | `0x08` | Number of entries                            |
+//! | `0x10` | `0x08` | Size of the package                          |
+//! | `0x18` | `0x30` | First entry (offset, size, and 32-byte name) |
+//!
+//! All fields are big-endian, and entry offsets are relative to the start of the header.
+
+use std::convert::{TryFrom as _, TryInto as _};
+
+/// The file name of the package within `update_files.tar`.
+pub const FILE_NAME: &str = "CORE_OS_PACKAGE.pkg";
+
+const HEADER_SIZE: usize = 0x18;
+const ENTRY_SIZE: usize = 0x30;
+
+/// A component of the CoreOS.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Entry<'a> {
+    /// The file name of the component.
+    pub name: String,
+    /// The offset of the component from the start of the package.
+    pub offset: u64,
+    /// The component itself.
+    pub data: &'a [u8],
+}
+
+/// Reads the entry table of a decrypted `CORE_OS_PACKAGE.pkg`.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::core_os;
+///
+/// let mut data = Vec::new();
+/// data.extend_from_slice(&1u64.to_be_bytes());
+/// data.extend_from_slice(&1u64.to_be_bytes());
+/// data.extend_from_slice(&0x4Bu64.to_be_bytes());
+/// data.extend_from_slice(&0x48u64.to_be_bytes());
+/// data.extend_from_slice(&3u64.to_be_bytes());
+/// data.extend_from_slice(b"lv0");
+/// data.resize(0x48, 0);
+/// data.extend_from_slice(b"foo");
+///
+/// let entries = core_os::entries(&data).unwrap();
+///
+/// assert_eq!("lv0", entries[0].name);
+/// assert_eq!(b"foo", entries[0].data);
+/// ```
+pub fn entries(data: &[u8]) -> Result<Vec<Entry<'_>>, String> {
+    let header = data
+        .get(..HEADER_SIZE)
+        .ok_or("package is too small for a header")?;
+    let count = read_u64(&header[0x08..0x10]);
+
+    let table_size = usize::try_from(count)
+        .ok()
+        .and_then(|x| x.checked_mul(ENTRY_SIZE))
+        .filter(|x| HEADER_SIZE + x <= data.len())
+        .ok_or_else(|| format!("package is too small for {} entries", count))?;
+
+    data[HEADER_SIZE..HEADER_SIZE + table_size]
+        .chunks(ENTRY_SIZE)
+        .enumerate()
+        .map(|(i, entry)| {
+            let offset = read_u64(&entry[0x00..0x08]);
+            let size = read_u64(&entry[0x08..0x10]);
+            let name = &entry[0x10..0x30];
+            let name = &name[..name.iter().position(|x| *x == 0).unwrap_or(name.len())];
+
+            let range = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(size).ok())
+                .and_then(|(start, size)| Some(start..start.checked_add(size)?))
+                .filter(|x| x.end <= data.len())
+                .ok_or_else(|| format!("entry {} lies outside the package", i))?;
+
+            Ok(Entry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                offset,
+                data: &data[range],
+            })
+        })
+        .collect()
+}
+
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data.try_into().unwrap())
+}
@@ -0,0 +1,114 @@
+use super::Node;
+
+/// Recognizes and parses a kind of content this crate doesn't know about (e.g., a proprietary
+/// debug segment).
+///
+/// Handlers are registered in a [`HandlerRegistry`] and consulted, in registration order, before
+/// the built-in formats by [`super::extract_with`]. Only [`Self::detect`] is required.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::{self, ContentHandler, HandlerRegistry, NoDecryptor};
+///
+/// // Concatenated 'name=value' lines.
+/// struct Ini;
+///
+/// impl ContentHandler for Ini {
+///     fn detect(&self, name: &str, _: &[u8]) -> bool {
+///         name.ends_with(".ini")
+///     }
+///
+///     fn describe(&self, _: &str, data: &[u8]) -> String {
+///         format!("INI with {} entries", data.split(|x| *x == b'\n').count())
+///     }
+///
+///     fn parse(&self, _: &str, data: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+///         data.split(|x| *x == b'\n')
+///             .map(|line| {
+///                 let i = line.iter().position(|x| *x == b'=')?;
+///                 let name = String::from_utf8(line[..i].to_vec()).ok()?;
+///
+///                 Some((name, line[(i + 1)..].to_vec()))
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// let mut handlers = HandlerRegistry::new();
+/// handlers.register(Ini);
+///
+/// let node = content::extract_with("debug.ini", b"a=1\nb=2", &NoDecryptor, &handlers);
+///
+/// assert_eq!(Some("INI with 2 entries".to_string()), node.description);
+/// assert_eq!("b", node.children[1].name);
+/// assert_eq!(b"2", &*node.children[1].data);
+/// ```
+pub trait ContentHandler {
+    /// Whether this handler recognizes the file with the given name and data.
+    fn detect(&self, name: &str, data: &[u8]) -> bool;
+
+    /// A short description of a recognized file, shown in place of the guess of [`super::sniff`].
+    fn describe(&self, _name: &str, _data: &[u8]) -> String {
+        "unknown".into()
+    }
+
+    /// The files inside a recognized file, as name-data pairs, or [`None`] if it's malformed.
+    ///
+    /// Files with no files inside needn't implement this.
+    fn parse(&self, _name: &str, _data: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+        Some(Vec::new())
+    }
+
+    /// Rebuilds a recognized file from the files inside it, as returned by [`Self::parse`], or
+    /// returns [`None`] if that isn't supported.
+    fn rebuild(&self, _name: &str, _files: &[(String, Vec<u8>)]) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The [`ContentHandler`]s to consult while extracting.
+#[derive(Default)]
+pub struct HandlerRegistry(Vec<Box<dyn ContentHandler>>);
+
+impl HandlerRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a handler, which is consulted after those added before it.
+    pub fn register<H: ContentHandler + 'static>(&mut self, handler: H) {
+        self.0.push(Box::new(handler));
+    }
+
+    /// The first handler that recognizes the file with the given name and data.
+    #[must_use]
+    pub fn find(&self, name: &str, data: &[u8]) -> Option<&dyn ContentHandler> {
+        self.0
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|x| x.detect(name, data))
+    }
+
+    /// Rebuilds a file from its children with the handler that recognizes it, if any handler
+    /// does and supports rebuilding.
+    ///
+    /// Children are rebuilt first, so changes to nested files carry over.
+    #[must_use]
+    pub fn rebuild(&self, node: &Node) -> Option<Vec<u8>> {
+        let handler = self.find(&node.name, &node.data)?;
+        let files: Vec<(String, Vec<u8>)> = node
+            .children
+            .iter()
+            .map(|x| {
+                let data = self.rebuild(x).unwrap_or_else(|| x.data.to_vec());
+
+                (x.name.clone(), data)
+            })
+            .collect();
+
+        handler.rebuild(&node.name, &files)
+    }
+}
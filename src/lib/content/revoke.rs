@@ -0,0 +1,104 @@
+//! The revoke lists found in `update_files.tar` (i.e., `RL_FOR_PACKAGE.img` and
+//! `RL_FOR_PROGRAM.img`).
+//!
+//! Both are SCE containers; the lists are read from their decrypted bodies, which consist of a
+//! 0x20-byte header followed by 0x20-byte entries:
+//!
+//! | Offset | Size   | Header field      | Entry field  |
+//! |--------|--------|-------------------|--------------|
+//! | `0x00` | `0x04` | List type         | Target type  |
+//! | `0x04` | `0x04` | Unknown           | Check type   |
+//! | `0x08` | `0x08` | List version      | Version      |
+//! | `0x10` | `0x04` | Number of entries | ID (8 bytes) |
+//! | `0x18` | `0x08` | Padding           | Unknown      |
+//!
+//! All fields are big-endian.
+
+use std::convert::{TryFrom as _, TryInto as _};
+
+/// The file name of the package revoke list within `update_files.tar`.
+pub const PACKAGE_FILE_NAME: &str = "RL_FOR_PACKAGE.img";
+
+/// The file name of the program revoke list within `update_files.tar`.
+pub const PROGRAM_FILE_NAME: &str = "RL_FOR_PROGRAM.img";
+
+const HEADER_SIZE: usize = 0x20;
+const ENTRY_SIZE: usize = 0x20;
+
+/// A list of revoked packages or programs.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::revoke::RevokeList;
+///
+/// let mut data = vec![0; 0x40];
+/// data[0x08..0x10].copy_from_slice(&0x0004_0001_0000_0000u64.to_be_bytes());
+/// data[0x10..0x14].copy_from_slice(&1u32.to_be_bytes());
+/// data[0x30..0x38].copy_from_slice(&0x1070_0000_0000_0001u64.to_be_bytes());
+///
+/// let list = RevokeList::parse(&data).unwrap();
+///
+/// assert_eq!(0x0004_0001_0000_0000, list.version);
+/// assert_eq!(0x1070_0000_0000_0001, list.entries[0].id);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RevokeList {
+    /// The type of the list.
+    pub kind: u32,
+    /// The version of the list, which increases with each revocation.
+    pub version: u64,
+    /// The revoked packages or programs.
+    pub entries: Vec<RevokeEntry>,
+}
+
+impl RevokeList {
+    /// Parses a [`RevokeList`] from the decrypted body of a revoke list file.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let header = data
+            .get(..HEADER_SIZE)
+            .ok_or("revoke list is too small for a header")?;
+        let count = u32::from_be_bytes(header[0x10..0x14].try_into().unwrap());
+
+        let entries = usize::try_from(count)
+            .ok()
+            .and_then(|x| x.checked_mul(ENTRY_SIZE))
+            .and_then(|x| data.get(HEADER_SIZE..HEADER_SIZE.checked_add(x)?))
+            .ok_or_else(|| format!("revoke list is too small for {} entries", count))?
+            .chunks(ENTRY_SIZE)
+            .map(|x| RevokeEntry {
+                kind: read_u32(&x[0x00..0x04]),
+                check_type: read_u32(&x[0x04..0x08]),
+                version: read_u64(&x[0x08..0x10]),
+                id: read_u64(&x[0x10..0x18]),
+            })
+            .collect();
+
+        Ok(Self {
+            kind: read_u32(&header[0x00..0x04]),
+            version: read_u64(&header[0x08..0x10]),
+            entries,
+        })
+    }
+}
+
+/// A revoked package or program in a [`RevokeList`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RevokeEntry {
+    /// The type of the revoked package or program.
+    pub kind: u32,
+    /// How [`Self::version`] is compared against that of the package or program.
+    pub check_type: u32,
+    /// The version being revoked.
+    pub version: u64,
+    /// The ID of the package or program (e.g., a program authority ID).
+    pub id: u64,
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_be_bytes(data.try_into().unwrap())
+}
+
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data.try_into().unwrap())
+}
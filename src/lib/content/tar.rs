@@ -0,0 +1,278 @@
+//! The tar archive held by segment `0x300` (i.e., `update_files.tar`).
+
+use std::{
+    convert::TryFrom as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The size of a tar block.
+const BLOCK_SIZE: usize = 0x200;
+
+/// The size of a tar record; archives are padded to a multiple of it.
+const RECORD_SIZE: usize = 20 * BLOCK_SIZE;
+
+/// A regular file in a tar archive.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Member<'a> {
+    /// The path of the file within the archive.
+    pub name: String,
+    /// The contents of the file.
+    pub data: &'a [u8],
+}
+
+/// Whether the given data look like a ustar archive.
+#[must_use]
+pub fn is_tar(data: &[u8]) -> bool {
+    data.get(0x101..0x106) == Some(b"ustar")
+}
+
+/// Reads the regular files of a ustar archive, in order.
+///
+/// Entries of other types (e.g., directories) are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::tar;
+///
+/// let dir = std::env::temp_dir().join("pupper-tar-members-example");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("UPL.xz"), b"foo").unwrap();
+///
+/// let data = tar::build_update_files(&dir).unwrap();
+/// let members = tar::members(&data).unwrap();
+///
+/// assert_eq!("UPL.xz", members[0].name);
+/// assert_eq!(b"foo", members[0].data);
+/// ```
+pub fn members(data: &[u8]) -> Result<Vec<Member<'_>>, String> {
+    let mut members = Vec::new();
+    let mut offset = 0;
+
+    while let Some(block) = data.get(offset..offset + BLOCK_SIZE) {
+        // The archive ends at the first empty block.
+        if block.iter().all(|x| *x == 0) {
+            break;
+        }
+        let block = <&[u8; BLOCK_SIZE]>::try_from(block).unwrap();
+
+        let size = read_octal(&block[0x07C..0x088])
+            .ok_or_else(|| format!("entry at {:#x} has an invalid size", offset))?;
+        let start = offset + BLOCK_SIZE;
+        let end = usize::try_from(size)
+            .ok()
+            .and_then(|x| start.checked_add(x))
+            .filter(|x| *x <= data.len())
+            .ok_or_else(|| format!("entry at {:#x} is truncated", offset))?;
+
+        // Old-style archives mark regular files with a NUL type.
+        if matches!(block[0x09C], b'0' | 0) {
+            members.push(Member {
+                name: read_name(block),
+                data: &data[start..end],
+            });
+        }
+
+        offset = end + (BLOCK_SIZE - (end - start) % BLOCK_SIZE) % BLOCK_SIZE;
+    }
+
+    Ok(members)
+}
+
+/// The length of the archive at the start of the given data, including the end-of-archive blocks
+/// and padding to a whole record, if it's all there.
+pub(crate) fn archive_len(data: &[u8]) -> Option<usize> {
+    let mut offset = 0;
+
+    while let Some(block) = data.get(offset..offset + BLOCK_SIZE) {
+        if block.iter().all(|x| *x == 0) {
+            // Round up to a whole record, but only over padding.
+            let end = (offset + RECORD_SIZE - 1) / RECORD_SIZE * RECORD_SIZE;
+            let padding = data.get(offset..end.min(data.len()))?;
+            let len = offset + padding.iter().take_while(|x| **x == 0).count();
+
+            return Some(len - len % BLOCK_SIZE);
+        }
+
+        let size = usize::try_from(read_octal(&block[0x07C..0x088])?).ok()?;
+        offset = (offset + BLOCK_SIZE)
+            .checked_add(size)?
+            .checked_add((BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE)?;
+    }
+
+    None
+}
+
+fn read_name(block: &[u8; BLOCK_SIZE]) -> String {
+    let name = read_str(&block[0x000..0x064]);
+    let prefix = if is_tar(block) {
+        read_str(&block[0x159..0x1F4])
+    } else {
+        String::new()
+    };
+
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+fn read_str(field: &[u8]) -> String {
+    let len = field.iter().position(|x| *x == 0).unwrap_or(field.len());
+
+    String::from_utf8_lossy(&field[..len]).into_owned()
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let digits = read_str(field);
+    let digits = digits.trim_matches(|x| x == ' ' || x == '\0');
+
+    if digits.is_empty() {
+        Some(0)
+    } else {
+        u64::from_str_radix(digits, 8).ok()
+    }
+}
+
+/// Builds an `update_files.tar` from the files in a directory tree.
+///
+/// Each regular file becomes a ustar entry named after its path relative to `dir`. Entries are
+/// ordered by name, and their modification times are all zero, so that the same tree always
+/// produces the same archive, wherever it was checked out. The archive is padded to a whole tar
+/// record, like those in official packages.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::content::tar;
+///
+/// let dir = std::env::temp_dir().join("pupper-tar-example");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("UPL.xz"), b"foo").unwrap();
+///
+/// let data = tar::build_update_files(&dir).unwrap();
+///
+/// assert_eq!(b"UPL.xz\0", &data[..7]);
+/// // The modification time is zero, whenever the file was written.
+/// assert_eq!(b"00000000000\0", &data[0x88..0x94]);
+/// assert_eq!(0, data.len() % 0x2800);
+/// ```
+pub fn build_update_files(dir: &Path) -> io::Result<Vec<u8>> {
+    let mut paths = Vec::new();
+    collect_files(dir, &mut paths)?;
+
+    let mut entries = paths
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .strip_prefix(dir)
+                .unwrap()
+                .components()
+                .map(|x| x.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            (name, path)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut data = Vec::new();
+    for (name, path) in entries.iter() {
+        let contents = fs::read(path)?;
+
+        data.extend_from_slice(&header(name, contents.len() as u64)?);
+        data.extend_from_slice(&contents);
+        pad_to(&mut data, BLOCK_SIZE);
+    }
+
+    // The end of the archive is marked by two empty blocks.
+    data.resize(data.len() + 2 * BLOCK_SIZE, 0);
+    pad_to(&mut data, RECORD_SIZE);
+
+    Ok(data)
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            collect_files(&entry.path(), paths)?;
+        } else if file_type.is_file() {
+            paths.push(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
+fn header(path: &str, size: u64) -> io::Result<[u8; BLOCK_SIZE]> {
+    let mut block = [0; BLOCK_SIZE];
+    let invalid = |what| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} of '{}' is too large for a ustar header", what, path),
+        )
+    };
+
+    let (prefix, name) = split_name(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' is too long for a ustar header", path),
+        )
+    })?;
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    block[0x159..0x159 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    block[0x064..0x06C].copy_from_slice(b"0000644\0");
+    // The owner is root.
+    block[0x06C..0x074].copy_from_slice(b"0000000\0");
+    block[0x074..0x07C].copy_from_slice(b"0000000\0");
+    // Sizes of 8 GiB and up need more than the 11 digits the field holds.
+    write_octal(&mut block[0x07C..0x088], size).ok_or_else(|| invalid("the size"))?;
+    // The modification time is the epoch, for reproducibility.
+    block[0x088..0x094].copy_from_slice(b"00000000000\0");
+    // A regular file.
+    block[0x09C] = b'0';
+    block[0x101..0x107].copy_from_slice(b"ustar\0");
+    block[0x107..0x109].copy_from_slice(b"00");
+
+    // The checksum is calculated as if its own field were filled with spaces.
+    block[0x094..0x09C].copy_from_slice(&[b' '; 8]);
+    let checksum = block.iter().map(|x| u64::from(*x)).sum();
+    write_octal(&mut block[0x094..0x09B], checksum).ok_or_else(|| invalid("the checksum"))?;
+
+    Ok(block)
+}
+
+// Splits a name into a ustar prefix and name, if it fits at all.
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    if name.len() <= 100 {
+        return Some(("", name));
+    }
+
+    name.match_indices('/')
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100 && !name.is_empty())
+}
+
+// Writes a zero-padded, NUL-terminated octal number that fills the given field, or returns `None`
+// if it has too many digits to fit.
+fn write_octal(field: &mut [u8], value: u64) -> Option<()> {
+    let digits = format!("{:0width$o}\0", value, width = field.len() - 1);
+    if digits.len() != field.len() {
+        return None;
+    }
+    field.copy_from_slice(digits.as_bytes());
+
+    Some(())
+}
+
+fn pad_to(data: &mut Vec<u8>, alignment: usize) {
+    let len = data.len();
+    data.resize(len + (alignment - len % alignment) % alignment, 0);
+}
@@ -0,0 +1,206 @@
+//! Structured comparison of two [`Pup`]s.
+//!
+//! [`diff`] matches segments by ID and summarizes what changed, down to the byte level for
+//! segments present in both. A [`Patch`] captures the same changes in a form that can be
+//! distributed and applied.
+
+mod patch;
+
+pub use patch::{Patch, PatchError};
+
+use crate::{Digest, Pup, Segment, SegmentId};
+
+/// Compares two [`Pup`]s.
+///
+/// Segments are matched by ID; if several share an ID, the first in `old` is matched with the
+/// first in `new`, and so on. Data are compared by SHA-1 digest, so signatures and segment order
+/// don't matter.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{diff::{self, ChangeKind}, Pup, Segment, SegmentId};
+///
+/// let mut old = Pup::default();
+/// old.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+/// old.segments.push(Segment::new(SegmentId(0x101), Vec::new()));
+///
+/// let mut new = Pup::new(Vec::new(), 1);
+/// new.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+/// new.segments.push(Segment::new(SegmentId(0x300), Vec::new()));
+///
+/// let delta = diff::diff(&old, &new);
+/// let kinds: Vec<_> = delta.segments.iter().map(|x| (x.id.0, x.kind())).collect();
+///
+/// assert_eq!(Some((0, 1)), delta.image_version);
+/// assert_eq!(
+///     vec![
+///         (0x100, ChangeKind::Modified),
+///         (0x101, ChangeKind::Removed),
+///         (0x300, ChangeKind::Added),
+///     ],
+///     kinds,
+/// );
+///
+/// // Only the last two bytes differ.
+/// let bytes = delta.segments[0].bytes.unwrap();
+/// assert_eq!(2, bytes.changed_bytes);
+/// assert_eq!(Some(2), bytes.first_difference);
+/// ```
+#[must_use]
+pub fn diff(old: &Pup, new: &Pup) -> PupDelta {
+    let mut unmatched: Vec<Option<&Segment>> = new.segments.iter().map(Some).collect();
+    let mut segments = Vec::new();
+
+    for seg in old.segments.iter() {
+        let counterpart = unmatched
+            .iter_mut()
+            .find(|x| matches!(x, Some(x) if x.id == seg.id))
+            .and_then(Option::take);
+
+        let old_version = SegmentVersion::of(seg);
+        let new_version = counterpart.map(SegmentVersion::of);
+        if new_version != Some(old_version) {
+            segments.push(SegmentChange {
+                id: seg.id,
+                old: Some(old_version),
+                new: new_version,
+                bytes: counterpart.map(|x| ByteDiff::between(seg.data(), x.data())),
+            });
+        }
+    }
+
+    segments.extend(unmatched.into_iter().flatten().map(|seg| SegmentChange {
+        id: seg.id,
+        old: None,
+        new: Some(SegmentVersion::of(seg)),
+        bytes: None,
+    }));
+
+    let changed = |a: u64, b: u64| Some((a, b)).filter(|_| a != b);
+
+    PupDelta {
+        image_version: changed(old.image_version, new.image_version),
+        package_version: changed(old.package_version, new.package_version),
+        segments,
+    }
+}
+
+/// Everything that differs between two [`Pup`]s, returned by [`diff`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PupDelta {
+    /// The old and new image versions, if they differ.
+    pub image_version: Option<(u64, u64)>,
+    /// The old and new package versions, if they differ.
+    pub package_version: Option<(u64, u64)>,
+    /// The segments that were added, removed, or modified.
+    pub segments: Vec<SegmentChange>,
+}
+
+impl PupDelta {
+    /// Whether the two [`Pup`]s are equivalent.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.image_version.is_none() && self.package_version.is_none() && self.segments.is_empty()
+    }
+}
+
+/// A segment that differs between two [`Pup`]s.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SegmentChange {
+    /// The ID of the segment.
+    pub id: SegmentId,
+    /// The segment in the old [`Pup`], if it's there.
+    pub old: Option<SegmentVersion>,
+    /// The segment in the new [`Pup`], if it's there.
+    pub new: Option<SegmentVersion>,
+    /// How the data changed, if the segment is in both.
+    pub bytes: Option<ByteDiff>,
+}
+
+impl SegmentChange {
+    /// Whether the segment was added, removed, or modified.
+    #[must_use]
+    pub fn kind(&self) -> ChangeKind {
+        match (self.old, self.new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Modified,
+        }
+    }
+}
+
+/// The data of one side of a [`SegmentChange`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SegmentVersion {
+    /// The size of the data, in bytes.
+    pub size: u64,
+    /// The SHA-1 digest of the data.
+    pub digest: Digest,
+}
+
+impl SegmentVersion {
+    fn of(seg: &Segment) -> Self {
+        Self {
+            size: seg.data().len() as u64,
+            digest: Digest::sha1_of(seg.data()),
+        }
+    }
+}
+
+/// The kind of a [`SegmentChange`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChangeKind {
+    /// The segment is only in the new [`Pup`].
+    Added,
+    /// The segment is only in the old [`Pup`].
+    Removed,
+    /// The segment is in both, but its data differ.
+    Modified,
+}
+
+/// A byte-level summary of how the data of a modified segment changed.
+///
+/// Data are compared offset by offset; bytes past the end of the shorter side count as changed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ByteDiff {
+    /// The number of offsets whose bytes differ.
+    pub changed_bytes: u64,
+    /// The number of contiguous runs of differing bytes.
+    pub changed_runs: u64,
+    /// The offset of the first differing byte, if any.
+    pub first_difference: Option<u64>,
+}
+
+impl ByteDiff {
+    fn between(old: &[u8], new: &[u8]) -> Self {
+        let common = old.len().min(new.len());
+        let tail = (old.len().max(new.len()) - common) as u64;
+
+        let mut summary = Self::default();
+        let mut in_run = false;
+        for (i, (a, b)) in old.iter().zip(new.iter()).enumerate() {
+            if a == b {
+                in_run = false;
+                continue;
+            }
+
+            summary.changed_bytes += 1;
+            summary.first_difference.get_or_insert(i as u64);
+            if !in_run {
+                summary.changed_runs += 1;
+                in_run = true;
+            }
+        }
+
+        if tail != 0 {
+            summary.changed_bytes += tail;
+            summary.first_difference.get_or_insert(common as u64);
+            if !in_run {
+                summary.changed_runs += 1;
+            }
+        }
+
+        summary
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    BuildError, Digest, FixedSize as _, Pup, Segment, SegmentId, SerializeOptions, SignatureKind,
+};
+
+use std::{
+    convert::{TryFrom, TryInto as _},
+    fmt::{self, Display, Formatter},
+};
+
+/// The magic of a serialized [`Patch`].
+const MAGIC: &[u8; 8] = b"PUPPATCH";
+
+/// The only version of the patch format.
+const FORMAT_VERSION: u64 = 1;
+
+/// The changes that turn one [`Pup`] into another, compact enough to distribute instead of the
+/// whole package.
+///
+/// Segments whose data already exist in the source are referenced by index; only new data are
+/// stored. Both sides are identified by the SHA-1 digest of their serialization, so a patch is
+/// only applied to the [`Pup`] it was made from, and only succeeds if it reproduces the target
+/// exactly.
+///
+/// Serialized patches (conventionally `.puppatch` files) start with the magic `PUPPATCH`; like
+/// PUPs, all integers are big-endian.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{diff::Patch, Pup, Segment, SegmentId};
+/// use std::convert::TryFrom as _;
+///
+/// let mut old = Pup::default();
+/// old.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+/// old.segments.push(Segment::new(SegmentId(0x300), vec![0; 0x1000]));
+///
+/// let mut new = old.clone();
+/// new.segments[0].set_data(b"4.90".to_vec());
+/// new.segments[0].sign();
+///
+/// let patch = Patch::new(&old, &new).unwrap();
+/// let data = Vec::<u8>::from(&patch);
+///
+/// // The unchanged segment isn't stored.
+/// assert!(data.len() < 0x1000);
+///
+/// let patch = Patch::try_from(data.as_slice()).unwrap();
+/// assert_eq!(Ok(new), patch.apply(&old));
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Patch {
+    source: Digest,
+    target: Digest,
+    image_version: u64,
+    package_version: u64,
+    segments: Vec<PatchSegment>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct PatchSegment {
+    id: SegmentId,
+    sig_kind: SignatureKind,
+    sig: Digest,
+    data: PatchData,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum PatchData {
+    /// The data of the source segment at a specific index.
+    Copy(u64),
+    /// New data.
+    Literal(Vec<u8>),
+}
+
+impl Patch {
+    /// Creates a [`Patch`] that turns `source` into `target`.
+    ///
+    /// Fails if either can't be serialized.
+    pub fn new(source: &Pup, target: &Pup) -> Result<Self, BuildError> {
+        let segments = target
+            .segments
+            .iter()
+            .map(|seg| {
+                let data = match source.segments.iter().position(|x| x.data() == seg.data()) {
+                    Some(i) => PatchData::Copy(i as u64),
+                    None => PatchData::Literal(seg.data().to_vec()),
+                };
+
+                PatchSegment {
+                    id: seg.id,
+                    sig_kind: seg.signature_kind(),
+                    sig: *seg.signature(),
+                    data,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            source: fingerprint(source)?,
+            target: fingerprint(target)?,
+            image_version: target.image_version,
+            package_version: target.package_version,
+            segments,
+        })
+    }
+
+    /// Applies this [`Patch`] to the [`Pup`] it was made from.
+    pub fn apply(&self, source: &Pup) -> Result<Pup, PatchError> {
+        if fingerprint(source)? != self.source {
+            return Err(PatchError::SourceMismatch);
+        }
+
+        let segments = self
+            .segments
+            .iter()
+            .map(|x| {
+                let data = match x.data {
+                    PatchData::Copy(i) => source
+                        .segments
+                        .get(i as usize)
+                        .map(|x| x.data().to_vec())
+                        .ok_or(PatchError::SourceMismatch)?,
+                    PatchData::Literal(ref data) => data.clone(),
+                };
+
+                let mut seg = Segment::new(x.id, data);
+                seg.sig_kind = x.sig_kind;
+                seg.sig = x.sig;
+
+                Ok(seg)
+            })
+            .collect::<Result<_, PatchError>>()?;
+
+        let mut target = Pup::new(segments, self.image_version);
+        target.package_version = self.package_version;
+
+        if fingerprint(&target)? != self.target {
+            return Err(PatchError::TargetMismatch);
+        }
+
+        Ok(target)
+    }
+
+    /// The SHA-1 digest of the serialized [`Pup`] this patch applies to.
+    #[must_use]
+    pub fn source(&self) -> &Digest {
+        &self.source
+    }
+
+    /// The SHA-1 digest of the serialized [`Pup`] this patch produces.
+    #[must_use]
+    pub fn target(&self) -> &Digest {
+        &self.target
+    }
+}
+
+fn fingerprint(pup: &Pup) -> Result<Digest, BuildError> {
+    pup.serialize(&SerializeOptions::default())
+        .map(|x| Digest::sha1_of(&x))
+}
+
+impl From<&Patch> for Vec<u8> {
+    fn from(patch: &Patch) -> Self {
+        let mut data = Vec::new();
+
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+        data.extend_from_slice(&patch.source.0);
+        data.extend_from_slice(&patch.target.0);
+        data.extend_from_slice(&patch.image_version.to_be_bytes());
+        data.extend_from_slice(&patch.package_version.to_be_bytes());
+        data.extend_from_slice(&(patch.segments.len() as u64).to_be_bytes());
+
+        for seg in patch.segments.iter() {
+            data.extend_from_slice(&seg.id.0.to_be_bytes());
+            data.extend_from_slice(&u32::from(seg.sig_kind).to_be_bytes());
+            data.extend_from_slice(&seg.sig.0);
+
+            match seg.data {
+                PatchData::Copy(i) => {
+                    data.push(0);
+                    data.extend_from_slice(&i.to_be_bytes());
+                }
+                PatchData::Literal(ref x) => {
+                    data.push(1);
+                    data.extend_from_slice(&(x.len() as u64).to_be_bytes());
+                    data.extend_from_slice(x);
+                }
+            }
+        }
+
+        data
+    }
+}
+
+impl TryFrom<&[u8]> for Patch {
+    type Error = PatchError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = Reader(data);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(PatchError::InvalidMagic);
+        }
+        let version = reader.u64()?;
+        if version != FORMAT_VERSION {
+            return Err(PatchError::UnsupportedVersion(version));
+        }
+
+        let source = reader.digest()?;
+        let target = reader.digest()?;
+        let image_version = reader.u64()?;
+        let package_version = reader.u64()?;
+        let seg_count = reader.u64()?;
+
+        let segments = (0..seg_count)
+            .map(|_| {
+                let id = SegmentId(reader.u64()?);
+                let sig_kind = u32::from_be_bytes(reader.take(4)?.try_into().unwrap())
+                    .try_into()
+                    .map_err(|_| PatchError::Malformed)?;
+                let sig = reader.digest()?;
+                let data = match reader.take(1)?[0] {
+                    0 => PatchData::Copy(reader.u64()?),
+                    1 => {
+                        let len = reader.u64()?;
+                        PatchData::Literal(reader.take(len as usize)?.to_vec())
+                    }
+                    _ => return Err(PatchError::Malformed),
+                };
+
+                Ok(PatchSegment {
+                    id,
+                    sig_kind,
+                    sig,
+                    data,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        if !reader.0.is_empty() {
+            return Err(PatchError::Malformed);
+        }
+
+        Ok(Self {
+            source,
+            target,
+            image_version,
+            package_version,
+            segments,
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        if self.0.len() < len {
+            return Err(PatchError::Malformed);
+        }
+
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+
+        Ok(taken)
+    }
+
+    fn u64(&mut self) -> Result<u64, PatchError> {
+        self.take(8)
+            .map(|x| u64::from_be_bytes(x.try_into().unwrap()))
+    }
+
+    fn digest(&mut self) -> Result<Digest, PatchError> {
+        self.take(Digest::SIZE)
+            .map(|x| Digest(x.try_into().unwrap()))
+    }
+}
+
+/// An erroneous result of parsing or applying a [`Patch`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum PatchError {
+    /// The data don't start with the patch magic.
+    InvalidMagic,
+    /// The patch has an unknown format version.
+    UnsupportedVersion(u64),
+    /// The patch is truncated or otherwise malformed.
+    Malformed,
+    /// The patch was made from a different [`Pup`].
+    SourceMismatch,
+    /// Applying the patch didn't produce the expected [`Pup`].
+    TargetMismatch,
+    /// The source or the result couldn't be serialized to check its digest.
+    Build(BuildError),
+}
+
+impl From<BuildError> for PatchError {
+    fn from(err: BuildError) -> Self {
+        Self::Build(err)
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "data aren't a patch"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "patch format version {} is unsupported", version)
+            }
+            Self::Malformed => write!(f, "patch is malformed"),
+            Self::SourceMismatch => write!(f, "patch was made for a different PUP"),
+            Self::TargetMismatch => write!(f, "patch didn't produce the expected PUP"),
+            Self::Build(err) => write!(f, "{}", err),
+        }
+    }
+}
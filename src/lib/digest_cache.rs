@@ -0,0 +1,96 @@
+use crate::{Digest, Segment, SignatureKind, HMAC_KEY};
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher as _, Hasher as _},
+};
+
+/// Remembers the signatures it has computed, so that segments whose data haven't changed aren't
+/// signed or verified from scratch again.
+///
+/// Entries are keyed by the key used (i.e., the current [`HMAC_KEY`]), by the signature kind, and
+/// by the size and a
+/// randomly keyed hash of the data, which is much cheaper to compute than a signature. Long-lived
+/// users, such as an interactive editor, should [`Self::clear`] the cache now and then to bound
+/// its size.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{DigestCache, Segment, SegmentId};
+///
+/// let mut cache = DigestCache::new();
+///
+/// let mut seg = Segment::new(SegmentId(0x100), b"4.88".to_vec());
+/// cache.sign(&mut seg);
+/// assert_eq!(1, cache.len());
+///
+/// // Verifying the same data reuses the signature.
+/// assert!(cache.verify(&seg));
+/// assert_eq!(1, cache.len());
+///
+/// seg.set_data(b"4.90".to_vec());
+/// assert!(!cache.verify(&seg));
+///
+/// cache.clear();
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DigestCache {
+    state: RandomState,
+    entries: HashMap<(Digest, SignatureKind, usize, u64), Digest>,
+}
+
+impl DigestCache {
+    /// Creates an empty [`DigestCache`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The HMAC-SHA1 digest of the given data, keyed with [`HMAC_KEY`], computing it only if it
+    /// isn't already cached.
+    pub fn hmac_sha1_of(&mut self, data: &[u8]) -> Digest {
+        self.signature_of(SignatureKind::HmacSha1, data)
+    }
+
+    /// Like [`Segment::sign`], but computes the signature only if it isn't already cached.
+    pub fn sign(&mut self, seg: &mut Segment) {
+        let sig = self.signature_of(seg.signature_kind(), seg.data());
+        seg.set_signature(sig);
+    }
+
+    /// Whether the signature of the given segment is valid, computing the expected signature
+    /// only if it isn't already cached.
+    pub fn verify(&mut self, seg: &Segment) -> bool {
+        *seg.signature() == self.signature_of(seg.signature_kind(), seg.data())
+    }
+
+    /// The number of cached signatures.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no signatures are cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets every cached signature.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn signature_of(&mut self, kind: SignatureKind, data: &[u8]) -> Digest {
+        let key_id = HMAC_KEY.with(|key| Digest::sha1_of(key));
+        let mut hasher = self.state.build_hasher();
+        hasher.write(data);
+
+        *self
+            .entries
+            .entry((key_id, kind, data.len(), hasher.finish()))
+            .or_insert_with(|| Digest::signature_of(kind, data))
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{Digest, RawHeader, SegmentId, SignatureKind};
+
+use std::convert::TryFrom as _;
+
+/// What a directory of segment files can't express on its own: the `pup.toml` that
+/// `pupper unpack` writes next to the files, and that [`Pup::from_dir`] reads back.
+///
+/// It records the versions of the PUP and, for each segment in order, its file, ID, signature
+/// kind, alignment, original signature, and the SHA-1 fingerprint of its original data. A
+/// segment whose file still matches its fingerprint keeps its original signature, even if that
+/// signature was invalid, so that an unmodified directory packs into the same PUP.
+///
+/// [`Pup::from_dir`]: crate::Pup::from_dir
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{DirManifest, Pup, RawHeader, Segment, SegmentId};
+/// use std::convert::TryFrom as _;
+///
+/// let mut pup = Pup::new(Vec::new(), 5);
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+/// pup.segments[0].sign();
+/// let data = Vec::<u8>::from(&pup);
+/// let header = RawHeader::try_from(data.as_slice()).unwrap();
+///
+/// let fingerprints = [pupper::Digest::sha1_of(b"4.88\n")];
+/// let manifest = DirManifest::from_header(&header, &["version.txt".into()], &fingerprints);
+/// let parsed = DirManifest::from_toml(&manifest.to_toml()).unwrap();
+///
+/// assert_eq!(manifest, parsed);
+/// assert_eq!(5, parsed.image_version);
+/// assert_eq!(SegmentId(0x100), parsed.segments[0].id);
+/// assert_eq!(*pup.segments[0].signature(), parsed.segments[0].signature);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DirManifest {
+    /// The image version.
+    pub image_version: u64,
+    /// The package version.
+    pub package_version: u64,
+    /// Each segment, in order.
+    pub segments: Vec<DirManifestEntry>,
+}
+
+/// A segment of a [`DirManifest`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DirManifestEntry {
+    /// The name of the file holding the data, relative to the directory.
+    pub file_name: String,
+    /// The segment ID.
+    pub id: SegmentId,
+    /// The signature kind.
+    pub signature_kind: SignatureKind,
+    /// The alignment, as by [`Segment::set_alignment`].
+    ///
+    /// [`Segment::set_alignment`]: crate::Segment::set_alignment
+    pub alignment: u64,
+    /// The original signature.
+    pub signature: Digest,
+    /// The SHA-1 fingerprint of the original data (see [`Digest::sha1_of`]).
+    pub fingerprint: Digest,
+}
+
+impl DirManifest {
+    /// The file name of the manifest within the directory.
+    pub const FILE_NAME: &'static str = "pup.toml";
+
+    /// Creates a [`DirManifest`] for the segments of a PUP, given the file name and fingerprint of
+    /// each.
+    ///
+    /// Alignments aren't stored in a PUP, so each is the smallest that reproduces the padding
+    /// before the segment, if any.
+    #[must_use]
+    pub fn from_header(header: &RawHeader, file_names: &[String], fingerprints: &[Digest]) -> Self {
+        let mut end = header.metadata.header_size;
+        let segments = header
+            .segment_entries
+            .iter()
+            .zip(file_names.iter().zip(fingerprints.iter()))
+            .enumerate()
+            .map(|(i, (entry, (file_name, fingerprint)))| {
+                let alignment = alignment_between(end, entry.offset);
+                end = entry.offset.saturating_add(entry.size);
+
+                DirManifestEntry {
+                    file_name: file_name.clone(),
+                    id: entry.id,
+                    signature_kind: entry.signature_kind,
+                    alignment,
+                    signature: header
+                        .digest_entries
+                        .iter()
+                        .find(|x| x.segment_index == i as u64)
+                        .map_or_else(Digest::default, |x| x.digest),
+                    fingerprint: *fingerprint,
+                }
+            })
+            .collect();
+
+        Self {
+            image_version: header.metadata.image_version,
+            package_version: header.metadata.package_version,
+            segments,
+        }
+    }
+
+    /// Serializes this [`DirManifest`] as TOML. IDs are written in hexadecimal.
+    #[must_use]
+    pub fn to_toml(&self) -> String {
+        let mut toml = format!(
+            "image-version = {}\npackage-version = {}\n",
+            self.image_version, self.package_version
+        );
+
+        for seg in self.segments.iter() {
+            toml += &format!(
+                "\n[[segment]]\nfile = {}\nid = \"{:#x}\"\nsignature-kind = {}\nalignment = {}\n\
+                 signature = \"{}\"\nfingerprint = \"{}\"\n",
+                toml::Value::from(seg.file_name.as_str()),
+                seg.id.0,
+                u32::from(seg.signature_kind),
+                seg.alignment,
+                seg.signature,
+                seg.fingerprint,
+            );
+        }
+
+        toml
+    }
+
+    /// Parses a [`DirManifest`] from TOML, as written by [`Self::to_toml`].
+    ///
+    /// IDs may be written in decimal or, as strings with a `0x` prefix, in hexadecimal. Only
+    /// `file` and `id` are required of each segment; the signature kind defaults to
+    /// [`SignatureKind::default_for`] the ID, the alignment to 1, and a missing signature or
+    /// fingerprint means the segment is signed anew.
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        let table: toml::value::Table =
+            toml::from_str(toml).map_err(|err| format!("failed to parse TOML: {}", err))?;
+        let integer = |table: &toml::value::Table, key: &str| {
+            table
+                .get(key)
+                .map(|x| {
+                    x.as_integer()
+                        .and_then(|x| u64::try_from(x).ok())
+                        .ok_or_else(|| format!("'{}' is not a non-negative integer", key))
+                })
+                .transpose()
+        };
+
+        let segments = match table.get("segment") {
+            Some(x) => x.as_array().ok_or("'segment' is not an array of tables")?,
+            None => return Err("'segment' is missing".into()),
+        };
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(i, seg)| {
+                let seg = seg
+                    .as_table()
+                    .ok_or_else(|| format!("segment {} is not a table", i))?;
+                let string =
+                    |key: &'static str| seg.get(key).map(|x| x.as_str().ok_or(key)).transpose();
+                let err = |key| format!("'{}' of segment {} is invalid", key, i);
+
+                let id = match seg.get("id") {
+                    Some(toml::Value::Integer(x)) => u64::try_from(*x).ok().map(SegmentId),
+                    Some(toml::Value::String(x)) => crate::names::parse_id(x),
+                    _ => None,
+                }
+                .ok_or_else(|| err("id"))?;
+                let digest = |key| {
+                    string(key)
+                        .map_err(err)?
+                        .map(|x| x.parse().map_err(|_| err(key)))
+                        .transpose()
+                };
+
+                Ok(DirManifestEntry {
+                    file_name: string("file")
+                        .map_err(err)?
+                        .ok_or_else(|| err("file"))?
+                        .into(),
+                    id,
+                    signature_kind: integer(seg, "signature-kind")?
+                        .map(|x| {
+                            u32::try_from(x)
+                                .ok()
+                                .and_then(|x| SignatureKind::try_from(x).ok())
+                                .ok_or_else(|| err("signature-kind"))
+                        })
+                        .transpose()?
+                        .unwrap_or_else(|| SignatureKind::default_for(id)),
+                    alignment: integer(seg, "alignment")?.unwrap_or(1),
+                    signature: digest("signature")?.unwrap_or_default(),
+                    fingerprint: digest("fingerprint")?.unwrap_or_default(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            image_version: integer(&table, "image-version")?.unwrap_or(0),
+            package_version: integer(&table, "package-version")?
+                .unwrap_or(crate::header::meta::Metadata::PKG_VERSION),
+            segments,
+        })
+    }
+}
+
+// The smallest alignment that pads a segment ending at 'end' to 'offset': a power of two if one
+// will do, or else the offset itself.
+fn alignment_between(end: u64, offset: u64) -> u64 {
+    if offset <= end {
+        return 1;
+    }
+
+    (1..64)
+        .map(|x| 1_u64 << x)
+        .take_while(|x| *x <= offset)
+        .find(|x| offset % x == 0 && offset - end < *x)
+        .unwrap_or(offset)
+}
@@ -0,0 +1,97 @@
+use crate::{Digest, Error, Pup, Segment, SegmentId};
+
+impl Pup {
+    /// Applies a series of changes to this [`Pup`] all at once.
+    ///
+    /// The changes are staged in a [`Transaction`]. If the given function returns [`Ok`], they are
+    /// applied, and every staged segment without a signature (i.e., one that was replaced or newly
+    /// created) is signed. Otherwise, this [`Pup`] is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Error, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+    ///
+    /// let result: Result<(), Error> = pup.edit(|tx| {
+    ///     tx.set_image_version(1);
+    ///     tx.replace(SegmentId(0x100), b"bar".to_vec())?;
+    ///     tx.replace(SegmentId(0x9999), Vec::new())?;
+    ///
+    ///     Ok(())
+    /// });
+    ///
+    /// // The last replacement failed, so nothing changed.
+    /// assert_eq!(Err(Error::SegmentNotFound(SegmentId(0x9999))), result);
+    /// assert_eq!(0, pup.image_version);
+    /// assert_eq!(b"foo", pup.segments[0].data());
+    /// ```
+    pub fn edit<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction) -> Result<T, E>,
+    {
+        let mut tx = Transaction(self.clone());
+        let result = f(&mut tx)?;
+
+        for seg in tx.0.segments.iter_mut() {
+            if *seg.signature() == Digest::default() {
+                seg.sign();
+            }
+        }
+        *self = tx.0;
+
+        Ok(result)
+    }
+}
+
+/// Changes staged by [`Pup::edit`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Transaction(Pup);
+
+impl Transaction {
+    /// The staged [`Pup`], with all changes made so far.
+    #[must_use]
+    pub fn pup(&self) -> &Pup {
+        &self.0
+    }
+
+    /// Replaces the data of the first segment with a specific ID.
+    pub fn replace(&mut self, id: SegmentId, data: Vec<u8>) -> Result<(), Error> {
+        self.segment_mut(id)?.set_data(data);
+
+        Ok(())
+    }
+
+    /// Appends a segment.
+    pub fn push(&mut self, seg: Segment) {
+        self.0.segments.push(seg);
+    }
+
+    /// Removes the first segment with a specific ID, returning it.
+    pub fn remove(&mut self, id: SegmentId) -> Result<Segment, Error> {
+        let i = self.position(id)?;
+
+        Ok(self.0.segments.remove(i))
+    }
+
+    /// Sets the image version.
+    pub fn set_image_version(&mut self, image_version: u64) {
+        self.0.image_version = image_version;
+    }
+
+    fn segment_mut(&mut self, id: SegmentId) -> Result<&mut Segment, Error> {
+        let i = self.position(id)?;
+
+        Ok(&mut self.0.segments[i])
+    }
+
+    fn position(&self, id: SegmentId) -> Result<usize, Error> {
+        self.0
+            .segments
+            .iter()
+            .position(|x| x.id == id)
+            .ok_or(Error::SegmentNotFound(id))
+    }
+}
@@ -0,0 +1,479 @@
+use crate::{Digest, FirmwareVersion, ParseOptions, Pup, SegmentId, ValidationLevel};
+
+use std::fmt::{self, Display, Formatter};
+
+/// A database of official firmware releases, used to recognize known packages.
+///
+/// This crate doesn't ship any releases of its own; databases are loaded with
+/// [`Self::from_toml`] or built with [`Self::push`].
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Digest, FirmwareDb, Pup, Release};
+///
+/// let data = Vec::<u8>::from(&Pup::default());
+///
+/// let mut db = FirmwareDb::new();
+/// db.push(Release {
+///     version: "4.88".into(),
+///     region: "CEX".into(),
+///     image_version: 0,
+///     size: data.len() as u64,
+///     sha1: Digest::sha1_of(&data),
+///     segments: Vec::new(),
+/// });
+///
+/// assert_eq!("4.88 CEX", db.find(&data).unwrap().to_string());
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FirmwareDb(Vec<Release>);
+
+impl FirmwareDb {
+    /// Creates an empty [`FirmwareDb`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a [`FirmwareDb`] from a TOML document.
+    ///
+    /// Each release is an entry of the `release` array. Segment lists are optional, but without
+    /// them, [`Release::compare`] can't explain differences in segments:
+    ///
+    /// ```toml
+    /// [[release]]
+    /// version = "4.88"
+    /// region = "CEX"
+    /// image-version = 0x12345
+    /// size = 206213120
+    /// sha1 = "0123456789abcdef0123456789abcdef01234567"
+    ///
+    /// [[release.segment]]
+    /// id = 0x100
+    /// size = 16
+    /// sha1 = "0123456789abcdef0123456789abcdef01234567"
+    /// ```
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        let table: toml::value::Table =
+            toml::from_str(toml).map_err(|err| format!("failed to parse TOML: {}", err))?;
+
+        let releases = match table.get("release") {
+            Some(x) => x.as_array().ok_or("'release' is not an array")?.as_slice(),
+            None => &[],
+        };
+
+        releases
+            .iter()
+            .enumerate()
+            .map(|(i, x)| parse_release(x).map_err(|err| format!("release {}: {}", i, err)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Adds a release.
+    pub fn push(&mut self, release: Release) {
+        self.0.push(release);
+    }
+
+    /// Iterates over all releases in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &Release> {
+        self.0.iter()
+    }
+
+    /// The release whose package is byte-for-byte identical to the given data, if any.
+    #[must_use]
+    pub fn find(&self, data: &[u8]) -> Option<&Release> {
+        // Comparing sizes first saves hashing in the common case.
+        let size = data.len() as u64;
+        let mut candidates = self.0.iter().filter(|x| x.size == size).peekable();
+        candidates.peek()?;
+
+        let sha1 = Digest::sha1_of(data);
+        candidates.find(|x| x.sha1 == sha1)
+    }
+
+    /// The release that most resembles the given [`Pup`], if any resembles it at all.
+    ///
+    /// A release with the same image version is preferred; ties are broken by the number of
+    /// segments with identical data.
+    #[must_use]
+    pub fn closest(&self, pup: &Pup) -> Option<&Release> {
+        let fingerprints: Vec<_> = pup
+            .segments
+            .iter()
+            .map(|x| (x.id, Digest::sha1_of(x.data())))
+            .collect();
+
+        self.0
+            .iter()
+            .map(|release| {
+                let shared = release
+                    .segments
+                    .iter()
+                    .filter(|x| fingerprints.contains(&(x.id, x.sha1)))
+                    .count();
+
+                (
+                    (release.image_version == pup.image_version, shared),
+                    release,
+                )
+            })
+            .filter(|(score, _)| *score != (false, 0))
+            // Reversing first makes ties go to the earliest release.
+            .rev()
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, release)| release)
+    }
+
+    /// The release whose segment layout a [`Pup`] of the given version is expected to follow.
+    ///
+    /// Layouts change between firmware eras, so this is the latest release of that version or
+    /// earlier, or failing that, the earliest release. Only releases with segment lists and
+    /// readable versions are considered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{FirmwareDb, FirmwareVersion, Release, ReleaseSegment};
+    ///
+    /// let mut db = FirmwareDb::new();
+    /// for version in ["3.55", "4.88"] {
+    ///     db.push(Release {
+    ///         version: version.into(),
+    ///         segments: vec![ReleaseSegment::default()],
+    ///         ..Default::default()
+    ///     });
+    /// }
+    ///
+    /// let reference = |x: &str| db.reference_for(x.parse().unwrap()).unwrap().version.as_str();
+    ///
+    /// assert_eq!("3.55", reference("4.00"));
+    /// assert_eq!("4.88", reference("4.91"));
+    /// assert_eq!("3.55", reference("3.41"));
+    /// ```
+    #[must_use]
+    pub fn reference_for(&self, version: FirmwareVersion) -> Option<&Release> {
+        let releases = self
+            .0
+            .iter()
+            .filter(|x| !x.segments.is_empty())
+            .filter_map(|x| Some((x.version.parse::<FirmwareVersion>().ok()?, x)));
+
+        releases
+            .clone()
+            .filter(|(x, _)| *x <= version)
+            // Reversing first makes ties go to the earliest release.
+            .rev()
+            .max_by_key(|(x, _)| *x)
+            .or_else(|| releases.min_by_key(|(x, _)| *x))
+            .map(|(_, x)| x)
+    }
+}
+
+fn parse_release(value: &toml::Value) -> Result<Release, String> {
+    let segments = match value.get("segment") {
+        Some(x) => x
+            .as_array()
+            .ok_or("'segment' is not an array")?
+            .iter()
+            .enumerate()
+            .map(|(i, x)| parse_segment(x).map_err(|err| format!("segment {}: {}", i, err)))
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+
+    Ok(Release {
+        version: get_str(value, "version")?.into(),
+        region: get_str(value, "region")?.into(),
+        image_version: get_u64(value, "image-version")?,
+        size: get_u64(value, "size")?,
+        sha1: get_str(value, "sha1")?.parse()?,
+        segments,
+    })
+}
+
+fn parse_segment(value: &toml::Value) -> Result<ReleaseSegment, String> {
+    Ok(ReleaseSegment {
+        id: SegmentId(get_u64(value, "id")?),
+        size: get_u64(value, "size")?,
+        sha1: get_str(value, "sha1")?.parse()?,
+    })
+}
+
+fn get_str<'a>(value: &'a toml::Value, key: &str) -> Result<&'a str, String> {
+    value
+        .get(key)
+        .and_then(toml::Value::as_str)
+        .ok_or_else(|| format!("'{}' is missing or not a string", key))
+}
+
+fn get_u64(value: &toml::Value, key: &str) -> Result<u64, String> {
+    value
+        .get(key)
+        .and_then(toml::Value::as_integer)
+        .filter(|x| *x >= 0)
+        .map(|x| x as u64)
+        .ok_or_else(|| format!("'{}' is missing or not a non-negative integer", key))
+}
+
+/// An official firmware release in a [`FirmwareDb`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Release {
+    /// The system software version (e.g., `4.88`).
+    pub version: String,
+    /// The region or console type (e.g., `CEX`).
+    pub region: String,
+    /// The image version of the package.
+    pub image_version: u64,
+    /// The size of the package, in bytes.
+    pub size: u64,
+    /// The SHA-1 digest of the package.
+    pub sha1: Digest,
+    /// The segments of the package, in order.
+    pub segments: Vec<ReleaseSegment>,
+}
+
+impl Release {
+    /// Explains how the given data differ from the package of this release.
+    ///
+    /// Segments are only compared if the data parse as a [`Pup`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Deviation, Digest, Pup, Release, ReleaseSegment, Segment, SegmentId};
+    ///
+    /// let release = Release {
+    ///     segments: vec![ReleaseSegment {
+    ///         id: SegmentId(0x100),
+    ///         size: 3,
+    ///         sha1: Digest::sha1_of(b"foo"),
+    ///     }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"bar".to_vec()));
+    /// let data = Vec::<u8>::from(&pup);
+    ///
+    /// let deviations = release.compare(&data);
+    ///
+    /// assert!(deviations.contains(&Deviation::SegmentHashMismatch(SegmentId(0x100))));
+    /// ```
+    #[must_use]
+    pub fn compare(&self, data: &[u8]) -> Vec<Deviation> {
+        let mut deviations = Vec::new();
+
+        let size = data.len() as u64;
+        if size != self.size {
+            deviations.push(Deviation::SizeMismatch {
+                expected: self.size,
+                actual: size,
+            });
+        }
+
+        let sha1 = Digest::sha1_of(data);
+        if sha1 != self.sha1 {
+            deviations.push(Deviation::HashMismatch {
+                expected: self.sha1,
+                actual: sha1,
+            });
+        }
+
+        let pup = match Pup::parse(
+            data,
+            &mut ParseOptions {
+                validation: ValidationLevel::None,
+                ..Default::default()
+            },
+        ) {
+            Ok(x) => x,
+            Err(_) => return deviations,
+        };
+
+        if pup.image_version != self.image_version {
+            deviations.push(Deviation::ImageVersionMismatch {
+                expected: self.image_version,
+                actual: pup.image_version,
+            });
+        }
+
+        for expected in self.segments.iter() {
+            let seg = match pup.segments.iter().find(|x| x.id == expected.id) {
+                Some(x) => x,
+                None => {
+                    deviations.push(Deviation::MissingSegment(expected.id));
+                    continue;
+                }
+            };
+
+            let size = seg.data().len() as u64;
+            if size != expected.size {
+                deviations.push(Deviation::SegmentSizeMismatch {
+                    id: expected.id,
+                    expected: expected.size,
+                    actual: size,
+                });
+            } else if Digest::sha1_of(seg.data()) != expected.sha1 {
+                deviations.push(Deviation::SegmentHashMismatch(expected.id));
+            }
+        }
+
+        for seg in pup.segments.iter() {
+            if !self.segments.iter().any(|x| x.id == seg.id) {
+                deviations.push(Deviation::ExtraSegment(seg.id));
+            }
+        }
+
+        deviations
+    }
+}
+
+impl Release {
+    /// Compares the segment IDs of a [`Pup`], and their order, against this release, ignoring
+    /// segment data.
+    ///
+    /// Only [`Deviation::MissingSegment`], [`Deviation::ExtraSegment`], and
+    /// [`Deviation::SegmentOutOfOrder`] are reported. Unlike [`Pup::sort_canonical`], which knows
+    /// a single order, this holds a package to the exact layout of a release, such as the one
+    /// [`FirmwareDb::reference_for`] picks for its era.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Deviation, Pup, Release, ReleaseSegment, Segment, SegmentId};
+    ///
+    /// let segment = |id| ReleaseSegment {
+    ///     id: SegmentId(id),
+    ///     ..Default::default()
+    /// };
+    /// let release = Release {
+    ///     segments: vec![segment(0x100), segment(0x101), segment(0x200)],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut pup = Pup::default();
+    /// for id in [0x200, 0x100, 0x9999] {
+    ///     pup.segments.push(Segment::new(SegmentId(id), Vec::new()));
+    /// }
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         Deviation::MissingSegment(SegmentId(0x101)),
+    ///         Deviation::SegmentOutOfOrder(SegmentId(0x100)),
+    ///         Deviation::ExtraSegment(SegmentId(0x9999)),
+    ///     ],
+    ///     release.compare_layout(&pup),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn compare_layout(&self, pup: &Pup) -> Vec<Deviation> {
+        let mut deviations: Vec<_> = self
+            .segments
+            .iter()
+            .filter(|expected| !pup.segments.iter().any(|x| x.id == expected.id))
+            .map(|x| Deviation::MissingSegment(x.id))
+            .collect();
+
+        let mut latest = 0;
+        for seg in pup.segments.iter() {
+            match self.segments.iter().position(|x| x.id == seg.id) {
+                Some(i) if i < latest => deviations.push(Deviation::SegmentOutOfOrder(seg.id)),
+                Some(i) => latest = i,
+                None => deviations.push(Deviation::ExtraSegment(seg.id)),
+            }
+        }
+
+        deviations
+    }
+}
+
+impl Display for Release {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.version, self.region)
+    }
+}
+
+/// A segment of a [`Release`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ReleaseSegment {
+    /// The ID of the segment.
+    pub id: SegmentId,
+    /// The size of the segment data, in bytes.
+    pub size: u64,
+    /// The SHA-1 digest of the segment data.
+    pub sha1: Digest,
+}
+
+/// A difference between a package and a [`Release`], as reported by [`Release::compare`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Deviation {
+    /// The package size differs.
+    SizeMismatch {
+        /// The size of the release.
+        expected: u64,
+        /// The size of the package.
+        actual: u64,
+    },
+    /// The SHA-1 digest of the package differs.
+    HashMismatch {
+        /// The digest of the release.
+        expected: Digest,
+        /// The digest of the package.
+        actual: Digest,
+    },
+    /// The image version differs.
+    ImageVersionMismatch {
+        /// The image version of the release.
+        expected: u64,
+        /// The image version of the package.
+        actual: u64,
+    },
+    /// A segment of the release is missing from the package.
+    MissingSegment(SegmentId),
+    /// The package has a segment that the release doesn't.
+    ExtraSegment(SegmentId),
+    /// The size of a segment differs.
+    SegmentSizeMismatch {
+        /// The ID of the segment.
+        id: SegmentId,
+        /// The size of the segment in the release.
+        expected: u64,
+        /// The size of the segment in the package.
+        actual: u64,
+    },
+    /// The data of a segment differ, though its size doesn't.
+    SegmentHashMismatch(SegmentId),
+    /// A segment comes before one that precedes it in the release.
+    SegmentOutOfOrder(SegmentId),
+}
+
+impl Display for Deviation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::SizeMismatch { expected, actual } => {
+                write!(f, "size is {}, but should be {}", actual, expected)
+            }
+            Self::HashMismatch { expected, actual } => {
+                write!(f, "SHA-1 is {}, but should be {}", actual, expected)
+            }
+            Self::ImageVersionMismatch { expected, actual } => {
+                write!(f, "image version is {}, but should be {}", actual, expected)
+            }
+            Self::MissingSegment(id) => write!(f, "segment {} is missing", id),
+            Self::ExtraSegment(id) => write!(f, "segment {} is unexpected", id),
+            Self::SegmentSizeMismatch {
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "segment {} has size {}, but should have size {}",
+                id, actual, expected
+            ),
+            Self::SegmentHashMismatch(id) => write!(f, "data of segment {} differ", id),
+            Self::SegmentOutOfOrder(id) => write!(f, "segment {} is out of order", id),
+        }
+    }
+}
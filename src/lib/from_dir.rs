@@ -0,0 +1,184 @@
+use crate::{Digest, DirManifest, Pup, Segment, SegmentId, SegmentNameMap};
+
+use std::{
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+impl Pup {
+    /// Creates a [`Pup`] from a directory of segment files, such as one written by
+    /// `pupper unpack`.
+    ///
+    /// Each file becomes a signed segment. Files are recognized by their names in
+    /// [`FromDirOptions::names`] or by their IDs in hex (e.g., `0x9999.bin`). As `unpack` names
+    /// them, a file name may end with the index of a segment sharing its ID (e.g.,
+    /// `version.txt.3`). Segments are ordered as by [`Self::sort_canonical`]. Subdirectories are
+    /// ignored.
+    ///
+    /// If the directory holds a [`DirManifest`] (`pup.toml`), as `unpack` writes, the manifest
+    /// decides instead which file holds which segment, in what order, and everything else the
+    /// files can't express; [`FromDirOptions::names`] and [`FromDirOptions::image_version`] are
+    /// then ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{FromDirOptions, Pup, SegmentId};
+    ///
+    /// let dir = std::env::temp_dir().join("pupper-from-dir-example");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// std::fs::write(dir.join("0x9999.bin"), b"foo").unwrap();
+    /// std::fs::write(dir.join("version.txt"), b"4.88\n").unwrap();
+    ///
+    /// let pup = Pup::from_dir(&dir, &FromDirOptions::default()).unwrap();
+    /// let ids: Vec<u64> = pup.segments.iter().map(|x| x.id.0).collect();
+    ///
+    /// assert_eq!(vec![0x100, 0x9999], ids);
+    /// assert_eq!(b"foo", pup.segments[1].data());
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn from_dir(path: &Path, options: &FromDirOptions) -> Result<Self, FromDirError> {
+        let manifest = match fs::read_to_string(path.join(DirManifest::FILE_NAME)) {
+            Ok(x) => Some(DirManifest::from_toml(&x).map_err(FromDirError::Manifest)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(FromDirError::Io(err)),
+        };
+        let mut files = Vec::new();
+
+        for entry in fs::read_dir(path).map_err(FromDirError::Io)? {
+            let entry = entry.map_err(FromDirError::Io)?;
+            if !entry.file_type().map_err(FromDirError::Io)?.is_file() {
+                continue;
+            }
+
+            let file_name = entry.file_name();
+            if let Some(ref manifest) = manifest {
+                let listed = file_name.to_str().is_some_and(|x| {
+                    x == DirManifest::FILE_NAME
+                        || manifest.segments.iter().any(|seg| seg.file_name == x)
+                });
+                if !(listed || options.skip_unknown) {
+                    return Err(FromDirError::UnknownFile(entry.path()));
+                }
+
+                continue;
+            }
+
+            match file_name
+                .to_str()
+                .and_then(|x| segment_of(x, &options.names))
+            {
+                Some((id, index)) => files.push((id, index, entry.path())),
+                None if options.skip_unknown => {}
+                None => return Err(FromDirError::UnknownFile(entry.path())),
+            }
+        }
+
+        if let Some(manifest) = manifest {
+            return Self::from_manifest(path, &manifest);
+        }
+
+        // Directory order is arbitrary, so it can't be relied on.
+        files.sort_by_key(|(id, index, _)| (id.canonical_rank_in(&options.names), *index));
+
+        let segments = files
+            .into_iter()
+            .map(|(id, _, path)| {
+                let mut seg = Segment::new(id, fs::read(path).map_err(FromDirError::Io)?);
+                seg.sign();
+
+                Ok(seg)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(segments, options.image_version))
+    }
+
+    fn from_manifest(path: &Path, manifest: &DirManifest) -> Result<Self, FromDirError> {
+        let segments = manifest
+            .segments
+            .iter()
+            .map(|entry| {
+                let data = fs::read(path.join(&entry.file_name)).map_err(FromDirError::Io)?;
+                // Only modified data need a new signature.
+                let unmodified = Digest::sha1_of(&data) == entry.fingerprint;
+
+                let mut seg = Segment::new(entry.id, data);
+                seg.set_signature_kind(entry.signature_kind);
+                seg.set_alignment(entry.alignment);
+                if unmodified {
+                    seg.set_signature(entry.signature);
+                } else {
+                    seg.sign();
+                }
+
+                Ok(seg)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut pup = Self::new(segments, manifest.image_version);
+        pup.package_version = manifest.package_version;
+
+        Ok(pup)
+    }
+}
+
+// The segment ID of a file name, along with the index 'unpack' appends to all but the first of
+// several segments sharing an ID (0 for that first one).
+fn segment_of(file_name: &str, names: &SegmentNameMap) -> Option<(SegmentId, usize)> {
+    let id_of = |x: &str| {
+        names.id_of(x).or_else(|| {
+            x.strip_prefix("0x")?
+                .strip_suffix(".bin")
+                .and_then(|x| u64::from_str_radix(x, 16).ok())
+                .map(SegmentId)
+        })
+    };
+
+    if let Some(id) = id_of(file_name) {
+        return Some((id, 0));
+    }
+
+    let (file_name, index) = file_name.rsplit_once('.')?;
+
+    Some((id_of(file_name)?, index.parse().ok()?))
+}
+
+/// Options for [`Pup::from_dir`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FromDirOptions {
+    /// The file names of segment IDs.
+    ///
+    /// These are the well-known names by default; override a name with
+    /// [`SegmentNameMap::insert`].
+    pub names: SegmentNameMap,
+    /// The image version of the [`Pup`].
+    pub image_version: u64,
+    /// Whether to skip files whose segment ID can't be determined, rather than fail with
+    /// [`FromDirError::UnknownFile`].
+    pub skip_unknown: bool,
+}
+
+/// An erroneous result returned by [`Pup::from_dir`].
+#[derive(Debug)]
+pub enum FromDirError {
+    /// The segment ID of a file can't be determined from its name.
+    UnknownFile(PathBuf),
+    /// The [`DirManifest`] of the directory is malformed.
+    Manifest(String),
+    /// Reading the directory or a file failed.
+    Io(io::Error),
+}
+
+impl Display for FromDirError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownFile(path) => {
+                write!(f, "'{}' isn't named after a segment", path.display())
+            }
+            Self::Manifest(err) => write!(f, "'{}' is malformed: {}", DirManifest::FILE_NAME, err),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-pub(crate) mod sig;
+pub(crate) mod digest;
 pub(crate) mod meta;
 pub(crate) mod seg;
 mod table;
@@ -6,74 +6,144 @@ mod table;
 use meta::Metadata;
 use table::Table;
 
-use crate::{Digest, Error, FixedSize, Pup};
+use crate::{Digest, FixedSize, ParseError, Pup};
 
 use std::convert::{TryFrom, TryInto as _};
 
+// A PUP header consists of two distinct kinds of digests:
+//
+//   a. The digest table, which holds one signature per segment. It is always generated from the
+//      segments themselves.
+//   b. The header signature, which signs everything in the header that precedes it (i.e., the
+//      metadata, the segment table, and the digest table). It is always generated from the rest
+//      of the header.
 #[derive(Clone, Default)]
 pub struct Header {
     pub meta: Metadata,
     pub seg_table: Table<seg::Entry>,
-    pub sig_table: Table<sig::Entry>,
+    pub digest_table: Table<digest::Entry>,
     header_sig: Digest,
 }
 
 impl Header {
+    /// Creates a new [`Header`], signing it with [`crate::HMAC_KEY`].
     pub fn new(
         meta: Metadata,
         seg_table: Table<seg::Entry>,
-        sig_table: Table<sig::Entry>,
+        digest_table: Table<digest::Entry>,
     ) -> Self {
-        Self {
+        let mut header = Self {
             meta,
             seg_table,
-            sig_table,
+            digest_table,
             header_sig: Digest::default(),
-        }
-    }
+        };
+        header.header_sig = Digest::hmac_sha1_of(&header.signed_data());
 
-    pub fn header_sig(&self) -> &Digest {
-        &self.header_sig
+        header
     }
-}
 
-impl TryFrom<&[u8]> for Header {
-    type Error = Error;
-
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    /// Parses a [`Header`], optionally accepting any package version.
+    pub fn parse(data: &[u8], check_pkg_version: bool) -> Result<Self, ParseError> {
         let meta: Metadata = data
             .get(..Metadata::SIZE)
-            .ok_or(Self::Error::Undersized)
+            .ok_or(ParseError::Undersized)
             .and_then(|x| <&[u8; Metadata::SIZE]>::try_from(x).unwrap().try_into())?;
+        if check_pkg_version {
+            meta.check_pkg_version()?;
+        }
         let data = &data[Metadata::SIZE..];
 
-        let seg_table_size = (meta.seg_count as usize) * seg::Entry::SIZE;
+        // The segment count comes from the file, so the table sizes may not even be representable.
+        let table_size = |entry_size: usize| {
+            usize::try_from(meta.seg_count)
+                .ok()
+                .and_then(|x| x.checked_mul(entry_size))
+                .ok_or(ParseError::Undersized)
+        };
+
+        let seg_table_size = table_size(seg::Entry::SIZE)?;
         let seg_table = data
             .get(..seg_table_size)
-            .ok_or(Self::Error::Undersized)
+            .ok_or(ParseError::Undersized)
             .and_then(|x| x.try_into())?;
         let data = &data[seg_table_size..];
 
-        let sig_table_size = (meta.seg_count as usize) * sig::Entry::SIZE;
-        let sig_table = data
-            .get(..sig_table_size)
-            .ok_or(Self::Error::Undersized)
+        let digest_table_size = table_size(digest::Entry::SIZE)?;
+        let digest_table = data
+            .get(..digest_table_size)
+            .ok_or(ParseError::Undersized)
             .and_then(|x| x.try_into())?;
-        let data = &data[sig_table_size..];
+        let data = &data[digest_table_size..];
 
         let header_sig = data
             .get(..Digest::SIZE)
-            .ok_or(Self::Error::Undersized)
+            .ok_or(ParseError::Undersized)
             .map(|x| <[u8; Digest::SIZE]>::try_from(x).unwrap())?;
         let header_sig = Digest(header_sig);
 
         Ok(Self {
             meta,
             seg_table,
-            sig_table,
+            digest_table,
             header_sig,
         })
     }
+
+    pub fn header_sig(&self) -> &Digest {
+        &self.header_sig
+    }
+
+    /// The data size this header should declare: the sum of all segment sizes, plus any gaps
+    /// left between segments (e.g., for alignment). Overlapping segments add no gap.
+    pub fn padded_data_size(&self) -> u64 {
+        let mut end = self.meta.header_size;
+
+        self.seg_table.iter().fold(0, |size, entry| {
+            let gap = entry.offset.saturating_sub(end);
+            end = end.max(entry.offset.saturating_add(entry.size));
+
+            size.saturating_add(gap).saturating_add(entry.size)
+        })
+    }
+
+    /// The size of a header with the given segment count, excluding padding.
+    ///
+    /// This saturates at [`u64::MAX`] for segment counts no header could hold.
+    pub fn unpadded_size(seg_count: u64) -> u64 {
+        let entry_size = (seg::Entry::SIZE + digest::Entry::SIZE) as u64;
+
+        seg_count
+            .saturating_mul(entry_size)
+            .saturating_add((Metadata::SIZE + Digest::SIZE) as u64)
+    }
+
+    /// The size of a header with the given segment count, rounded up to a multiple of 0x10 like
+    /// official PUPs (e.g., 0x350 bytes for 12 segments).
+    ///
+    /// This saturates like [`Self::unpadded_size`].
+    pub fn padded_size(seg_count: u64) -> u64 {
+        Self::unpadded_size(seg_count).saturating_add(0xF) & !0xF
+    }
+
+    /// The serialized portion of this header covered by the header signature.
+    fn signed_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        data.append(&mut <[u8; Metadata::SIZE]>::from(self.meta).into());
+        data.append(&mut Vec::from(&self.seg_table));
+        data.append(&mut Vec::from(&self.digest_table));
+
+        data
+    }
+}
+
+impl TryFrom<&[u8]> for Header {
+    type Error = ParseError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, true)
+    }
 }
 
 impl From<&Pup> for Header {
@@ -84,11 +154,7 @@ impl From<&Pup> for Header {
 
 impl From<&Header> for Vec<u8> {
     fn from(header: &Header) -> Self {
-        let mut data = Self::new();
-
-        data.append(&mut <[u8; Metadata::SIZE]>::from(header.meta).into());
-        data.append(&mut Self::from(&header.seg_table));
-        data.append(&mut Self::from(&header.sig_table));
+        let mut data = header.signed_data();
         data.append(&mut header.header_sig.0.into());
 
         // Pad the header to the requested size.
@@ -10,7 +10,7 @@ impl From<&Pup> for super::Table<Entry> {
             .enumerate()
             .map(|(i, seg)| Entry {
                 seg_index: i as u64,
-                sig: *seg.signature(),
+                digest: *seg.signature(),
             })
             .collect();
 
@@ -21,17 +21,17 @@ impl From<&Pup> for super::Table<Entry> {
 #[derive(Clone, Copy, Default)]
 pub struct Entry {
     pub seg_index: u64,
-    pub sig: Digest,
+    pub digest: Digest,
 }
 
 impl TryFrom<&[u8; Self::SIZE]> for Entry {
-    type Error = crate::Error;
+    type Error = crate::ParseError;
 
     fn try_from(data: &[u8; Self::SIZE]) -> Result<Self, Self::Error> {
         let seg_index = u64::from_be_bytes(data[0x00..0x08].try_into().unwrap());
-        let sig = Digest(data[0x08..0x1C].try_into().unwrap());
+        let digest = Digest(data[0x08..0x1C].try_into().unwrap());
 
-        Ok(Self { seg_index, sig })
+        Ok(Self { seg_index, digest })
     }
 }
 
@@ -40,7 +40,7 @@ impl From<Entry> for [u8; Entry::SIZE] {
         let mut data = [0; Entry::SIZE];
 
         data[0x00..0x08].copy_from_slice(&entry.seg_index.to_be_bytes());
-        data[0x08..0x1C].copy_from_slice(&entry.sig.0);
+        data[0x08..0x1C].copy_from_slice(&entry.digest.0);
 
         data
     }
@@ -4,6 +4,7 @@ use std::convert::{TryFrom, TryInto as _};
 
 #[derive(Clone, Copy, Default)]
 pub struct Metadata {
+    pub pkg_version: u64,
     pub img_version: u64,
     pub seg_count: u64,
     pub header_size: u64,
@@ -11,7 +12,7 @@ pub struct Metadata {
 }
 
 impl TryFrom<&[u8; Self::SIZE]> for Metadata {
-    type Error = crate::Error;
+    type Error = crate::ParseError;
 
     fn try_from(data: &[u8; Self::SIZE]) -> Result<Self, Self::Error> {
         // First, check the magic to verify this is actually a PUP file.
@@ -20,19 +21,16 @@ impl TryFrom<&[u8; Self::SIZE]> for Metadata {
             return Err(Self::Error::InvalidMagic(magic));
         }
 
-        // Next, check the package version to verify this PUP file is supported.
+        // Whether the package version is supported is up to the caller; see
+        // `Self::check_pkg_version`.
         let pkg_version = u64::from_be_bytes(data[0x08..0x10].try_into().unwrap());
-        if pkg_version != Self::PKG_VERSION {
-            return Err(Self::Error::UnsupportedPackageVersion(pkg_version));
-        }
-
-        // LGTM.
         let img_version = u64::from_be_bytes(data[0x10..0x18].try_into().unwrap());
         let seg_count = u64::from_be_bytes(data[0x18..0x20].try_into().unwrap());
         let header_size = u64::from_be_bytes(data[0x20..0x28].try_into().unwrap());
         let data_size = u64::from_be_bytes(data[0x28..0x30].try_into().unwrap());
 
         Ok(Self {
+            pkg_version,
             img_version,
             seg_count,
             header_size,
@@ -44,6 +42,7 @@ impl TryFrom<&[u8; Self::SIZE]> for Metadata {
 impl From<&Pup> for Metadata {
     fn from(pup: &Pup) -> Self {
         Self {
+            pkg_version: pup.package_version,
             img_version: pup.image_version,
             seg_count: pup.segments.len() as u64,
             header_size: pup.header_size() as u64,
@@ -57,7 +56,7 @@ impl From<Metadata> for [u8; Metadata::SIZE] {
         let mut data = [0; Metadata::SIZE];
 
         data[0x00..0x08].copy_from_slice(&Magic::default().0);
-        data[0x08..0x10].copy_from_slice(&Metadata::PKG_VERSION.to_be_bytes());
+        data[0x08..0x10].copy_from_slice(&meta.pkg_version.to_be_bytes());
         data[0x10..0x18].copy_from_slice(&meta.img_version.to_be_bytes());
         data[0x18..0x20].copy_from_slice(&meta.seg_count.to_be_bytes());
         data[0x20..0x28].copy_from_slice(&meta.header_size.to_be_bytes());
@@ -72,5 +71,17 @@ impl FixedSize for Metadata {
 }
 
 impl Metadata {
-    const PKG_VERSION: u64 = 1;
+    /// The only package version known to be in use.
+    pub(crate) const PKG_VERSION: u64 = 1;
+
+    /// Fails if the package version isn't [`Self::PKG_VERSION`].
+    pub fn check_pkg_version(&self) -> Result<(), crate::ParseError> {
+        if self.pkg_version == Self::PKG_VERSION {
+            Ok(())
+        } else {
+            Err(crate::ParseError::UnsupportedPackageVersion(
+                self.pkg_version,
+            ))
+        }
+    }
 }
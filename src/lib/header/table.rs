@@ -5,34 +5,35 @@ use crate::FixedSize;
 #[derive(Clone, Default)]
 pub struct Table<T>(pub Vec<T>);
 
-impl<'a, T: FixedSize + TryFrom<&'a [u8; T::SIZE]>> TryFrom<&'a [u8]> for Table<T>
-where
-    [(); T::SIZE]: Sized,
-{
-    type Error = crate::Error;
-
-    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        let entries = data
-            .chunks_exact(T::SIZE)
-            .flat_map(|x| <&[u8; T::SIZE]>::try_from(x).unwrap().try_into())
-            .collect();
-
-        Ok(Self(entries))
-    }
+macro_rules! impl_table {
+    ($t:ty) => {
+        impl<'a> TryFrom<&'a [u8]> for Table<$t> {
+            type Error = crate::ParseError;
+
+            fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+                let entries = data
+                    .chunks_exact(<$t>::SIZE)
+                    .flat_map(|x| <&[u8; <$t>::SIZE]>::try_from(x).unwrap().try_into())
+                    .collect();
+
+                Ok(Self(entries))
+            }
+        }
+
+        impl From<&Table<$t>> for Vec<u8> {
+            fn from(table: &Table<$t>) -> Self {
+                table
+                    .0
+                    .iter()
+                    .flat_map(|x| <[u8; <$t>::SIZE]>::from(*x).to_vec())
+                    .collect()
+            }
+        }
+    };
 }
 
-impl<'a, T: FixedSize + Copy> From<&Table<T>> for Vec<u8>
-where
-    [u8; T::SIZE]: From<T>,
-{
-    fn from(table: &Table<T>) -> Self {
-        table
-            .0
-            .iter()
-            .flat_map(|x| <[u8; T::SIZE]>::from(*x).to_vec())
-            .collect()
-    }
-}
+impl_table!(super::seg::Entry);
+impl_table!(super::digest::Entry);
 
 impl<T> std::ops::Deref for Table<T> {
     type Target = Vec<T>;
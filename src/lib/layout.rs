@@ -0,0 +1,345 @@
+use crate::{
+    header::{digest, meta::Metadata, seg, Header},
+    Digest, FixedSize as _, Pup, RawHeader, SegmentId,
+};
+
+use std::fmt::{self, Display, Formatter};
+
+/// The offset, size, and name of each field of the header metadata.
+const METADATA_FIELDS: [(u64, u64, &str); 6] = [
+    (0x00, 0x08, "magic"),
+    (0x08, 0x08, "package version"),
+    (0x10, 0x08, "image version"),
+    (0x18, 0x08, "segment count"),
+    (0x20, 0x08, "header size"),
+    (0x28, 0x08, "data size"),
+];
+
+/// The offset, size, and name of each field of a segment table entry.
+const SEGMENT_ENTRY_FIELDS: [(u64, u64, &str); 5] = [
+    (0x00, 0x08, "id"),
+    (0x08, 0x08, "offset"),
+    (0x10, 0x08, "size"),
+    (0x18, 0x04, "signature kind"),
+    (0x1C, 0x04, "padding"),
+];
+
+/// The offset, size, and name of each field of a digest table entry.
+const DIGEST_ENTRY_FIELDS: [(u64, u64, &str); 3] = [
+    (0x00, 0x08, "segment index"),
+    (0x08, 0x14, "digest"),
+    (0x1C, 0x04, "padding"),
+];
+
+impl Pup {
+    /// Describes every byte this [`Pup`] would have if it were serialized, as
+    /// [`RawHeader::annotate`] does for a parsed header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+    ///
+    /// let regions = pup.annotate();
+    /// let covered: u64 = regions.iter().map(|x| x.length).sum();
+    ///
+    /// assert_eq!("metadata.magic", regions[0].name);
+    /// assert_eq!(Vec::<u8>::from(&pup).len() as u64, covered);
+    /// ```
+    #[must_use]
+    pub fn annotate(&self) -> Vec<Region> {
+        RawHeader::from(&Header::from(self)).annotate()
+    }
+}
+
+impl RawHeader {
+    /// Describes every structure in the PUP this header was read from: each header field, the
+    /// header signature and padding, and the data of each segment.
+    ///
+    /// Regions are in file order, except that segment data are in segment table order. Bytes a
+    /// malformed PUP doesn't account for (or accounts for twice) aren't pointed out, but can be
+    /// found by comparing the regions against the file size.
+    #[must_use]
+    pub fn annotate(&self) -> Vec<Region> {
+        let meta = &self.metadata;
+        let mut regions = Vec::new();
+
+        let meta_values = [
+            Some("SCEUF".to_string()),
+            Some(meta.package_version.to_string()),
+            Some(meta.image_version.to_string()),
+            Some(meta.segment_count.to_string()),
+            Some(format!("{:#x}", meta.header_size)),
+            Some(format!("{:#x}", meta.data_size)),
+        ];
+        annotate_fields(&mut regions, "metadata", 0, &METADATA_FIELDS, &meta_values);
+
+        let mut start = Metadata::SIZE as u64;
+        for (i, entry) in self.segment_entries.iter().enumerate() {
+            let values = [
+                Some(entry.id.to_string()),
+                Some(format!("{:#x}", entry.offset)),
+                Some(format!("{:#x}", entry.size)),
+                Some(entry.signature_kind.to_string()),
+                None,
+            ];
+            let prefix = format!("segment_table[{}]", i);
+            annotate_fields(&mut regions, &prefix, start, &SEGMENT_ENTRY_FIELDS, &values);
+
+            start += seg::Entry::SIZE as u64;
+        }
+
+        for (i, entry) in self.digest_entries.iter().enumerate() {
+            let values = [
+                Some(entry.segment_index.to_string()),
+                Some(entry.digest.to_string()),
+                None,
+            ];
+            let prefix = format!("digest_table[{}]", i);
+            annotate_fields(&mut regions, &prefix, start, &DIGEST_ENTRY_FIELDS, &values);
+
+            start += digest::Entry::SIZE as u64;
+        }
+
+        regions.push(Region {
+            name: "header_signature".into(),
+            start,
+            length: Digest::SIZE as u64,
+            description: format!("header signature: {}", self.header_signature),
+        });
+        start += Digest::SIZE as u64;
+
+        if meta.header_size > start {
+            regions.push(Region {
+                name: "header_padding".into(),
+                start,
+                length: meta.header_size - start,
+                description: "padding".into(),
+            });
+        }
+
+        for (i, entry) in self.segment_entries.iter().enumerate() {
+            regions.push(Region {
+                name: format!("segment[{}]", i),
+                start: entry.offset,
+                length: entry.size,
+                description: format!("data of segment {}", entry.id),
+            });
+        }
+
+        regions
+    }
+
+    /// Finds what lives at a specific file offset of the PUP this header was read from.
+    ///
+    /// Header structures take precedence over segment data, and earlier segments over later
+    /// ones. Returns [`None`] if the offset is neither in the header nor in any segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Location, Pup, RawHeader, Segment, SegmentId};
+    /// use std::convert::TryFrom as _;
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x10]));
+    /// pup.segments.push(Segment::new(SegmentId(0x300), vec![0; 0x10]));
+    /// let data = Vec::<u8>::from(&pup);
+    /// let header = RawHeader::try_from(data.as_slice()).unwrap();
+    ///
+    /// // The size field of the second segment table entry.
+    /// assert_eq!(
+    ///     Some(Location::SegmentEntry { index: 1, field: "size", offset: 0 }),
+    ///     header.locate(0x30 + 0x20 + 0x10),
+    /// );
+    ///
+    /// let start = header.segment_entries[1].offset;
+    /// assert_eq!(
+    ///     Some(Location::SegmentData { index: 1, id: SegmentId(0x300), offset: 4 }),
+    ///     header.locate(start + 4),
+    /// );
+    /// assert_eq!(None, header.locate(start + 0x10));
+    /// ```
+    #[must_use]
+    pub fn locate(&self, offset: u64) -> Option<Location> {
+        let seg_count = self.segment_entries.len() as u64;
+        let seg_table_start = Metadata::SIZE as u64;
+        let digest_table_start = seg_table_start + (seg_count * seg::Entry::SIZE as u64);
+        let sig_start = digest_table_start + (seg_count * digest::Entry::SIZE as u64);
+        let padding_start = sig_start + Digest::SIZE as u64;
+
+        if offset < seg_table_start {
+            let (field, offset) = find_field(&METADATA_FIELDS, offset)?;
+            return Some(Location::Metadata { field, offset });
+        }
+        if offset < digest_table_start {
+            let relative = offset - seg_table_start;
+            let (field, offset) =
+                find_field(&SEGMENT_ENTRY_FIELDS, relative % seg::Entry::SIZE as u64)?;
+
+            return Some(Location::SegmentEntry {
+                index: relative / seg::Entry::SIZE as u64,
+                field,
+                offset,
+            });
+        }
+        if offset < sig_start {
+            let relative = offset - digest_table_start;
+            let (field, offset) =
+                find_field(&DIGEST_ENTRY_FIELDS, relative % digest::Entry::SIZE as u64)?;
+
+            return Some(Location::DigestEntry {
+                index: relative / digest::Entry::SIZE as u64,
+                field,
+                offset,
+            });
+        }
+        if offset < padding_start {
+            return Some(Location::HeaderSignature {
+                offset: offset - sig_start,
+            });
+        }
+        if offset < self.metadata.header_size {
+            return Some(Location::HeaderPadding {
+                offset: offset - padding_start,
+            });
+        }
+
+        self.segment_entries
+            .iter()
+            .enumerate()
+            .find(|(_, x)| offset >= x.offset && offset - x.offset < x.size)
+            .map(|(i, x)| Location::SegmentData {
+                index: i as u64,
+                id: x.id,
+                offset: offset - x.offset,
+            })
+    }
+}
+
+fn annotate_fields(
+    regions: &mut Vec<Region>,
+    prefix: &str,
+    start: u64,
+    fields: &[(u64, u64, &str)],
+    values: &[Option<String>],
+) {
+    for ((offset, size, field), value) in fields.iter().zip(values.iter()) {
+        regions.push(Region {
+            name: format!("{}.{}", prefix, field.replace(' ', "_")),
+            start: start + offset,
+            length: *size,
+            description: match value {
+                Some(value) => format!("{}: {}", field, value),
+                None => field.to_string(),
+            },
+        });
+    }
+}
+
+fn find_field(fields: &[(u64, u64, &'static str)], offset: u64) -> Option<(&'static str, u64)> {
+    fields
+        .iter()
+        .find(|(start, size, _)| offset >= *start && offset - start < *size)
+        .map(|(start, _, name)| (*name, offset - start))
+}
+
+/// A run of bytes in a PUP, as described by [`RawHeader::annotate`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Region {
+    /// A unique, machine-friendly name (e.g., `segment_table[2].size`).
+    pub name: String,
+    /// The file offset of the first byte.
+    pub start: u64,
+    /// The length, in bytes.
+    pub length: u64,
+    /// A human-readable description, including the value of header fields.
+    pub description: String,
+}
+
+/// What lives at a file offset of a PUP, as found by [`RawHeader::locate`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Location {
+    /// A field of the header metadata.
+    Metadata {
+        /// The name of the field.
+        field: &'static str,
+        /// The offset into the field.
+        offset: u64,
+    },
+    /// A field of a segment table entry.
+    SegmentEntry {
+        /// The index of the entry.
+        index: u64,
+        /// The name of the field.
+        field: &'static str,
+        /// The offset into the field.
+        offset: u64,
+    },
+    /// A field of a digest table entry.
+    DigestEntry {
+        /// The index of the entry.
+        index: u64,
+        /// The name of the field.
+        field: &'static str,
+        /// The offset into the field.
+        offset: u64,
+    },
+    /// The header signature.
+    HeaderSignature {
+        /// The offset into the signature.
+        offset: u64,
+    },
+    /// The padding between the header signature and the end of the header.
+    HeaderPadding {
+        /// The offset into the padding.
+        offset: u64,
+    },
+    /// The data of a segment.
+    SegmentData {
+        /// The index of the segment.
+        index: u64,
+        /// The ID of the segment.
+        id: SegmentId,
+        /// The offset into the segment data.
+        offset: u64,
+    },
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Metadata { field, offset } => {
+                write!(f, "header metadata, field `{}`, byte {}", field, offset)
+            }
+            Self::SegmentEntry {
+                index,
+                field,
+                offset,
+            } => write!(
+                f,
+                "segment table entry {}, field `{}`, byte {}",
+                index, field, offset
+            ),
+            Self::DigestEntry {
+                index,
+                field,
+                offset,
+            } => write!(
+                f,
+                "digest table entry {}, field `{}`, byte {}",
+                index, field, offset
+            ),
+            Self::HeaderSignature { offset } => write!(f, "header signature, byte {}", offset),
+            Self::HeaderPadding { offset } => write!(f, "header padding, byte {}", offset),
+            Self::SegmentData { index, id, offset } => write!(
+                f,
+                "inside segment {} with ID {}, offset {:#x} into segment data",
+                index, id, offset
+            ),
+        }
+    }
+}
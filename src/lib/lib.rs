@@ -42,7 +42,7 @@
 //!
 //! // Segment is (mostly) a POD type, too!
 //! assert_eq!(id, segment.id);
-//! assert_eq!(data, segment.data);
+//! assert_eq!(data, segment.data());
 //!
 //! pup.segments.push(segment.clone());
 //! assert_eq!(segment, pup.segments[0]);
@@ -68,20 +68,71 @@
 
 #![deny(missing_docs)]
 
-// TODO: Remove these after stabilization.
-#![feature(const_evaluatable_checked, const_generics)]
-
+mod analyze;
+mod build_info;
+pub mod content;
+pub mod diff;
+mod digest_cache;
+mod dir_manifest;
+mod edit;
+mod firmware;
+mod from_dir;
 mod header;
+mod layout;
+mod names;
+mod parallel;
+mod parse;
+mod progress;
+mod reader;
+mod recover;
+mod search;
+mod serialize;
+mod sniff;
+mod stats;
+mod summary;
+mod update_list;
+mod usb;
+mod validate;
+mod version;
+mod walk;
 
-use header::Header;
+pub use analyze::SegmentAnalysis;
+pub use build_info::{BuildInfo, ImageVersionMismatch, SelfInfo, SelfType};
+pub use digest_cache::DigestCache;
+pub use dir_manifest::{DirManifest, DirManifestEntry};
+pub use edit::Transaction;
+pub use firmware::{Deviation, FirmwareDb, Release, ReleaseSegment};
+pub use from_dir::{FromDirError, FromDirOptions};
+pub use layout::{Location, Region};
+pub use names::SegmentNameMap;
+pub use parallel::Parallelism;
+pub use parse::{Diagnostic, ParseOptions, PupStream, ValidationLevel};
+pub use progress::{CancellationToken, Progress};
+pub use reader::SegmentReader;
+pub use recover::{carve, recover, Recovery, RecoveryNote};
+pub use search::{search, SearchMatch};
+pub use serialize::{DuplicatePolicy, SerializeOptions, WriteError};
+pub use sniff::{is_pup, sniff, PupKind};
+pub use stats::{SegmentStats, Stats};
+pub use summary::{
+    verify, verify_with_key, verify_with_progress, PupSummary, SegmentSummary, SummaryOptions,
+    VerificationSummary,
+};
+pub use update_list::{UpdateList, UpdateListEntry, UpdateListMismatch};
+pub use usb::{install_to_usb, UsbError, USB_UPDATE_PATH};
+pub use validate::{validate, Finding};
+pub use version::FirmwareVersion;
+pub use walk::{DigestEntry, HeaderMetadata, PupVisitor, RawHeader, SegmentEntry};
 
 use std::{
-    convert::{TryFrom, TryInto as _},
+    convert::TryFrom,
     fmt::{self, Display, Formatter},
+    num::NonZeroU64,
+    str::FromStr,
 };
 
 /// A PS3 PUP (PlayStation Update Package).
-#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Pup {
     /// The segments, or files, contained in this PUP.
     pub segments: Vec<Segment>,
@@ -91,81 +142,94 @@ pub struct Pup {
     /// Presumably, this field identifies the revision of this PUP's contents. I don't work for
     /// Sony, though. ¯\_(ツ)_/¯
     pub image_version: u64,
-}
 
-impl TryFrom<&[u8]> for Pup {
-    type Error = Error;
+    /// The package version of this PUP, i.e., the version of the format itself.
+    ///
+    /// This is always 1 in practice, and other values only parse with [`ValidationLevel::None`].
+    /// They are serialized as is, though, for experimentation's sake.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{ParseOptions, Pup, ValidationLevel};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.package_version = 2;
+    /// let data = Vec::<u8>::from(&pup);
+    ///
+    /// assert!(Pup::parse(&data, &mut ParseOptions::default()).is_err());
+    ///
+    /// let parsed = Pup::parse(
+    ///     &data,
+    ///     &mut ParseOptions {
+    ///         validation: ValidationLevel::None,
+    ///         ..Default::default()
+    ///     },
+    /// );
+    /// assert_eq!(Ok(pup), parsed);
+    /// ```
+    pub package_version: u64,
 
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        let header: Header = data.try_into()?;
+    /// Digest table entries that refer to no segment, as found while parsing.
+    ///
+    /// These can only be parsed with [`ValidationLevel::None`], and aren't serialized: the digest
+    /// table has exactly one entry per segment, so there is no room for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{ParseOptions, Pup, Segment, SegmentId, ValidationLevel};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), Vec::new()));
+    /// pup.segments.push(Segment::new(SegmentId(0x101), Vec::new()));
+    /// let mut data = Vec::<u8>::from(&pup);
+    ///
+    /// // Point the second digest table entry at a nonexistent segment.
+    /// data[0x97] = 5;
+    ///
+    /// let parsed = Pup::parse(
+    ///     &data,
+    ///     &mut ParseOptions {
+    ///         validation: ValidationLevel::None,
+    ///         ..Default::default()
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(1, parsed.segments.len());
+    /// assert_eq!(5, parsed.extra_digests[0].segment_index);
+    /// ```
+    pub extra_digests: Vec<DigestEntry>,
 
-        // First (and most importantly), we generate segments, drawing from three separate
-        // locations: the segment table, the digest table, and the actual data.
-        let segments = header
-            .seg_table
-            .iter()
-            .enumerate()
-            .flat_map(|(i, entry)| {
-                let i = i as u64;
+    header_sig: Option<Digest>,
+}
 
-                let sig = header
-                    .sig_table
-                    .iter()
-                    .find(|x| x.seg_index == i)
-                    .ok_or(Self::Error::MissingSignature(i))
-                    .map(|x| x.sig)?;
-
-                let data = {
-                    // [may_panic(Add)]
-                    let start = entry.offset as usize;
-                    let end = start + (entry.size as usize);
-
-                    data.get(start..end)
-                        .ok_or(Self::Error::MissingData(i))
-                        .map(|x| x.to_vec())?
-                };
-
-                let seg = Segment {
-                    id: entry.id,
-                    data,
-                    sig,
-                };
-
-                Result::<Segment, Self::Error>::Ok(seg)
-            })
-            .collect();
+impl Default for Pup {
+    fn default() -> Self {
+        Self::new(Vec::new(), 0)
+    }
+}
 
-        // Next, we copy over metadata that aren't inherently represented in the segments.
-        Ok(Self {
-            segments,
-            image_version: header.meta.img_version,
-        })
+impl TryFrom<&[u8]> for Pup {
+    type Error = ParseError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(data, &mut ParseOptions::default())
     }
 }
 
+/// Serializes a [`Pup`] with the default [`SerializeOptions`].
+///
+/// # Panics
+///
+/// Panics if the [`Pup`] is too large to be serialized on this platform. Use [`Pup::serialize`]
+/// to handle that case instead.
 impl From<&Pup> for Vec<u8> {
     fn from(pup: &Pup) -> Self {
-        // Create the header first to generate the segment table and location information.
-        let header = Header::from(pup);
-
-        let header_size = header.meta.header_size as usize;
-        let data_size = header.meta.data_size as usize;
-
-        // [may_panic(Add)]
-        let mut data = Self::from(&header);
-        data.resize_with(header_size + data_size, Default::default);
-
-        // Fill in data according to the offset and size specified by the segment entries.
-        // Note: This will crash and burn if Header::from() gets things wrong...
-        for (i, entry) in header.seg_table.iter().enumerate() {
-            // [may_panic(Add)]
-            let start = entry.offset as usize;
-            let end = start + (entry.size as usize);
-
-            data[start..end].copy_from_slice(&pup.segments[i].data);
-        }
-
-        data
+        // Without a cancellation token, the size is the only thing that can go wrong.
+        pup.to_bytes(&mut Progress::default())
+            .expect("PUP is too large to serialize")
     }
 }
 
@@ -176,37 +240,313 @@ impl Pup {
         Self {
             segments,
             image_version,
+            package_version: header::meta::Metadata::PKG_VERSION,
+            extra_digests: Vec::new(),
+            header_sig: None,
+        }
+    }
+
+    /// The header signature this [`Pup`] was parsed with, if it was parsed with
+    /// [`ParseOptions::record_provenance`].
+    ///
+    /// This is only kept so that it can be checked later (e.g., against another key). Serializing
+    /// always signs the header anew, regardless of this signature or whether anything changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{ParseOptions, Pup};
+    ///
+    /// let data = Vec::<u8>::from(&Pup::default());
+    /// let parsed = Pup::parse(
+    ///     &data,
+    ///     &mut ParseOptions {
+    ///         record_provenance: true,
+    ///         ..Default::default()
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(None, Pup::default().header_signature());
+    /// assert_eq!(&data[0x30..0x44], &parsed.header_signature().unwrap().0);
+    /// ```
+    #[must_use]
+    pub fn header_signature(&self) -> Option<&Digest> {
+        self.header_sig.as_ref()
+    }
+
+    /// Creates a [`Pup`] with an empty, signed segment for each well-known ID, in the order used
+    /// by official firmware.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, SegmentId};
+    ///
+    /// let pup = Pup::standard(0);
+    ///
+    /// assert_eq!(12, pup.segments.len());
+    /// assert_eq!(SegmentId(0x100), pup.segments[0].id);
+    /// assert_eq!(SegmentId(0x601), pup.segments[11].id);
+    /// assert!(pup.validate().is_empty());
+    /// ```
+    #[must_use]
+    pub fn standard(image_version: u64) -> Self {
+        let segments = SEGMENT_ID_MAP
+            .iter()
+            .map(|(id, _)| {
+                let mut seg = Segment::new(SegmentId(*id), Vec::new());
+                seg.sign();
+
+                seg
+            })
+            .collect();
+
+        Self::new(segments, image_version)
+    }
+
+    /// Sorts segments into the order used by official firmware.
+    ///
+    /// Segments with well-known IDs come first, in the conventional order; the rest follow,
+    /// ordered by ID. The relative order of segments sharing an ID is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x9999), Vec::new()));
+    /// pup.segments.push(Segment::new(SegmentId(0x300), Vec::new()));
+    /// pup.segments.push(Segment::new(SegmentId(0x100), Vec::new()));
+    ///
+    /// pup.sort_canonical();
+    ///
+    /// let ids: Vec<u64> = pup.segments.iter().map(|x| x.id.0).collect();
+    /// assert_eq!(vec![0x100, 0x300, 0x9999], ids);
+    /// ```
+    pub fn sort_canonical(&mut self) {
+        self.segments.sort_by_key(|x| x.id.canonical_rank());
+    }
+
+    /// Inserts a segment, returning its index.
+    ///
+    /// Fails with [`Error::DuplicateSegmentId`] if a segment already has the same ID. If
+    /// `canonical` is `true`, the segment is inserted before the first segment that
+    /// [`Self::sort_canonical`] would place after it; otherwise, it's appended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Error, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.insert_segment(Segment::new(SegmentId(0x100), Vec::new()), true).unwrap();
+    /// pup.insert_segment(Segment::new(SegmentId(0x300), Vec::new()), true).unwrap();
+    ///
+    /// assert_eq!(Ok(1), pup.insert_segment(Segment::new(SegmentId(0x200), Vec::new()), true));
+    /// assert_eq!(
+    ///     Err(Error::DuplicateSegmentId(SegmentId(0x100))),
+    ///     pup.insert_segment(Segment::new(SegmentId(0x100), Vec::new()), true),
+    /// );
+    ///
+    /// let ids: Vec<u64> = pup.segments.iter().map(|x| x.id.0).collect();
+    /// assert_eq!(vec![0x100, 0x200, 0x300], ids);
+    /// ```
+    pub fn insert_segment(&mut self, seg: Segment, canonical: bool) -> Result<usize, Error> {
+        if self.segments.iter().any(|x| x.id == seg.id) {
+            return Err(Error::DuplicateSegmentId(seg.id));
+        }
+
+        let rank = seg.id.canonical_rank();
+        let index = self
+            .segments
+            .iter()
+            .position(|x| canonical && x.id.canonical_rank() > rank)
+            .unwrap_or(self.segments.len());
+        self.segments.insert(index, seg);
+
+        Ok(index)
+    }
+
+    /// Normalizes this [`Pup`] so that equivalent packages serialize identically.
+    ///
+    /// Segments are sorted with [`Self::sort_canonical`] and, if `resign` is `true`, re-signed
+    /// with [`HMAC_KEY`]. Everything else that could differ between equivalent packages (i.e.,
+    /// segment offsets, padding, and the digest table) is regenerated from scratch whenever a
+    /// [`Pup`] is serialized anyway.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let a = Segment::new(SegmentId(0x100), b"foo".to_vec());
+    /// let b = Segment::new(SegmentId(0x200), b"bar".to_vec());
+    ///
+    /// let mut x = Pup::new(vec![a.clone(), b.clone()], 0);
+    /// let mut y = Pup::new(vec![b, a], 0);
+    /// y.segments[0].set_signature("0123456789abcdef0123456789abcdef01234567".parse().unwrap());
+    ///
+    /// x.canonicalize(true);
+    /// y.canonicalize(true);
+    ///
+    /// assert_eq!(Vec::<u8>::from(&x), Vec::<u8>::from(&y));
+    /// ```
+    pub fn canonicalize(&mut self, resign: bool) {
+        self.sort_canonical();
+
+        if resign {
+            for seg in self.segments.iter_mut() {
+                seg.sign();
+            }
         }
     }
 
+    /// Compares this [`Pup`] with another, ignoring segment signatures.
+    ///
+    /// Image and package versions and, for each segment in order, IDs, signature kinds, alignments,
+    /// and data must be equal. Offsets and padding aren't stored in a [`Pup`] to begin with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut signed = Pup::default();
+    /// signed.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+    /// let unsigned = signed.clone();
+    /// signed.segments[0].sign();
+    ///
+    /// assert_ne!(signed, unsigned);
+    /// assert!(signed.semantically_eq(&unsigned));
+    /// ```
+    #[must_use]
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.image_version == other.image_version
+            && self.package_version == other.package_version
+            && self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(other.segments.iter())
+                .all(|(a, b)| {
+                    (a.id == b.id)
+                        && (a.signature_kind() == b.signature_kind())
+                        && (a.alignment() == b.alignment())
+                        && (a.data() == b.data())
+                })
+    }
+
+    /// Retains only the segments for which the given predicate returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    /// use std::convert::TryFrom as _;
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), Vec::new()));
+    /// pup.segments.push(Segment::new(SegmentId(0x9999), Vec::new()));
+    ///
+    /// // Strip everything without a well-known name.
+    /// pup.retain_segments(|x| <&str>::try_from(x.id).is_ok());
+    ///
+    /// assert_eq!(1, pup.segments.len());
+    /// assert_eq!(SegmentId(0x100), pup.segments[0].id);
+    /// ```
+    pub fn retain_segments<F: FnMut(&Segment) -> bool>(&mut self, pred: F) {
+        self.segments.retain(pred);
+    }
+
+    /// Replaces each segment with the result of the given function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+    ///
+    /// pup.map_segments(|mut x| {
+    ///     let data = x.take_data().to_ascii_uppercase();
+    ///     x.set_data(data);
+    ///     x
+    /// });
+    ///
+    /// assert_eq!(b"FOO", pup.segments[0].data());
+    /// ```
+    pub fn map_segments<F: FnMut(Segment) -> Segment>(&mut self, f: F) {
+        self.segments = std::mem::take(&mut self.segments)
+            .into_iter()
+            .map(f)
+            .collect();
+    }
+
     // The following methods exist on Pup because, without them, Metadata::from() would need to be
     // called every time header or data size must be known.
 
     fn header_size(&self) -> usize {
         // With just the segment count, we can calculate exactly what the full header size should
         // be.
+        header::Header::padded_size(self.segments.len() as u64) as usize
+    }
 
-        let mut header_size: usize;
+    fn data_size(&self) -> usize {
+        // Data are aligned by file offset, so the header size is taken into account.
+        let header_size = self.header_size();
 
         // [may_panic(Add)]
-        header_size = header::meta::Metadata::SIZE;
-        header_size += self.segments.len() * header::seg::Entry::SIZE;
-        header_size += self.segments.len() * header::sig::Entry::SIZE;
-        header_size += Digest::SIZE;
-        header_size += header_size % 0x10; // Round up to a multiple of 0x10.
+        self.segments.iter().fold(header_size, |end, seg| {
+            end + (seg.padding_at(end as u64) as usize) + seg.data.len()
+        }) - header_size
+    }
+}
+
+/// Any erroneous result returned by this crate.
+///
+/// Functions that only parse or only serialize return [`ParseError`] or [`BuildError`] instead;
+/// both convert into this type with `?`.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Deserialization failed.
+    Parse(ParseError),
+    /// Serialization failed.
+    Build(BuildError),
+    /// No segment has a specific ID.
+    SegmentNotFound(SegmentId),
+    /// A segment with a specific ID already exists.
+    DuplicateSegmentId(SegmentId),
+}
 
-        header_size
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
     }
+}
 
-    fn data_size(&self) -> usize {
-        // [may_panic(Iterator::sum)]
-        self.segments.iter().map(|x| x.data.len()).sum::<usize>()
+impl From<BuildError> for Error {
+    fn from(err: BuildError) -> Self {
+        Self::Build(err)
     }
 }
 
-/// An erroneous result returned by [`Pup::try_from`].
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{}", err),
+            Self::Build(err) => write!(f, "{}", err),
+            Self::SegmentNotFound(id) => write!(f, "no segment has ID {}", id),
+            Self::DuplicateSegmentId(id) => write!(f, "a segment already has ID {}", id),
+        }
+    }
+}
+
+/// An erroneous result returned by [`Pup::try_from`] or [`Pup::parse`].
 #[derive(Debug, Eq, PartialEq)]
-pub enum Error {
+pub enum ParseError {
     /// The input data is too short.
     Undersized,
     /// The file magic is invalid.
@@ -219,9 +559,66 @@ pub enum Error {
     MissingSignature(u64),
     /// A segment at a specific index has no corresponding data.
     MissingData(u64),
+    /// The header is inconsistent (only returned by [`ValidationLevel::Strict`] and above).
+    Inconsistent(Finding),
+    /// A segment at a specific index has an invalid signature.
+    InvalidSignature(u64),
+    /// The header signature is invalid.
+    InvalidHeaderSignature,
+    /// The segment count disagrees with the number of decodable table entries (e.g., because an
+    /// entry has an invalid signature kind).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{ParseError, Pup, Segment, SegmentId};
+    /// use std::convert::TryFrom as _;
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), Vec::new()));
+    ///
+    /// // Corrupt the signature kind of the first segment entry.
+    /// let mut data = Vec::<u8>::from(&pup);
+    /// data[0x4B] = 0xFF;
+    ///
+    /// assert_eq!(
+    ///     Err(ParseError::SegmentCountMismatch {
+    ///         declared: 1,
+    ///         seg_entries: 0,
+    ///         sig_entries: 1,
+    ///     }),
+    ///     Pup::try_from(data.as_slice()),
+    /// );
+    /// ```
+    SegmentCountMismatch {
+        /// The segment count declared in the header.
+        declared: u64,
+        /// The number of decodable segment table entries.
+        seg_entries: u64,
+        /// The number of decodable digest table entries.
+        sig_entries: u64,
+    },
+    /// The declared header size is too small to contain the header's own tables (only returned
+    /// by [`ValidationLevel::Strict`] and above).
+    HeaderSizeTooSmall {
+        /// The header size declared in the header.
+        declared: u64,
+        /// The smallest header size that fits the metadata, tables, and header signature.
+        minimum: u64,
+    },
+    /// The declared data size disagrees with the sum of all segment sizes, plus any padding
+    /// between segments (only returned by [`ValidationLevel::Strict`] and above).
+    DataSizeMismatch {
+        /// The data size declared in the header.
+        declared: u64,
+        /// The sum of all segment sizes, plus any padding between segments.
+        actual: u64,
+    },
+    /// The operation was cancelled through a [`CancellationToken`].
+    Cancelled,
 }
 
-impl Display for Error {
+impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::Undersized => write!(f, "input data is too short"),
@@ -237,6 +634,65 @@ impl Display for Error {
             }
             Self::MissingSignature(i) => write!(f, "signature for segment {} is missing", i),
             Self::MissingData(i) => write!(f, "data for segment {} is missing", i),
+            Self::Inconsistent(finding) => write!(f, "header is inconsistent: {}", finding),
+            Self::InvalidSignature(i) => write!(f, "signature for segment {} is invalid", i),
+            Self::InvalidHeaderSignature => write!(f, "header signature is invalid"),
+            Self::SegmentCountMismatch {
+                declared,
+                seg_entries,
+                sig_entries,
+            } => write!(
+                f,
+                "segment count is {}, but there are {} segment entries and {} signature entries",
+                declared, seg_entries, sig_entries
+            ),
+            Self::HeaderSizeTooSmall { declared, minimum } => write!(
+                f,
+                "header size is {}, but the header requires at least {}",
+                declared, minimum
+            ),
+            Self::DataSizeMismatch { declared, actual } => write!(
+                f,
+                "data size is {}, but segments total {}",
+                declared, actual
+            ),
+            Self::Cancelled => write!(f, "operation was cancelled"),
+        }
+    }
+}
+
+/// An erroneous result returned by [`Pup::serialize`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum BuildError {
+    /// More than one segment has a specific ID.
+    DuplicateSegmentId(SegmentId),
+    /// The package would be inconsistent.
+    Inconsistent(Finding),
+    /// The package would be larger than this platform can address.
+    TooLarge,
+    /// The computed location of the segment at a specific index doesn't fit its data.
+    ///
+    /// This indicates a bug in this crate.
+    LayoutMismatch(u64),
+    /// The operation was cancelled through a [`CancellationToken`].
+    Cancelled,
+    /// The package wouldn't serialize the same way again (see [`SerializeOptions::deterministic`]).
+    ///
+    /// This indicates a bug in this crate.
+    NotDeterministic,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateSegmentId(id) => write!(f, "segment ID {} is duplicated", id),
+            Self::Inconsistent(finding) => write!(f, "package is inconsistent: {}", finding),
+            Self::TooLarge => write!(f, "package is too large"),
+            Self::LayoutMismatch(i) => {
+                write!(f, "location of segment {} doesn't fit its data", i)
+            }
+            Self::Cancelled => write!(f, "operation was cancelled"),
+            Self::NotDeterministic => write!(f, "serialization isn't deterministic"),
         }
     }
 }
@@ -246,35 +702,282 @@ impl Display for Error {
 pub struct Segment {
     /// The ID of this segment.
     pub id: SegmentId,
-    /// The actual data this segment represents.
-    pub data: Vec<u8>,
 
+    // Data is private so that it can't change without the signature knowing about it.
+    data: Vec<u8>,
+    sig_kind: SignatureKind,
     sig: Digest,
+    provenance: Option<Provenance>,
+    alignment: Option<NonZeroU64>,
 }
 
 impl Segment {
-    /// Creates a new [`Segment`].
+    /// Creates a new [`Segment`], signed as [`SignatureKind::default_for`] its ID.
     #[must_use]
     pub fn new(id: SegmentId, data: Vec<u8>) -> Self {
         Self {
             id,
             data,
+            sig_kind: SignatureKind::default_for(id),
             sig: Digest::default(),
+            provenance: None,
+            alignment: None,
         }
     }
 
+    /// The well-known file name of this segment's ID, if it has one.
+    ///
+    /// See [`Self::file_name_in`] to look the ID up in a [`SegmentNameMap`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Segment, SegmentId};
+    ///
+    /// assert_eq!(Some("version.txt"), Segment::new(SegmentId(0x100), Vec::new()).file_name());
+    /// assert_eq!(None, Segment::new(SegmentId(0x9999), Vec::new()).file_name());
+    /// ```
+    #[must_use]
+    pub fn file_name(&self) -> Option<&'static str> {
+        <&'static str>::try_from(self.id).ok()
+    }
+
+    /// The file name of this segment's ID in a [`SegmentNameMap`], if it has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Segment, SegmentId, SegmentNameMap};
+    ///
+    /// let mut names = SegmentNameMap::default();
+    /// names.insert(SegmentId(0x9999), "foo.bin");
+    ///
+    /// let seg = Segment::new(SegmentId(0x9999), Vec::new());
+    /// assert_eq!(Some("foo.bin"), seg.file_name_in(&names));
+    /// ```
+    #[must_use]
+    pub fn file_name_in<'a>(&self, names: &'a SegmentNameMap) -> Option<&'a str> {
+        names.name_of(self.id)
+    }
+
+    /// The actual data this segment represents.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Replaces the data of this segment.
+    ///
+    /// Because the old signature no longer applies, it is cleared; call [`Self::sign`] to
+    /// generate a new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Digest, Segment, SegmentId};
+    ///
+    /// let mut seg = Segment::new(SegmentId(0x100), b"foo".to_vec());
+    /// seg.sign();
+    ///
+    /// seg.set_data(b"bar".to_vec());
+    ///
+    /// assert_eq!(b"bar", seg.data());
+    /// assert_eq!(Digest::default(), *seg.signature());
+    /// ```
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data;
+        self.sig = Digest::default();
+    }
+
+    /// Takes the data out of this segment, leaving it empty.
+    ///
+    /// Like [`Self::set_data`], this clears the signature.
+    pub fn take_data(&mut self) -> Vec<u8> {
+        self.sig = Digest::default();
+        std::mem::take(&mut self.data)
+    }
+
+    /// The algorithm used to sign this segment.
+    pub fn signature_kind(&self) -> SignatureKind {
+        self.sig_kind
+    }
+
+    /// Replaces the result of [`Self::signature_kind`].
+    ///
+    /// Like [`Self::set_data`], this clears the signature.
+    pub fn set_signature_kind(&mut self, kind: SignatureKind) {
+        self.sig_kind = kind;
+        self.sig = Digest::default();
+    }
+
     /// The signed hash digest of this segment's data.
     pub fn signature(&self) -> &Digest {
         &self.sig
     }
 
+    /// Overwrites the result of [`Self::signature`] without recomputing it.
+    ///
+    /// This is useful when reconstructing a segment whose signature is already known.
+    pub fn set_signature(&mut self, sig: Digest) {
+        self.sig = sig;
+    }
+
     /// Updates the result of [`Self::signature`].
     pub fn sign(&mut self) {
-        // Unwrapping is safe because an error is only returned when the key is an invalid length
-        // (HMAC_KEY is a fixed 0x40 bytes).
-        HMAC_KEY.with(|_| {
-            todo!();
-        });
+        self.sig = Digest::signature_of(self.sig_kind, &self.data);
+    }
+
+    /// Where this segment was found, if it was parsed with
+    /// [`ParseOptions::record_provenance`].
+    ///
+    /// This describes the segment as it was parsed; later changes don't affect it.
+    #[must_use]
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    /// The number the file offset of this segment's data must be a multiple of.
+    ///
+    /// This is 1 (i.e., any offset) unless set with [`Self::set_alignment`]. Serialization pads
+    /// the preceding data with zeros as needed, and the padding counts towards the declared data
+    /// size. Alignment isn't stored in the PUP, so parsed segments never have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x300), vec![0xff; 4]));
+    /// pup.segments[1].set_alignment(0x40);
+    ///
+    /// let data = Vec::<u8>::from(&pup);
+    ///
+    /// assert_eq!([0xff; 4], data[0x100..0x104]);
+    /// assert!(pup.validate().is_empty());
+    /// assert!(pupper::validate(&data).unwrap().is_empty());
+    /// ```
+    #[must_use]
+    pub fn alignment(&self) -> u64 {
+        self.alignment.map_or(1, NonZeroU64::get)
+    }
+
+    /// Sets the result of [`Self::alignment`]; 0 and 1 both mean any offset.
+    pub fn set_alignment(&mut self, alignment: u64) {
+        self.alignment = NonZeroU64::new(alignment).filter(|x| x.get() != 1);
+    }
+
+    // The number of padding bytes needed before this segment's data if they would otherwise start
+    // at the given offset.
+    pub(crate) fn padding_at(&self, offset: u64) -> u64 {
+        let alignment = self.alignment();
+
+        (alignment - offset % alignment) % alignment
+    }
+}
+
+/// Where a parsed [`Segment`] was found, as returned by [`Segment::provenance`].
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{ParseOptions, Pup, Segment, SegmentId};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+/// let mut data = Vec::<u8>::from(&pup);
+/// data.push(0);
+///
+/// let parsed = Pup::parse(
+///     &data,
+///     &mut ParseOptions {
+///         record_provenance: true,
+///         ..Default::default()
+///     },
+/// )
+/// .unwrap();
+/// let provenance = parsed.segments[0].provenance().unwrap();
+///
+/// assert_eq!(data.len() as u64 - 4, provenance.offset);
+/// assert_eq!(3, provenance.size);
+/// assert_eq!(1, provenance.trailing_padding);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Provenance {
+    /// The offset of the segment data within the file.
+    pub offset: u64,
+    /// The size of the segment data, in bytes.
+    pub size: u64,
+    /// The digest stored for the segment.
+    pub digest: Digest,
+    /// The number of bytes between the end of the segment data and the start of the next
+    /// segment's (or the end of the file).
+    pub trailing_padding: u64,
+}
+
+/// The algorithm used to sign a [`Segment`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SignatureKind {
+    /// HMAC-SHA1, keyed with [`HMAC_KEY`].
+    #[default]
+    HmacSha1,
+    /// HMAC-SHA256, keyed with [`HMAC_KEY`] and truncated to the size of a [`Digest`].
+    HmacSha256,
+}
+
+impl SignatureKind {
+    /// The signature kind official packages use for a segment ID: HMAC-SHA256 for the spkg
+    /// segments (0x501 and 0x601), and HMAC-SHA1 for all others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Segment, SegmentId, SignatureKind};
+    ///
+    /// assert_eq!(SignatureKind::HmacSha1, SignatureKind::default_for(SegmentId(0x300)));
+    /// assert_eq!(SignatureKind::HmacSha256, SignatureKind::default_for(SegmentId(0x501)));
+    ///
+    /// // New segments are signed accordingly.
+    /// let seg = Segment::new(SegmentId(0x601), Vec::new());
+    /// assert_eq!(SignatureKind::HmacSha256, seg.signature_kind());
+    /// ```
+    #[must_use]
+    pub fn default_for(id: SegmentId) -> Self {
+        match id.0 {
+            0x501 | 0x601 => Self::HmacSha256,
+            _ => Self::HmacSha1,
+        }
+    }
+}
+
+impl Display for SignatureKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::HmacSha1 => write!(f, "HMAC-SHA1"),
+            Self::HmacSha256 => write!(f, "HMAC-SHA256"),
+        }
+    }
+}
+
+impl TryFrom<u32> for SignatureKind {
+    type Error = ParseError;
+
+    fn try_from(kind: u32) -> Result<Self, Self::Error> {
+        match kind {
+            0 => Ok(Self::HmacSha1),
+            2 => Ok(Self::HmacSha256),
+            _ => Err(Self::Error::InvalidSignatureKind(kind)),
+        }
+    }
+}
+
+impl From<SignatureKind> for u32 {
+    fn from(kind: SignatureKind) -> Self {
+        match kind {
+            SignatureKind::HmacSha1 => 0,
+            SignatureKind::HmacSha256 => 2,
+        }
     }
 }
 
@@ -283,16 +986,122 @@ thread_local! {
     ///
     /// It is of questionable legality to provide this key. Therefore, for accurate
     /// signature-related information, clients should overwrite this constant with the real key.
-    pub static HMAC_KEY: [u8; 0x40] = [0; 0x40];
+    pub static HMAC_KEY: [u8; 0x40] = const { [0; 0x40] };
 }
 
 /// The ID of a [`Segment`]. Can *usually* be [translated to a file name].
 ///
+/// The [`TryFrom`] implementations only know the well-known file names; see [`SegmentNameMap`] for
+/// a map that can be extended.
+///
 /// [translated to a file name]:
 ///     https://www.psdevwiki.com/ps3/Playstation_Update_Package_(PUP)#Segment_Entry_IDs
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct SegmentId(pub u64);
 
+impl SegmentId {
+    /// The sort key of this ID in the conventional segment order.
+    pub(crate) fn canonical_rank(self) -> (usize, u64) {
+        let position = SEGMENT_ID_MAP
+            .iter()
+            .position(|(id, _)| *id == self.0)
+            .unwrap_or(SEGMENT_ID_MAP.len());
+
+        (position, self.0)
+    }
+
+    /// Like [`Self::canonical_rank`], but other IDs named in the map follow the well-known ones,
+    /// in the order of the map.
+    pub(crate) fn canonical_rank_in(self, names: &SegmentNameMap) -> (usize, u64) {
+        match self.canonical_rank() {
+            (position, _) if position < SEGMENT_ID_MAP.len() => (position, self.0),
+            (position, _) => {
+                let offset = names
+                    .iter()
+                    .position(|(id, _)| id == self)
+                    .unwrap_or_else(|| names.iter().count());
+
+                (position + offset, self.0)
+            }
+        }
+    }
+
+    /// Formats the ID like its [`Display`] implementation, but with its file name from a
+    /// [`SegmentNameMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{SegmentId, SegmentNameMap};
+    ///
+    /// let mut names = SegmentNameMap::default();
+    /// names.insert(SegmentId(0x9999), "foo.bin");
+    ///
+    /// assert_eq!("0x9999 (foo.bin)", SegmentId(0x9999).display(&names).to_string());
+    /// assert_eq!("0x300 (update_files.tar)", SegmentId(0x300).display(&names).to_string());
+    /// ```
+    #[must_use]
+    pub fn display(self, names: &SegmentNameMap) -> impl Display + '_ {
+        NamedSegmentId { id: self, names }
+    }
+}
+
+struct NamedSegmentId<'a> {
+    id: SegmentId,
+    names: &'a SegmentNameMap,
+}
+
+impl Display for NamedSegmentId<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.id.0)?;
+
+        if let Some(file_name) = self.names.name_of(self.id) {
+            write!(f, " ({})", file_name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats the ID in hexadecimal, followed by its well-known file name, if it has one.
+///
+/// See [`SegmentId::display`] to use the names of a [`SegmentNameMap`] instead.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::SegmentId;
+///
+/// assert_eq!("0x300 (update_files.tar)", SegmentId(0x300).to_string());
+/// assert_eq!("0x9999", SegmentId(0x9999).to_string());
+///
+/// // Hexadecimal formatting applies to the ID alone.
+/// assert_eq!("0x0300", format!("{:#06x}", SegmentId(0x300)));
+/// ```
+impl Display for SegmentId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)?;
+
+        if let Ok(file_name) = <&str>::try_from(*self) {
+            write!(f, " ({})", file_name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::LowerHex for SegmentId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for SegmentId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
 impl TryFrom<SegmentId> for &'static str {
     type Error = String;
 
@@ -301,7 +1110,7 @@ impl TryFrom<SegmentId> for &'static str {
             .iter()
             .find(|(value, _)| *value == id.0)
             .map(|(_, file_name)| *file_name)
-            .ok_or_else(|| format!("segment ID '{}' has no corresponding file name", id.0))
+            .ok_or_else(|| format!("segment ID {:#x} has no corresponding file name", id.0))
     }
 }
 
@@ -320,6 +1129,8 @@ impl TryFrom<&str> for SegmentId {
 // This u64 <=> &str map exists because strings (e.g., these file names) would be prone to
 // accidental modification if repeated verbatim in the above two TryFrom implementations.
 //
+// The order of this map is also the order in which official firmware lays out its segments.
+//
 // This isn't a HashMap because...
 //   a. I don't think static HashMaps are possible?
 //   b. There's only 12 KV pairs.
@@ -358,9 +1169,96 @@ static SEGMENT_ID_MAP: [(u64, &str); 12] = [
 /// let expected = "00112233445566778899aabbccddeeff00112233";
 /// assert_eq!(expected, format!("{}", digest));
 /// ```
+///
+/// [`Self::from_str`] parses the same format:
+///
+/// ```
+/// let digest: pupper::Digest = "00112233445566778899aabbccddeeff00112233".parse().unwrap();
+///
+/// assert_eq!(0x11, digest.0[1]);
+/// ```
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct Digest(pub [u8; Self::SIZE]);
 
+impl Digest {
+    /// Computes the plain (i.e., unkeyed) SHA-1 digest of the given data.
+    ///
+    /// This is *not* a segment signature, but it is handy as a content fingerprint for comparing
+    /// segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let digest = pupper::Digest::sha1_of(b"abc");
+    ///
+    /// let expected = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    /// assert_eq!(expected, format!("{}", digest));
+    /// ```
+    #[must_use]
+    pub fn sha1_of(data: &[u8]) -> Self {
+        use sha1::Digest as _;
+
+        Self(sha1::Sha1::digest(data).into())
+    }
+
+    /// Computes the HMAC-SHA1 digest of the given data, keyed with [`HMAC_KEY`].
+    pub(crate) fn hmac_sha1_of(data: &[u8]) -> Self {
+        HMAC_KEY.with(|key| Self::hmac_sha1_with_key(key, data))
+    }
+
+    /// Computes the signature of the given kind of the given data, keyed with [`HMAC_KEY`].
+    pub(crate) fn signature_of(kind: SignatureKind, data: &[u8]) -> Self {
+        HMAC_KEY.with(|key| Self::signature_with_key(kind, key, data))
+    }
+
+    /// Computes the signature of the given kind of the given data, keyed with the given key
+    /// rather than [`HMAC_KEY`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Digest, Segment, SegmentId};
+    ///
+    /// let mut seg = Segment::new(SegmentId(0x501), b"spkg".to_vec());
+    /// seg.sign();
+    ///
+    /// let sig = Digest::signature_with_key(seg.signature_kind(), &[0; 0x40], seg.data());
+    /// assert_eq!(*seg.signature(), sig);
+    /// ```
+    #[must_use]
+    pub fn signature_with_key(kind: SignatureKind, key: &[u8], data: &[u8]) -> Self {
+        use hmac::{Mac as _, NewMac as _};
+
+        match kind {
+            SignatureKind::HmacSha1 => Self::hmac_sha1_with_key(key, data),
+            SignatureKind::HmacSha256 => {
+                // Unwrapping is safe because HMAC accepts keys of any length.
+                let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(key).unwrap();
+                mac.update(data);
+
+                // The digest table only has room for the first 20 bytes.
+                let mut digest = Self::default();
+                digest
+                    .0
+                    .copy_from_slice(&mac.finalize().into_bytes()[..Self::SIZE]);
+
+                digest
+            }
+        }
+    }
+
+    /// Computes the HMAC-SHA1 digest of the given data, keyed with the given key.
+    pub(crate) fn hmac_sha1_with_key(key: &[u8], data: &[u8]) -> Self {
+        use hmac::{Mac as _, NewMac as _};
+
+        // Unwrapping is safe because HMAC accepts keys of any length.
+        let mut mac = hmac::Hmac::<sha1::Sha1>::new_from_slice(key).unwrap();
+        mac.update(data);
+
+        Self(mac.finalize().into_bytes().into())
+    }
+}
+
 impl Display for Digest {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let digest: String = self.0.iter().map(|x| format!("{:02x}", *x)).collect();
@@ -369,13 +1267,35 @@ impl Display for Digest {
     }
 }
 
+impl FromStr for Digest {
+    type Err = String;
+
+    fn from_str(digest: &str) -> Result<Self, Self::Err> {
+        let err = || format!("digest '{}' is not {} hex digits", digest, Self::SIZE * 2);
+
+        if digest.len() != (Self::SIZE * 2) {
+            return Err(err());
+        }
+
+        let mut result = Self::default();
+        for (i, byte) in result.0.iter_mut().enumerate() {
+            *byte = digest
+                .get((i * 2)..((i * 2) + 2))
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .ok_or_else(err)?;
+        }
+
+        Ok(result)
+    }
+}
+
 impl FixedSize for Digest {
     const SIZE: usize = 0x14;
 }
 
 /// The file magic of a PUP. Always `SCEUF\0\0\0`.
 ///
-/// This type exists solely for being the 'return value' of [`Error::InvalidMagic`].
+/// This type exists solely for being the 'return value' of [`ParseError::InvalidMagic`].
 ///
 /// # Examples
 ///
@@ -0,0 +1,119 @@
+use crate::{SegmentId, SEGMENT_ID_MAP};
+
+/// A bidirectional map between [`SegmentId`]s and file names.
+///
+/// [`Self::default`] contains the well-known names also used by [`SegmentId`]'s [`TryFrom`]
+/// implementations. Unlike those, this map can be extended or replaced at runtime (e.g., to name
+/// the lesser-known segments found in some packages).
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{SegmentId, SegmentNameMap};
+///
+/// let mut names = SegmentNameMap::default();
+/// assert_eq!(Some("version.txt"), names.name_of(SegmentId(0x100)));
+///
+/// names.extend_from_toml("0x1ff = 'foo.bin'").unwrap();
+/// assert_eq!(Some(SegmentId(0x1FF)), names.id_of("foo.bin"));
+/// ```
+///
+/// [`TryFrom`]: std::convert::TryFrom
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SegmentNameMap(Vec<(SegmentId, String)>);
+
+impl Default for SegmentNameMap {
+    fn default() -> Self {
+        let entries = SEGMENT_ID_MAP
+            .iter()
+            .map(|(id, file_name)| (SegmentId(*id), (*file_name).to_string()))
+            .collect();
+
+        Self(entries)
+    }
+}
+
+impl SegmentNameMap {
+    /// Creates an empty [`SegmentNameMap`].
+    ///
+    /// Use [`Self::default`] to start with the well-known names instead.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// The file name of a segment ID, if it has one.
+    #[must_use]
+    pub fn name_of(&self, id: SegmentId) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(value, _)| *value == id)
+            .map(|(_, file_name)| file_name.as_str())
+    }
+
+    /// The segment ID of a file name, if it has one.
+    #[must_use]
+    pub fn id_of(&self, file_name: &str) -> Option<SegmentId> {
+        self.0
+            .iter()
+            .find(|(_, value)| value == file_name)
+            .map(|(id, _)| *id)
+    }
+
+    /// Maps a segment ID to a file name, replacing any existing mapping of either.
+    pub fn insert(&mut self, id: SegmentId, file_name: impl Into<String>) {
+        let file_name = file_name.into();
+
+        self.0.retain(|(x, y)| (*x != id) && (*y != file_name));
+        self.0.push((id, file_name));
+    }
+
+    /// Unmaps a segment ID, returning its file name if it had one.
+    pub fn remove(&mut self, id: SegmentId) -> Option<String> {
+        let i = self.0.iter().position(|(x, _)| *x == id)?;
+
+        Some(self.0.remove(i).1)
+    }
+
+    /// Iterates over all mappings in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (SegmentId, &str)> {
+        self.0
+            .iter()
+            .map(|(id, file_name)| (*id, file_name.as_str()))
+    }
+
+    /// Adds mappings from a TOML document, replacing existing mappings as [`Self::insert`] does.
+    ///
+    /// The document is a flat table of segment IDs to file names. IDs may be written in decimal
+    /// or, with a `0x` prefix, in hexadecimal:
+    ///
+    /// ```toml
+    /// 0x100 = "version.txt"
+    /// 0x1ff = "foo.bin"
+    /// ```
+    pub fn extend_from_toml(&mut self, toml: &str) -> Result<(), String> {
+        let table: toml::value::Table =
+            toml::from_str(toml).map_err(|err| format!("failed to parse TOML: {}", err))?;
+
+        for (key, value) in table.into_iter() {
+            let id =
+                parse_id(&key).ok_or_else(|| format!("key '{}' is not a valid segment ID", key))?;
+            let file_name = value
+                .as_str()
+                .ok_or_else(|| format!("value for segment ID '{}' is not a string", key))?;
+
+            self.insert(id, file_name);
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_id(id: &str) -> Option<SegmentId> {
+    let id = match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => id.parse(),
+    };
+
+    id.ok().map(SegmentId)
+}
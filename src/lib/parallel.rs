@@ -0,0 +1,119 @@
+use crate::progress::{Cancelled, Progress};
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// How many threads an operation may use at once.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::Parallelism;
+/// use std::num::NonZeroUsize;
+///
+/// let parallelism = Parallelism::Threads(NonZeroUsize::new(2).unwrap());
+///
+/// assert_eq!(2, parallelism.thread_count());
+/// assert!(Parallelism::Auto.thread_count() >= 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Parallelism {
+    /// One thread per available CPU.
+    #[default]
+    Auto,
+    /// At most this many threads.
+    Threads(NonZeroUsize),
+}
+
+impl Parallelism {
+    /// The number of threads this allows.
+    #[must_use]
+    pub fn thread_count(self) -> usize {
+        match self {
+            Self::Auto => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            Self::Threads(x) => x.get(),
+        }
+    }
+
+    /// Applies a function to each item, spreading the items over up to [`Self::thread_count`]
+    /// threads, and returns the results in order.
+    ///
+    /// Progress is reported on the calling thread as items finish, each counting as `weight` of
+    /// it. Once cancelled, no more items are started.
+    pub(crate) fn map_with_progress<T, U, F, W>(
+        self,
+        items: &[T],
+        weight: W,
+        f: F,
+        progress: &mut Progress,
+    ) -> Result<Vec<U>, Cancelled>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+        W: Fn(&T) -> u64,
+    {
+        let total = items.iter().map(&weight).fold(0, u64::saturating_add);
+        let mut done = 0_u64;
+        progress.report(done, total)?;
+
+        let thread_count = self.thread_count().min(items.len());
+        if thread_count <= 1 {
+            return items
+                .iter()
+                .map(|item| {
+                    let result = f(item);
+                    done = done.saturating_add(weight(item));
+                    progress.report(done, total).map(|_| result)
+                })
+                .collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+        let mut results = Vec::with_capacity(items.len());
+        thread::scope(|scope| {
+            for _ in 0..thread_count {
+                let (next, stop, f, sender) = (&next, &stop, &f, sender.clone());
+                scope.spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let item = match items.get(i) {
+                            Some(x) => x,
+                            None => break,
+                        };
+
+                        // The receiver only hangs up once cancelled.
+                        if sender.send((i, f(item))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (i, result) in receiver {
+                done = done.saturating_add(weight(&items[i]));
+                results.push((i, result));
+
+                if let Err(err) = progress.report(done, total) {
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(err);
+                }
+            }
+
+            Ok(())
+        })?;
+
+        results.sort_by_key(|(i, _)| *i);
+
+        Ok(results.into_iter().map(|(_, x)| x).collect())
+    }
+}
@@ -0,0 +1,634 @@
+use crate::{
+    header::Header, validate::check_header, Digest, Finding, FixedSize, HeaderMetadata, ParseError,
+    Progress, Provenance, Pup, Segment, SegmentId, SegmentNameMap,
+};
+
+use std::{
+    convert::TryFrom as _,
+    fmt::{self, Display, Formatter},
+};
+
+impl Pup {
+    /// Deserializes a [`Pup`] according to the given options.
+    ///
+    /// With the default options, this is equivalent to [`Pup::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// Non-fatal oddities can be collected through [`ParseOptions::on_warning`]:
+    ///
+    /// ```
+    /// use pupper::{Diagnostic, ParseOptions, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x9999), Vec::new()));
+    /// let data = Vec::<u8>::from(&pup);
+    ///
+    /// let mut warnings = Vec::new();
+    /// let parsed = Pup::parse(
+    ///     &data,
+    ///     &mut ParseOptions {
+    ///         on_warning: Some(Box::new(|x| warnings.push(x))),
+    ///         ..Default::default()
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(Ok(pup), parsed);
+    /// assert_eq!(
+    ///     vec![Diagnostic::UnknownSegmentId {
+    ///         index: 0,
+    ///         id: SegmentId(0x9999),
+    ///     }],
+    ///     warnings,
+    /// );
+    /// ```
+    ///
+    /// Offsets and sizes are bounds-checked, however large they are:
+    ///
+    /// ```
+    /// use pupper::{ParseError, ParseOptions, Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    /// let mut data = Vec::<u8>::from(&pup);
+    /// // A segment size that overflows when added to the offset.
+    /// data[0x40..0x48].copy_from_slice(&u64::MAX.to_be_bytes());
+    ///
+    /// let parsed = Pup::parse(&data, &mut ParseOptions::default());
+    ///
+    /// assert_eq!(Err(ParseError::MissingData(0)), parsed);
+    /// ```
+    ///
+    /// [`Pup::try_from`]: std::convert::TryFrom::try_from
+    pub fn parse(data: &[u8], options: &mut ParseOptions) -> Result<Self, ParseError> {
+        // Without validation, we take our chances with unknown package versions.
+        let header = Header::parse(data, options.validation != ValidationLevel::None)?;
+        if header.meta.check_pkg_version().is_err() {
+            options.warn(Diagnostic::UnsupportedPackageVersion(
+                header.meta.pkg_version,
+            ));
+        }
+
+        // Table entries that fail to decode go missing, which would otherwise silently drop
+        // segments.
+        let seg_count = header.meta.seg_count;
+        let seg_entries = header.seg_table.len() as u64;
+        let sig_entries = header.digest_table.len() as u64;
+        if (seg_entries != seg_count) || (sig_entries != seg_count) {
+            if options.validation == ValidationLevel::None {
+                options.warn(Diagnostic::SegmentCountMismatch {
+                    declared: seg_count,
+                    seg_entries,
+                    sig_entries,
+                });
+            } else {
+                return Err(ParseError::SegmentCountMismatch {
+                    declared: seg_count,
+                    seg_entries,
+                    sig_entries,
+                });
+            }
+        }
+
+        // First (and most importantly), we generate segments, drawing from three separate
+        // locations: the segment table, the digest table, and the actual data.
+        let record_provenance = options.record_provenance;
+        let file_size = data.len() as u64;
+        let segments = header.seg_table.iter().enumerate().map(|(i, entry)| {
+            let i = i as u64;
+
+            let sig = header
+                .digest_table
+                .iter()
+                .find(|x| x.seg_index == i)
+                .ok_or(ParseError::MissingSignature(i))
+                .map(|x| x.digest)?;
+
+            // The offset and size come from the file, so their sum may overflow.
+            let end = entry
+                .offset
+                .checked_add(entry.size)
+                .ok_or(ParseError::MissingData(i))?;
+            let data = usize::try_from(entry.offset)
+                .ok()
+                .zip(usize::try_from(end).ok())
+                .and_then(|(start, end)| data.get(start..end))
+                .ok_or(ParseError::MissingData(i))
+                .map(|x| x.to_vec())?;
+
+            let provenance = if record_provenance {
+                let next_start = header
+                    .seg_table
+                    .get((i as usize) + 1)
+                    .map_or(file_size, |x| x.offset);
+
+                Some(Provenance {
+                    offset: entry.offset,
+                    size: entry.size,
+                    digest: sig,
+                    trailing_padding: next_start.saturating_sub(end),
+                })
+            } else {
+                None
+            };
+
+            let seg = Segment {
+                id: entry.id,
+                data,
+                sig_kind: entry.sig_kind,
+                sig,
+                provenance,
+                alignment: None,
+            };
+
+            Ok::<_, ParseError>(seg)
+        });
+
+        // Without validation, we salvage whatever segments we can.
+        let segments = if options.validation == ValidationLevel::None {
+            segments
+                .enumerate()
+                .filter_map(|(i, x)| {
+                    if x.is_err() {
+                        options.warn(Diagnostic::SkippedSegment(i as u64));
+                    }
+
+                    x.ok()
+                })
+                .collect()
+        } else {
+            segments.collect::<Result<_, _>>()?
+        };
+
+        if options.validation >= ValidationLevel::Strict {
+            // Everything else is located using the declared sizes, so they're checked first.
+            check_sizes(&header)?;
+
+            // Non-canonical order and missing companions are unusual, but they aren't
+            // inconsistent. Neither is trailing data, which is only ever a warning.
+            if let Some(finding) = check_header(&header, data.len() as u64)
+                .into_iter()
+                .find(|x| match x {
+                    Finding::NonCanonicalOrder(_) | Finding::MissingCompanion { .. } => false,
+                    Finding::FileSizeMismatch { declared, actual } => actual < declared,
+                    _ => true,
+                })
+            {
+                return Err(ParseError::Inconsistent(finding));
+            }
+        }
+
+        // Next, we copy over metadata that aren't inherently represented in the segments.
+        let pup = Self {
+            segments,
+            image_version: header.meta.img_version,
+            package_version: header.meta.pkg_version,
+            extra_digests: header
+                .digest_table
+                .iter()
+                .filter(|x| x.seg_index >= header.seg_table.len() as u64)
+                .map(Into::into)
+                .collect(),
+            header_sig: if record_provenance {
+                Some(*header.header_sig())
+            } else {
+                None
+            },
+        };
+
+        if options.validation >= ValidationLevel::Cryptographic {
+            verify_signatures(&pup, &header, data, &mut options.progress)?;
+        }
+
+        // Finally, we look for anything that parsed fine but would not survive re-serialization.
+        if options.on_warning.is_some() {
+            check_oddities(&pup, &header, data, options);
+        }
+
+        Ok(pup)
+    }
+}
+
+fn check_sizes(header: &Header) -> Result<(), ParseError> {
+    let min_header_size = Header::unpadded_size(header.meta.seg_count);
+    if header.meta.header_size < min_header_size {
+        return Err(ParseError::HeaderSizeTooSmall {
+            declared: header.meta.header_size,
+            minimum: min_header_size,
+        });
+    }
+
+    let actual_data_size = header.padded_data_size();
+    if header.meta.data_size != actual_data_size {
+        return Err(ParseError::DataSizeMismatch {
+            declared: header.meta.data_size,
+            actual: actual_data_size,
+        });
+    }
+
+    Ok(())
+}
+
+fn verify_signatures(
+    pup: &Pup,
+    header: &Header,
+    data: &[u8],
+    progress: &mut Progress,
+) -> Result<(), ParseError> {
+    let total = pup.data_size() as u64;
+    let mut done = 0;
+
+    progress.report(done, total)?;
+    for (i, seg) in pup.segments.iter().enumerate() {
+        let expected = Digest::signature_of(seg.signature_kind(), &seg.data);
+
+        if *seg.signature() != expected {
+            return Err(ParseError::InvalidSignature(i as u64));
+        }
+
+        done += seg.data.len() as u64;
+        progress.report(done, total)?;
+    }
+
+    // The header signature covers everything in the header that precedes it. At this point, the
+    // header is known to be at least that long.
+    let signed_size = (Header::unpadded_size(header.meta.seg_count) as usize) - Digest::SIZE;
+    if *header.header_sig() != Digest::hmac_sha1_of(&data[..signed_size]) {
+        return Err(ParseError::InvalidHeaderSignature);
+    }
+
+    Ok(())
+}
+
+fn check_oddities(pup: &Pup, header: &Header, data: &[u8], options: &mut ParseOptions) {
+    let expected_header_size = Header::padded_size(header.meta.seg_count);
+    if header.meta.header_size != expected_header_size {
+        options.warn(Diagnostic::NonstandardHeaderSize {
+            declared: header.meta.header_size,
+            expected: expected_header_size,
+        });
+    }
+
+    // The header padding follows the header signature.
+    let padding_start = Header::unpadded_size(header.meta.seg_count) as usize;
+    let padding_end = (header.meta.header_size as usize).min(data.len());
+    if let Some(offset) = data
+        .get(padding_start..padding_end)
+        .and_then(|x| x.iter().position(|x| *x != 0))
+    {
+        options.warn(Diagnostic::NonZeroPadding {
+            offset: (padding_start + offset) as u64,
+        });
+    }
+
+    for (i, seg) in pup.segments.iter().enumerate() {
+        let known = match options.names {
+            Some(names) => seg.file_name_in(names).is_some(),
+            None => seg.file_name().is_some(),
+        };
+        if !known {
+            options.warn(Diagnostic::UnknownSegmentId {
+                index: i as u64,
+                id: seg.id,
+            });
+        }
+    }
+
+    for entry in header.digest_table.iter() {
+        if entry.seg_index >= header.seg_table.len() as u64 {
+            options.warn(Diagnostic::OrphanSignature(entry.seg_index));
+        }
+    }
+
+    let end = header
+        .meta
+        .header_size
+        .saturating_add(header.meta.data_size);
+    if (data.len() as u64) > end {
+        options.warn(Diagnostic::TrailingData {
+            offset: end,
+            size: (data.len() as u64) - end,
+        });
+    }
+}
+
+/// An iterator over [`Pup`]s concatenated back-to-back, as some archives store them.
+///
+/// Each [`Pup`] is assumed to end where its header says it does (i.e., after the header size plus
+/// the data size), and is parsed with the default [`ParseOptions`]. Iteration stops at the end of
+/// the data or after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, PupStream, Segment, SegmentId};
+///
+/// let mut first = Pup::default();
+/// first.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+/// let mut second = Pup::default();
+/// second.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+///
+/// let mut data = Vec::<u8>::from(&first);
+/// data.extend_from_slice(&Vec::<u8>::from(&second));
+///
+/// let pups: Vec<_> = PupStream::new(&data).collect();
+///
+/// assert_eq!(vec![Ok(first), Ok(second)], pups);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PupStream<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PupStream<'a> {
+    /// Creates a [`PupStream`] over the given data.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// The offset of the next [`Pup`] within the data.
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.offset as u64
+    }
+}
+
+impl Iterator for PupStream<'_> {
+    type Item = Result<Pup, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.data.get(self.offset..).filter(|x| !x.is_empty())?;
+
+        let result = HeaderMetadata::try_from(rest).and_then(|meta| {
+            let len = meta
+                .header_size
+                .checked_add(meta.data_size)
+                .and_then(|x| usize::try_from(x).ok())
+                .filter(|x| *x <= rest.len())
+                .ok_or(ParseError::Undersized)?;
+            let pup = Pup::try_from(&rest[..len])?;
+
+            Ok((pup, len))
+        });
+
+        match result {
+            Ok((pup, len)) => {
+                self.offset += len;
+                Some(Ok(pup))
+            }
+            Err(err) => {
+                // Nothing past the error can be located.
+                self.offset = self.data.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Options for [`Pup::parse`].
+#[derive(Default)]
+pub struct ParseOptions<'a> {
+    /// How thoroughly the input is checked.
+    pub validation: ValidationLevel,
+    /// Called with each non-fatal oddity found while parsing.
+    ///
+    /// If this is [`None`], no time is spent looking for oddities.
+    pub on_warning: Option<Box<dyn FnMut(Diagnostic) + 'a>>,
+    /// Progress reporting and cancellation while segment signatures are verified (i.e., with
+    /// [`ValidationLevel::Cryptographic`]).
+    pub progress: Progress<'a>,
+    /// Whether to record where each segment was found (see [`Segment::provenance`]), along with
+    /// the header signature (see [`Pup::header_signature`]).
+    ///
+    /// Parsed PUPs and segments then no longer compare equal to otherwise identical ones created
+    /// with [`Pup::new`] and [`Segment::new`].
+    pub record_provenance: bool,
+    /// The names that make a segment ID known to [`Diagnostic::UnknownSegmentId`].
+    ///
+    /// If this is [`None`], only the well-known names are.
+    pub names: Option<&'a SegmentNameMap>,
+}
+
+impl ParseOptions<'_> {
+    fn warn(&mut self, diagnostic: Diagnostic) {
+        if let Some(on_warning) = self.on_warning.as_mut() {
+            on_warning(diagnostic);
+        }
+    }
+}
+
+/// How thoroughly [`Pup::parse`] checks its input.
+///
+/// Each level performs all checks of the levels before it.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{ParseError, ParseOptions, Pup, Segment, SegmentId, ValidationLevel};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+///
+/// let mut options = ParseOptions {
+///     validation: ValidationLevel::Cryptographic,
+///     ..Default::default()
+/// };
+///
+/// // The segment hasn't been signed yet.
+/// let data = Vec::<u8>::from(&pup);
+/// assert_eq!(Err(ParseError::InvalidSignature(0)), Pup::parse(&data, &mut options));
+///
+/// pup.segments[0].sign();
+/// let data = Vec::<u8>::from(&pup);
+/// assert_eq!(Ok(pup), Pup::parse(&data, &mut options));
+/// ```
+///
+/// Size declarations that disagree with the tables only matter from [`Self::Strict`] on:
+///
+/// ```
+/// use pupper::{ParseError, ParseOptions, Pup, Segment, SegmentId, ValidationLevel};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+///
+/// // Declare a data size of 4 instead of 3.
+/// let mut data = Vec::<u8>::from(&pup);
+/// data[0x2F] = 4;
+///
+/// assert!(Pup::parse(&data, &mut ParseOptions::default()).is_ok());
+/// assert_eq!(
+///     Err(ParseError::DataSizeMismatch {
+///         declared: 4,
+///         actual: 3,
+///     }),
+///     Pup::parse(
+///         &data,
+///         &mut ParseOptions {
+///             validation: ValidationLevel::Strict,
+///             ..Default::default()
+///         },
+///     ),
+/// );
+/// ```
+///
+/// At every level, data past the declared end of the PUP are allowed, and only reported as
+/// [`Diagnostic::TrailingData`]:
+///
+/// ```
+/// use pupper::{Diagnostic, ParseOptions, Pup, Segment, SegmentId, ValidationLevel};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"foo".to_vec()));
+/// let mut data = Vec::<u8>::from(&pup);
+/// let len = data.len() as u64;
+/// data.extend_from_slice(b"bar");
+///
+/// let mut warnings = Vec::new();
+/// let parsed = Pup::parse(
+///     &data,
+///     &mut ParseOptions {
+///         validation: ValidationLevel::Strict,
+///         on_warning: Some(Box::new(|x| warnings.push(x))),
+///         ..Default::default()
+///     },
+/// );
+///
+/// assert_eq!(Ok(pup), parsed);
+/// assert_eq!(vec![Diagnostic::TrailingData { offset: len, size: 3 }], warnings);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ValidationLevel {
+    /// Only what is necessary to read the header. Segments that can't be read are skipped.
+    None,
+    /// Every segment must have a signature and data.
+    #[default]
+    Structural,
+    /// The header must be free of inconsistencies (see [`crate::validate`]), except for
+    /// non-canonical segment order, missing companion segments, and trailing data.
+    Strict,
+    /// Every segment signature and the header signature must be valid for [`crate::HMAC_KEY`].
+    Cryptographic,
+}
+
+/// A non-fatal oddity reported through [`ParseOptions::on_warning`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Diagnostic {
+    /// The header size differs from the one this crate would serialize, which, like official
+    /// PUPs, is rounded up to a multiple of 0x10.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Diagnostic, ParseOptions, Pup};
+    ///
+    /// // Official PUPs have 12 segments and a header of 0x350 bytes.
+    /// let pup = Pup::standard(0);
+    /// let mut data = Vec::<u8>::from(&pup);
+    /// assert_eq!(0x350_u64.to_be_bytes(), data[0x20..0x28]);
+    ///
+    /// let mut parse = |data: &[u8]| {
+    ///     let mut warnings = Vec::new();
+    ///     Pup::parse(
+    ///         data,
+    ///         &mut ParseOptions {
+    ///             on_warning: Some(Box::new(|x| warnings.push(x))),
+    ///             ..Default::default()
+    ///         },
+    ///     )
+    ///     .unwrap();
+    ///
+    ///     warnings
+    /// };
+    ///
+    /// assert_eq!(Vec::<Diagnostic>::new(), parse(&data));
+    ///
+    /// data[0x20..0x28].copy_from_slice(&0x348_u64.to_be_bytes());
+    ///
+    /// assert!(parse(&data).contains(&Diagnostic::NonstandardHeaderSize {
+    ///     declared: 0x348,
+    ///     expected: 0x350,
+    /// }));
+    /// ```
+    NonstandardHeaderSize {
+        /// The header size declared in the header.
+        declared: u64,
+        /// The header size this crate would serialize.
+        expected: u64,
+    },
+    /// The header padding contains a non-zero byte at a specific file offset.
+    NonZeroPadding {
+        /// The file offset of the first non-zero padding byte.
+        offset: u64,
+    },
+    /// A segment has an ID with no file name (see [`ParseOptions::names`]).
+    UnknownSegmentId {
+        /// The index of the segment.
+        index: u64,
+        /// The ID of the segment.
+        id: SegmentId,
+    },
+    /// A signature refers to a segment index that doesn't exist.
+    OrphanSignature(u64),
+    /// The segment count disagrees with the number of decodable table entries (only reported by
+    /// [`ValidationLevel::None`]; other levels return [`ParseError::SegmentCountMismatch`]).
+    SegmentCountMismatch {
+        /// The segment count declared in the header.
+        declared: u64,
+        /// The number of decodable segment table entries.
+        seg_entries: u64,
+        /// The number of decodable digest table entries.
+        sig_entries: u64,
+    },
+    /// The segment at a specific index lacks a signature or data, and was skipped (only reported
+    /// by [`ValidationLevel::None`]).
+    SkippedSegment(u64),
+    /// The package version isn't the one this crate knows (only reported by
+    /// [`ValidationLevel::None`]; other levels return [`ParseError::UnsupportedPackageVersion`]).
+    UnsupportedPackageVersion(u64),
+    /// Data follow the end the header declares; this may be another PUP (see [`PupStream`]).
+    TrailingData {
+        /// The file offset of the trailing data.
+        offset: u64,
+        /// The size of the trailing data, in bytes.
+        size: u64,
+    },
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NonstandardHeaderSize { declared, expected } => write!(
+                f,
+                "header size is {}, but would be serialized as {}",
+                declared, expected
+            ),
+            Self::NonZeroPadding { offset } => {
+                write!(f, "header padding at offset {} is non-zero", offset)
+            }
+            Self::UnknownSegmentId { index, id } => {
+                write!(f, "segment {} has unknown ID {:#x}", index, id.0)
+            }
+            Self::OrphanSignature(i) => {
+                write!(f, "signature refers to nonexistent segment {}", i)
+            }
+            Self::SegmentCountMismatch {
+                declared,
+                seg_entries,
+                sig_entries,
+            } => write!(
+                f,
+                "segment count is {}, but there are {} segment entries and {} signature entries",
+                declared, seg_entries, sig_entries
+            ),
+            Self::SkippedSegment(i) => write!(f, "segment {} was skipped", i),
+            Self::UnsupportedPackageVersion(version) => {
+                write!(f, "package version {} is unsupported", version)
+            }
+            Self::TrailingData { offset, size } => {
+                write!(f, "{} B of trailing data at offset {}", size, offset)
+            }
+        }
+    }
+}
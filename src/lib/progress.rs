@@ -0,0 +1,115 @@
+use crate::{BuildError, ParseError};
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Progress reporting and cancellation for long-running operations: parsing with
+/// [`ValidationLevel::Cryptographic`], [`Pup::serialize_with_progress`], and
+/// [`verify_with_progress`].
+///
+/// Unpacking is left to callers, which can report progress per segment themselves; this crate
+/// has no C interface.
+///
+/// [`ValidationLevel::Cryptographic`]: crate::ValidationLevel::Cryptographic
+/// [`Pup::serialize_with_progress`]: crate::Pup::serialize_with_progress
+/// [`verify_with_progress`]: crate::verify_with_progress
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{
+///     BuildError, CancellationToken, Progress, Pup, Segment, SegmentId, SerializeOptions,
+/// };
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+///
+/// let mut reports = Vec::new();
+/// let data = pup.serialize_with_progress(
+///     &SerializeOptions::default(),
+///     &mut Progress {
+///         on_progress: Some(Box::new(|done, total| reports.push((done, total)))),
+///         ..Default::default()
+///     },
+/// );
+///
+/// assert!(data.is_ok());
+/// assert_eq!(Some(&(0x100, 0x100)), reports.last());
+///
+/// let cancellation = CancellationToken::new();
+/// cancellation.cancel();
+///
+/// let data = pup.serialize_with_progress(
+///     &SerializeOptions::default(),
+///     &mut Progress {
+///         cancellation: Some(cancellation),
+///         ..Default::default()
+///     },
+/// );
+///
+/// assert_eq!(Err(BuildError::Cancelled), data);
+/// ```
+#[derive(Default)]
+pub struct Progress<'a> {
+    /// Called with the number of bytes processed so far and the total number of bytes.
+    pub on_progress: Option<Box<dyn FnMut(u64, u64) + 'a>>,
+    /// Checked periodically; once cancelled, the operation stops with [`ParseError::Cancelled`]
+    /// or [`BuildError::Cancelled`].
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Progress<'_> {
+    /// Reports progress, then fails if the operation has been cancelled.
+    pub(crate) fn report(&mut self, done: u64, total: u64) -> Result<(), Cancelled> {
+        if let Some(on_progress) = self.on_progress.as_mut() {
+            on_progress(done, total);
+        }
+
+        match self.cancellation {
+            Some(ref x) if x.is_cancelled() => Err(Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The operation was cancelled; converts into the error type of whichever operation it was.
+pub(crate) struct Cancelled;
+
+impl From<Cancelled> for ParseError {
+    fn from(_: Cancelled) -> Self {
+        Self::Cancelled
+    }
+}
+
+impl From<Cancelled> for BuildError {
+    fn from(_: Cancelled) -> Self {
+        Self::Cancelled
+    }
+}
+
+/// A flag that aborts an operation when set, typically from another thread.
+///
+/// Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new, uncancelled [`CancellationToken`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that the operation stop as soon as possible.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`Self::cancel`] has been called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
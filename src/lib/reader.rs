@@ -0,0 +1,144 @@
+use crate::{Pup, Segment, SegmentEntry};
+
+use std::{
+    convert::TryFrom as _,
+    io::{self, BufRead, Cursor, Read, Seek, SeekFrom},
+};
+
+/// A [`Read`] and [`Seek`] view of the data of a segment, for consumers that take a stream rather
+/// than a slice.
+///
+/// The data are borrowed, not copied. Seeking is relative to the start of the segment, and
+/// reading stops at its end.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, Segment, SegmentId};
+/// use std::io::{Read as _, Seek as _, SeekFrom};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+///
+/// let mut reader = pup.segment_reader(0).unwrap();
+/// reader.seek(SeekFrom::Start(2)).unwrap();
+/// let mut minor = String::new();
+/// reader.read_to_string(&mut minor).unwrap();
+///
+/// assert_eq!("88\n", minor);
+/// assert!(pup.segment_reader(1).is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SegmentReader<'a> {
+    inner: Cursor<&'a [u8]>,
+}
+
+impl<'a> SegmentReader<'a> {
+    /// Creates a [`SegmentReader`] over the data of a [`Segment`].
+    #[must_use]
+    pub fn new(seg: &'a Segment) -> Self {
+        Self::from_data(seg.data())
+    }
+
+    /// Creates a [`SegmentReader`] over the data a [`SegmentEntry`] refers to within a serialized
+    /// PUP, such as one walked with [`RawHeader`], without parsing the PUP.
+    ///
+    /// Returns [`None`] if the data are out of bounds.
+    ///
+    /// [`RawHeader`]: crate::RawHeader
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, RawHeader, Segment, SegmentId, SegmentReader};
+    /// use std::{convert::TryFrom as _, io::Read as _};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    /// let data = Vec::<u8>::from(&pup);
+    /// let header = RawHeader::try_from(data.as_slice()).unwrap();
+    ///
+    /// let mut version = String::new();
+    /// SegmentReader::from_entry(&data, &header.segment_entries[0])
+    ///     .unwrap()
+    ///     .read_to_string(&mut version)
+    ///     .unwrap();
+    ///
+    /// assert_eq!("4.88\n", version);
+    /// ```
+    #[must_use]
+    pub fn from_entry(data: &'a [u8], entry: &SegmentEntry) -> Option<Self> {
+        let start = usize::try_from(entry.offset).ok()?;
+        let end = start.checked_add(usize::try_from(entry.size).ok()?)?;
+
+        data.get(start..end).map(Self::from_data)
+    }
+
+    fn from_data(data: &'a [u8]) -> Self {
+        Self {
+            inner: Cursor::new(data),
+        }
+    }
+
+    /// The size of the segment data, in bytes.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.inner.get_ref().len() as u64
+    }
+
+    /// Whether the segment data are empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.get_ref().is_empty()
+    }
+
+    /// The current position within the segment data.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.inner.position()
+    }
+
+    /// The whole of the segment data, regardless of the position.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        self.inner.get_ref()
+    }
+}
+
+impl Read for SegmentReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl BufRead for SegmentReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+    }
+}
+
+impl Seek for SegmentReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl Segment {
+    /// A [`SegmentReader`] over the data of this segment.
+    #[must_use]
+    pub fn reader(&self) -> SegmentReader<'_> {
+        SegmentReader::new(self)
+    }
+}
+
+impl Pup {
+    /// A [`SegmentReader`] over the data of the segment at the given index, if there is one.
+    #[must_use]
+    pub fn segment_reader(&self, index: usize) -> Option<SegmentReader<'_>> {
+        self.segments.get(index).map(Segment::reader)
+    }
+}
@@ -0,0 +1,338 @@
+use crate::{
+    content::tar, FixedSize as _, HeaderMetadata, Magic, ParseError, ParseOptions, Pup, RawHeader,
+    Segment, SegmentId, ValidationLevel,
+};
+
+use std::{
+    convert::{TryFrom as _, TryInto as _},
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+/// Finds the PUPs embedded in larger data, such as a NAND or NOR dump.
+///
+/// Each candidate found by its magic must be entirely present and parse at
+/// [`ValidationLevel::Strict`]. PUPs within an accepted PUP aren't searched for.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, Segment, SegmentId};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+/// let pup = Vec::<u8>::from(&pup);
+///
+/// let mut dump = vec![0xFF; 0x1000];
+/// dump.extend_from_slice(&pup);
+/// dump.extend_from_slice(&[0xFF; 0x1000]);
+///
+/// let ranges = pupper::carve(&dump);
+///
+/// assert_eq!(vec![0x1000..(0x1000 + pup.len() as u64)], ranges);
+/// assert_eq!(pup, &dump[0x1000..(0x1000 + pup.len())]);
+/// ```
+#[must_use]
+pub fn carve(data: &[u8]) -> Vec<Range<u64>> {
+    let magic = Magic::default().0;
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+
+    while let Some(found) = data
+        .get(offset..)
+        .and_then(|x| x.windows(Magic::SIZE).position(|x| x == magic))
+    {
+        let start = offset + found;
+        match carve_at(&data[start..]) {
+            Some(len) => {
+                ranges.push((start as u64)..((start + len) as u64));
+                offset = start + len;
+            }
+            None => offset = start + 1,
+        }
+    }
+
+    ranges
+}
+
+// Returns the length of the valid PUP at the start of the data.
+fn carve_at(data: &[u8]) -> Option<usize> {
+    let meta = HeaderMetadata::try_from(data).ok()?;
+    let len = meta
+        .header_size
+        .checked_add(meta.data_size)
+        .and_then(|x| usize::try_from(x).ok())?;
+    let data = data.get(..len)?;
+
+    let mut options = ParseOptions {
+        validation: ValidationLevel::Strict,
+        ..Default::default()
+    };
+
+    Pup::parse(data, &mut options).ok().map(|_| len)
+}
+
+/// Salvages what it can from a truncated or damaged PUP.
+///
+/// If the header is readable, every segment it describes that is entirely present is kept, along
+/// with its signature. Otherwise (or if the header describes nothing that's present), the data are
+/// scanned for SCE containers and tar archives, the formats of the large segments, and their IDs
+/// are guessed from their contents. Either way, everything that couldn't be recovered is noted.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, RecoveryNote, Segment, SegmentId};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+/// pup.segments.push(Segment::new(SegmentId(0x300), vec![0; 0x100]));
+/// let data = Vec::<u8>::from(&pup);
+///
+/// // A download that stopped halfway through the last segment.
+/// let recovery = pupper::recover(&data[..(data.len() - 0x80)]);
+///
+/// assert_eq!(1, recovery.pup.segments.len());
+/// assert_eq!(b"4.90", recovery.pup.segments[0].data());
+/// assert_eq!(
+///     vec![RecoveryNote::SegmentTruncated {
+///         index: 1,
+///         id: SegmentId(0x300),
+///     }],
+///     recovery.notes,
+/// );
+/// ```
+#[must_use]
+pub fn recover(data: &[u8]) -> Recovery {
+    match RawHeader::try_from(data) {
+        Ok(header) => {
+            let recovery = recover_from_header(data, &header);
+            if !recovery.pup.segments.is_empty() || header.segment_entries.is_empty() {
+                return recovery;
+            }
+
+            // The header is probably garbage that happens to parse.
+            scan(data, recovery.notes)
+        }
+        Err(err) => scan(data, vec![RecoveryNote::HeaderUnreadable(err)]),
+    }
+}
+
+fn recover_from_header(data: &[u8], header: &RawHeader) -> Recovery {
+    let mut notes = Vec::new();
+    let mut segments = Vec::new();
+
+    for (i, entry) in header.segment_entries.iter().enumerate() {
+        let seg_data = usize::try_from(entry.offset)
+            .ok()
+            .zip(usize::try_from(entry.size).ok())
+            .and_then(|(start, size)| data.get(start..start.checked_add(size)?));
+        let seg_data = match seg_data {
+            Some(x) => x,
+            None => {
+                notes.push(RecoveryNote::SegmentTruncated {
+                    index: i as u64,
+                    id: entry.id,
+                });
+                continue;
+            }
+        };
+
+        let mut seg = Segment::new(entry.id, seg_data.to_vec());
+        seg.sig_kind = entry.signature_kind;
+        match header
+            .digest_entries
+            .iter()
+            .find(|x| x.segment_index == i as u64)
+        {
+            Some(digest) => seg.set_signature(digest.digest),
+            None => {
+                seg.sign();
+                notes.push(RecoveryNote::SegmentResigned(segments.len() as u64));
+            }
+        }
+
+        segments.push(seg);
+    }
+
+    let mut pup = Pup::new(segments, header.metadata.image_version);
+    pup.package_version = header.metadata.package_version;
+
+    Recovery { pup, notes }
+}
+
+// Finds segments by their formats alone.
+fn scan(data: &[u8], mut notes: Vec<RecoveryNote>) -> Recovery {
+    let mut segments = Vec::new();
+    let mut self_count = 0;
+    let mut covered = 0;
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let found = find_sce(&data[offset..])
+            .map(|(len, is_self)| {
+                let id = match (is_self, self_count) {
+                    (false, _) => SegmentId(0x203),
+                    // ps3swu.self comes first and ps3swu2.self last.
+                    (true, 0) => SegmentId(0x200),
+                    (true, _) => SegmentId(0x601),
+                };
+                self_count += usize::from(is_self);
+
+                (len, id)
+            })
+            .or_else(|| find_tar(&data[offset..]));
+
+        let (len, id) = match found {
+            Some(x) => x,
+            None => {
+                offset += 1;
+                continue;
+            }
+        };
+
+        if offset > covered {
+            notes.push(RecoveryNote::Unrecovered {
+                start: covered as u64,
+                end: offset as u64,
+            });
+        }
+        notes.push(RecoveryNote::SegmentGuessed {
+            offset: offset as u64,
+            size: len as u64,
+            id,
+        });
+
+        let mut seg = Segment::new(id, data[offset..(offset + len)].to_vec());
+        seg.sign();
+        segments.push(seg);
+
+        offset += len;
+        covered = offset;
+    }
+
+    if data.len() > covered {
+        notes.push(RecoveryNote::Unrecovered {
+            start: covered as u64,
+            end: data.len() as u64,
+        });
+    }
+
+    // The image version may well have survived.
+    let image_version = HeaderMetadata::try_from(data).map_or(0, |x| x.image_version);
+
+    Recovery {
+        pup: Pup::new(segments, image_version),
+        notes,
+    }
+}
+
+// Returns the length of the SCE container at the start of the data, if it's a SELF or package
+// that's entirely present, and whether it's a SELF.
+fn find_sce(data: &[u8]) -> Option<(usize, bool)> {
+    if !data.starts_with(b"SCE\0") {
+        return None;
+    }
+
+    let is_self = match data.get(0x0A..0x0C)? {
+        [0, 1] => true,
+        [0, 3] => false,
+        _ => return None,
+    };
+    let header_len = u64::from_be_bytes(data.get(0x10..0x18)?.try_into().unwrap());
+    let data_len = u64::from_be_bytes(data.get(0x18..0x20)?.try_into().unwrap());
+
+    let len = usize::try_from(header_len.checked_add(data_len)?)
+        .ok()
+        .filter(|x| (0x20..=data.len()).contains(x))?;
+
+    Some((len, is_self))
+}
+
+// Returns the length of the tar archive at the start of the data, if it's entirely present, and
+// the ID of the segment it most likely is.
+fn find_tar(data: &[u8]) -> Option<(usize, SegmentId)> {
+    if !tar::is_tar(data) {
+        return None;
+    }
+
+    let len = tar::archive_len(data)?;
+    let members = tar::members(&data[..len]).ok()?;
+
+    let id = if members.iter().any(|x| x.name.contains("spkg_hdr")) {
+        SegmentId(0x501)
+    } else if members.iter().any(|x| x.name.ends_with(".pkg")) {
+        SegmentId(0x300)
+    } else {
+        SegmentId(0x201)
+    };
+
+    Some((len, id))
+}
+
+/// The result of [`recover`].
+#[derive(Debug, Eq, PartialEq)]
+pub struct Recovery {
+    /// The best-effort reconstruction.
+    pub pup: Pup,
+    /// What was guessed or couldn't be recovered, in the order it was found.
+    pub notes: Vec<RecoveryNote>,
+}
+
+/// Something [`recover`] had to guess or give up on.
+#[derive(Debug, Eq, PartialEq)]
+pub enum RecoveryNote {
+    /// The header couldn't be read, so segments were found by scanning for known formats.
+    HeaderUnreadable(ParseError),
+    /// The segment at a specific index of the header isn't entirely present and was dropped.
+    SegmentTruncated {
+        /// The index of the segment in the header.
+        index: u64,
+        /// The ID of the segment.
+        id: SegmentId,
+    },
+    /// The recovered segment at a specific index had no signature and was signed anew.
+    SegmentResigned(u64),
+    /// A segment was found by scanning, and its ID was guessed from its contents.
+    SegmentGuessed {
+        /// The file offset of the segment data.
+        offset: u64,
+        /// The size of the segment data, in bytes.
+        size: u64,
+        /// The guessed ID.
+        id: SegmentId,
+    },
+    /// A range of file offsets that no recovered segment accounts for.
+    Unrecovered {
+        /// The first offset.
+        start: u64,
+        /// The offset past the last.
+        end: u64,
+    },
+}
+
+impl Display for RecoveryNote {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::HeaderUnreadable(err) => write!(f, "header is unreadable: {}", err),
+            Self::SegmentTruncated { index, id } => {
+                write!(
+                    f,
+                    "segment {} with ID {} is truncated and was dropped",
+                    index, id
+                )
+            }
+            Self::SegmentResigned(i) => {
+                write!(f, "segment {} had no signature and was signed anew", i)
+            }
+            Self::SegmentGuessed { offset, size, id } => write!(
+                f,
+                "found {} B at {:#x}, guessed to have ID {}",
+                size, offset, id
+            ),
+            Self::Unrecovered { start, end } => {
+                write!(f, "bytes {:#x}..{:#x} couldn't be recovered", start, end)
+            }
+        }
+    }
+}
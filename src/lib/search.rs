@@ -0,0 +1,101 @@
+use crate::Pup;
+
+use std::io::{self, Read};
+
+/// The size of the chunks [`search`] reads at a time.
+const CHUNK_SIZE: usize = 0x10000;
+
+/// Finds every occurrence of a byte pattern in a stream, returning their offsets.
+///
+/// The stream is read in chunks, so it needn't fit in memory. Overlapping occurrences are all
+/// reported, and an empty pattern matches nothing.
+///
+/// # Examples
+///
+/// ```
+/// let data: &[u8] = b"SCE\0 ... SCE\0";
+///
+/// assert_eq!(vec![0, 9], pupper::search(data, b"SCE\0").unwrap());
+/// ```
+pub fn search(mut reader: impl Read, pattern: &[u8]) -> io::Result<Vec<u64>> {
+    let mut offsets = Vec::new();
+    if pattern.is_empty() {
+        return Ok(offsets);
+    }
+
+    // The window keeps the tail of the previous chunk so that matches spanning two chunks are
+    // found too.
+    let mut window = Vec::with_capacity(CHUNK_SIZE + pattern.len());
+    // The offset of the start of the window within the stream.
+    let mut window_offset = 0;
+    let mut chunk = vec![0; CHUNK_SIZE];
+
+    loop {
+        let len = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(x) => x,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        window.extend_from_slice(&chunk[..len]);
+
+        for (i, x) in window.windows(pattern.len()).enumerate() {
+            if x == pattern {
+                offsets.push(window_offset + i as u64);
+            }
+        }
+
+        // The tail is too short to hold a match by itself, so none of it has been checked yet.
+        let keep = window.len().min(pattern.len() - 1);
+        let drop = window.len() - keep;
+        window.drain(..drop);
+        window_offset += drop as u64;
+    }
+
+    Ok(offsets)
+}
+
+impl Pup {
+    /// Finds every occurrence of a byte pattern in the data of each segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, SearchMatch, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x102), b"vsh vsh".to_vec()));
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         SearchMatch { segment: 1, offset: 0 },
+    ///         SearchMatch { segment: 1, offset: 4 },
+    ///     ],
+    ///     pup.search(b"vsh"),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn search(&self, pattern: &[u8]) -> Vec<SearchMatch> {
+        self.segments
+            .iter()
+            .enumerate()
+            .flat_map(|(i, seg)| {
+                // Reading from a slice can't fail.
+                search(seg.data(), pattern)
+                    .unwrap()
+                    .into_iter()
+                    .map(move |offset| SearchMatch { segment: i, offset })
+            })
+            .collect()
+    }
+}
+
+/// An occurrence of a pattern found by [`Pup::search`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SearchMatch {
+    /// The index of the segment.
+    pub segment: usize,
+    /// The offset of the occurrence within the segment data.
+    pub offset: u64,
+}
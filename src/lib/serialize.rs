@@ -0,0 +1,379 @@
+use crate::{
+    header::Header, BuildError, Finding, ParseOptions, Progress, Pup, SegmentId, ValidationLevel,
+};
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    convert::TryFrom as _,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufWriter, Write as _},
+    path::Path,
+};
+
+impl Pup {
+    /// Serializes this [`Pup`] according to the given options.
+    ///
+    /// With the default options, this is equivalent to [`Vec::<u8>::from`], except that it fails
+    /// with [`BuildError::TooLarge`] instead of panicking.
+    ///
+    /// Serialization is deterministic: [`Pup`]s that are [semantically equal] and whose segments
+    /// have equal signatures always serialize to identical bytes. Tables follow segment order,
+    /// padding is zeroed, and nothing else (e.g., [`Pup::header_signature`],
+    /// [`Pup::extra_digests`], or segment provenance) is taken into account.
+    /// [`SerializeOptions::deterministic`] checks this for each serialization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{DuplicatePolicy, Pup, Segment, SegmentId, SerializeOptions};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"first".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"second".to_vec()));
+    ///
+    /// let options = SerializeOptions {
+    ///     duplicates: DuplicatePolicy::RejectOnSerialize,
+    ///     ..Default::default()
+    /// };
+    /// assert!(pup.serialize(&options).is_err());
+    ///
+    /// // Deduplicated output is just as deterministic.
+    /// let options = SerializeOptions {
+    ///     duplicates: DuplicatePolicy::DedupeKeepFirst,
+    ///     deterministic: true,
+    ///     ..Default::default()
+    /// };
+    /// pup.segments[1].set_alignment(0x40);
+    /// pup.segments.push(Segment::new(SegmentId(0x200), b"third".to_vec()));
+    /// let data = pup.serialize(&options).unwrap();
+    ///
+    /// pup.segments.remove(1);
+    /// assert_eq!(Vec::<u8>::from(&pup), data);
+    /// ```
+    ///
+    /// Segments that official packages only ever include together can be required to stay
+    /// together:
+    ///
+    /// ```
+    /// use pupper::{BuildError, Finding, Pup, Segment, SegmentId, SerializeOptions};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x601), Vec::new()));
+    ///
+    /// let options = SerializeOptions {
+    ///     require_companions: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     Err(BuildError::Inconsistent(Finding::MissingCompanion {
+    ///         index: 0,
+    ///         missing: SegmentId(0x501),
+    ///     })),
+    ///     pup.serialize(&options),
+    /// );
+    /// ```
+    ///
+    /// Parsing and serializing again reproduces the same bytes:
+    ///
+    /// ```
+    /// use pupper::{ParseOptions, Pup, Segment, SegmentId, SerializeOptions};
+    ///
+    /// let mut pup = Pup::new(Vec::new(), 1);
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+    /// pup.segments.push(Segment::new(SegmentId(0x300), vec![0xff; 3]));
+    /// pup.canonicalize(true);
+    ///
+    /// let options = SerializeOptions {
+    ///     deterministic: true,
+    ///     ..Default::default()
+    /// };
+    /// let data = pup.serialize(&options).unwrap();
+    ///
+    /// let mut parse_options = ParseOptions {
+    ///     record_provenance: true,
+    ///     ..Default::default()
+    /// };
+    /// let parsed = Pup::parse(&data, &mut parse_options).unwrap();
+    /// assert_eq!(Ok(data), parsed.serialize(&options));
+    /// ```
+    ///
+    /// [semantically equal]: Pup::semantically_eq
+    /// [`Vec::<u8>::from`]: From::from
+    pub fn serialize(&self, options: &SerializeOptions) -> Result<Vec<u8>, BuildError> {
+        self.serialize_with_progress(options, &mut Progress::default())
+    }
+
+    /// Like [`Self::serialize`], but reports progress as segment data are copied and can be
+    /// cancelled.
+    pub fn serialize_with_progress(
+        &self,
+        options: &SerializeOptions,
+        progress: &mut Progress,
+    ) -> Result<Vec<u8>, BuildError> {
+        if options.require_companions {
+            if let Some(finding) = self
+                .validate_with(options)
+                .into_iter()
+                .find(|x| matches!(x, Finding::MissingCompanion { .. }))
+            {
+                return Err(BuildError::Inconsistent(finding));
+            }
+        }
+
+        let pup = match options.duplicates {
+            DuplicatePolicy::Allow => Cow::Borrowed(self),
+            DuplicatePolicy::RejectOnSerialize => match self.first_duplicate_id() {
+                Some(id) => return Err(BuildError::DuplicateSegmentId(id)),
+                None => Cow::Borrowed(self),
+            },
+            DuplicatePolicy::DedupeKeepFirst => Cow::Owned(self.deduped()),
+        };
+        let data = pup.to_bytes(progress)?;
+
+        // The check is against what was actually written, which is deduped if need be.
+        if options.deterministic && !reserializes_identically(&pup, &data) {
+            return Err(BuildError::NotDeterministic);
+        }
+
+        Ok(data)
+    }
+
+    /// Checks the structure this [`Pup`] would have if it were serialized with the given options.
+    ///
+    /// Duplicate segment IDs that [`DuplicatePolicy::DedupeKeepFirst`] would remove are not
+    /// reported.
+    #[must_use]
+    pub fn validate_with(&self, options: &SerializeOptions) -> Vec<Finding> {
+        match options.duplicates {
+            DuplicatePolicy::DedupeKeepFirst => self.deduped().validate(),
+            _ => self.validate(),
+        }
+    }
+
+    /// The number of bytes this [`Pup`] serializes to: the header, padded to a multiple of 0x10
+    /// bytes, followed by the data of each segment, padded as its alignment requires.
+    ///
+    /// This is exact for [`Vec::<u8>::from`] and [`Self::serialize`], unless
+    /// [`DuplicatePolicy::DedupeKeepFirst`] drops segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.90".to_vec()));
+    ///
+    /// assert_eq!(Vec::<u8>::from(&pup).len() as u64, pup.serialized_size());
+    ///
+    /// // Like official PUPs, a header of 12 segments takes 0x350 bytes.
+    /// let pup = Pup::new((0..12).map(|x| Segment::new(SegmentId(x), Vec::new())).collect(), 0);
+    ///
+    /// assert_eq!(0x350, pup.serialized_size());
+    /// ```
+    ///
+    /// [`Vec::<u8>::from`]: From::from
+    #[must_use]
+    pub fn serialized_size(&self) -> u64 {
+        // Saturating, because a size that doesn't fit in a u64 is too large to serialize anyway.
+        let header_size = Header::padded_size(self.segments.len() as u64);
+
+        self.segments.iter().fold(header_size, |size, seg| {
+            size.saturating_add(seg.padding_at(size))
+                .saturating_add(seg.data.len() as u64)
+        })
+    }
+
+    /// Serializes this [`Pup`] straight into a new file at the given path, without holding the
+    /// whole package in memory.
+    ///
+    /// The file is extended to [`Self::serialized_size`] up front, so that the filesystem can
+    /// allocate it in one piece, and synced to disk before this returns. The contents are the same
+    /// as [`Vec::<u8>::from`]. If writing fails, the partially written file is left behind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::Pup;
+    /// use std::{env, fs};
+    ///
+    /// let pup = Pup::standard(0);
+    /// let path = env::temp_dir().join("pupper-write-to-file.PUP");
+    /// pup.write_to_file(&path).unwrap();
+    ///
+    /// assert_eq!(Vec::<u8>::from(&pup), fs::read(&path).unwrap());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// [`Vec::<u8>::from`]: From::from
+    pub fn write_to_file(&self, path: &Path) -> Result<(), WriteError> {
+        let size = self.serialized_size();
+        // Header::from() adds sizes up unchecked.
+        if usize::try_from(size).is_err() {
+            return Err(WriteError::Build(BuildError::TooLarge));
+        }
+
+        let header = Header::from(self);
+
+        let file = File::create(path).map_err(WriteError::Io)?;
+        file.set_len(size).map_err(WriteError::Io)?;
+
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(&Vec::from(&header))
+            .map_err(WriteError::Io)?;
+
+        // Segment data are laid out back-to-back, in order, right after the header, with only
+        // the padding their alignment requires in between.
+        let mut offset = header.meta.header_size;
+        for (i, (entry, seg)) in header
+            .seg_table
+            .iter()
+            .zip(self.segments.iter())
+            .enumerate()
+        {
+            let padding = seg.padding_at(offset);
+            offset += padding;
+            if entry.offset != offset || entry.size != seg.data.len() as u64 {
+                return Err(WriteError::Build(BuildError::LayoutMismatch(i as u64)));
+            }
+
+            writer
+                .write_all(&vec![0; padding as usize])
+                .map_err(WriteError::Io)?;
+            writer.write_all(&seg.data).map_err(WriteError::Io)?;
+            offset += entry.size;
+        }
+
+        writer
+            .into_inner()
+            .map_err(|err| WriteError::Io(err.into_error()))?
+            .sync_all()
+            .map_err(WriteError::Io)
+    }
+
+    pub(crate) fn to_bytes(&self, progress: &mut Progress) -> Result<Vec<u8>, BuildError> {
+        // Header::from() adds sizes up unchecked, so the total is checked beforehand. No Vec can
+        // hold more than isize::MAX bytes.
+        let size = usize::try_from(self.serialized_size())
+            .ok()
+            .filter(|x| isize::try_from(*x).is_ok())
+            .ok_or(BuildError::TooLarge)?;
+
+        // Create the header first to generate the segment table and location information.
+        let header = Header::from(self);
+
+        let data_size = header.meta.data_size;
+
+        let mut data = Vec::with_capacity(size);
+        data.append(&mut Vec::from(&header));
+        data.resize_with(size, Default::default);
+
+        // Fill in data according to the offset and size specified by the segment entries.
+        let mut done = 0;
+        progress.report(done, data_size)?;
+        for (i, entry) in header.seg_table.iter().enumerate() {
+            let seg_data = &self.segments[i].data;
+            let start = entry.offset as usize;
+
+            data.get_mut(start..start.saturating_add(entry.size as usize))
+                .filter(|x| x.len() == seg_data.len())
+                .ok_or(BuildError::LayoutMismatch(i as u64))?
+                .copy_from_slice(seg_data);
+
+            done += entry.size;
+            progress.report(done, data_size)?;
+        }
+
+        Ok(data)
+    }
+
+    fn first_duplicate_id(&self) -> Option<SegmentId> {
+        self.segments
+            .iter()
+            .enumerate()
+            .find(|(i, seg)| self.segments[..*i].iter().any(|x| x.id == seg.id))
+            .map(|(_, seg)| seg.id)
+    }
+
+    fn deduped(&self) -> Self {
+        let mut pup = self.clone();
+        let mut seen = HashSet::new();
+        pup.segments.retain(|seg| seen.insert(seg.id));
+
+        pup
+    }
+}
+
+// Whether parsing the given serialization of a Pup and serializing the result again reproduces
+// it. Alignment isn't serialized, so it's carried over from the original.
+fn reserializes_identically(pup: &Pup, data: &[u8]) -> bool {
+    let mut options = ParseOptions {
+        validation: ValidationLevel::None,
+        ..Default::default()
+    };
+
+    Pup::parse(data, &mut options)
+        .ok()
+        .and_then(|mut x| {
+            for (seg, original) in x.segments.iter_mut().zip(pup.segments.iter()) {
+                seg.set_alignment(original.alignment());
+            }
+
+            x.to_bytes(&mut Progress::default()).ok()
+        })
+        .as_deref()
+        == Some(data)
+}
+
+/// An erroneous result returned by [`Pup::write_to_file`].
+#[derive(Debug)]
+pub enum WriteError {
+    /// The [`Pup`] can't be serialized.
+    Build(BuildError),
+    /// Writing the file failed.
+    Io(io::Error),
+}
+
+impl Display for WriteError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Build(err) => write!(f, "{}", err),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Options for [`Pup::serialize`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SerializeOptions {
+    /// How segments sharing an ID are handled.
+    pub duplicates: DuplicatePolicy,
+    /// Whether to fail with [`BuildError::Inconsistent`] if a segment is missing a segment that
+    /// official packages always include alongside it (see [`Finding::MissingCompanion`]).
+    pub require_companions: bool,
+    /// Whether to check that the output depends only on the contents of the [`Pup`], as
+    /// documented for [`Pup::serialize`], by parsing it back and serializing it again.
+    ///
+    /// Serialization fails with [`BuildError::NotDeterministic`] if the two differ. This always
+    /// succeeds unless this crate has a bug; it's meant for reproducible-build pipelines that
+    /// would rather fail than publish a package they can't reproduce.
+    pub deterministic: bool,
+}
+
+/// The behavior when two or more segments share an ID.
+///
+/// A PUP with duplicate segment IDs is ambiguous: there's no telling which of the segments the
+/// updater will pick.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DuplicatePolicy {
+    /// Serialize all segments as-is.
+    #[default]
+    Allow,
+    /// Fail with [`BuildError::DuplicateSegmentId`].
+    RejectOnSerialize,
+    /// Serialize only the first segment with any given ID.
+    DedupeKeepFirst,
+}
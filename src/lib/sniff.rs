@@ -0,0 +1,62 @@
+use crate::{header::meta::Metadata, FixedSize as _, Magic};
+
+use std::convert::TryInto as _;
+
+/// Cheaply checks whether data look like a PUP that this crate can parse.
+///
+/// Only the magic and package version are read; see [`sniff`].
+///
+/// # Examples
+///
+/// ```
+/// use pupper::Pup;
+///
+/// assert!(pupper::is_pup(&Vec::<u8>::from(&Pup::default())));
+/// assert!(!pupper::is_pup(b"SCEUF"));
+/// ```
+#[must_use]
+pub fn is_pup(data: &[u8]) -> bool {
+    sniff(data) == Some(PupKind::Supported)
+}
+
+/// Cheaply recognizes a PUP from the magic and package version in its first 0x10 bytes, without
+/// parsing anything else.
+///
+/// Returns [`None`] if the data don't start with the PUP magic.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Pup, PupKind};
+///
+/// let mut data = Vec::<u8>::from(&Pup::default());
+/// assert_eq!(Some(PupKind::Supported), pupper::sniff(&data));
+///
+/// data[0x0F] = 2;
+/// assert_eq!(Some(PupKind::UnsupportedVersion(2)), pupper::sniff(&data));
+///
+/// assert_eq!(None, pupper::sniff(b"\x7FELF"));
+/// ```
+#[must_use]
+pub fn sniff(data: &[u8]) -> Option<PupKind> {
+    if data.get(..Magic::SIZE)? != Magic::default().0 {
+        return None;
+    }
+
+    let pkg_version = u64::from_be_bytes(data.get(0x08..0x10)?.try_into().unwrap());
+
+    Some(if pkg_version == Metadata::PKG_VERSION {
+        PupKind::Supported
+    } else {
+        PupKind::UnsupportedVersion(pkg_version)
+    })
+}
+
+/// The kind of PUP recognized by [`sniff`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PupKind {
+    /// A PUP with the package version this crate supports.
+    Supported,
+    /// A PUP with some other package version.
+    UnsupportedVersion(u64),
+}
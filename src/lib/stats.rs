@@ -0,0 +1,123 @@
+use crate::{Pup, SegmentId, SignatureKind};
+
+use std::collections::BTreeMap;
+
+impl Pup {
+    /// Summarizes the size and composition of this [`Pup`] as it would be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+    ///
+    /// let stats = pup.stats();
+    ///
+    /// assert_eq!(stats.total_size, stats.header_size + 0x100);
+    /// assert_eq!(0x100, stats.segments[0].size);
+    /// ```
+    ///
+    /// Padding for alignment counts as overhead, not data:
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId};
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+    /// pup.segments[0].set_alignment(0x1000);
+    ///
+    /// let stats = pup.stats();
+    ///
+    /// assert_eq!(0x100, stats.data_size);
+    /// assert_eq!(0x1000 - stats.header_size + 0x100, stats.padded_data_size);
+    /// assert_eq!(0x1000, stats.overhead());
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        let header_size = self.header_size() as u64;
+        let padded_data_size = self.data_size() as u64;
+        // [may_panic(Add)]
+        let total_size = header_size + padded_data_size;
+
+        let segments: Vec<_> = self
+            .segments
+            .iter()
+            .map(|seg| {
+                let size = seg.data.len() as u64;
+
+                SegmentStats {
+                    id: seg.id,
+                    size,
+                    percentage: percentage(size, total_size),
+                }
+            })
+            .collect();
+        let data_size = segments.iter().map(|x| x.size).sum();
+
+        let mut signature_kinds = BTreeMap::new();
+        for seg in self.segments.iter() {
+            *signature_kinds.entry(seg.signature_kind()).or_insert(0) += 1;
+        }
+
+        Stats {
+            total_size,
+            header_size,
+            data_size,
+            padded_data_size,
+            segments,
+            signature_kinds,
+        }
+    }
+}
+
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        ((part as f64) / (whole as f64)) * 100.0
+    }
+}
+
+/// A size and composition summary of a [`Pup`], returned by [`Pup::stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The size of the entire serialized PUP, in bytes.
+    pub total_size: u64,
+    /// The size of the header, including padding, in bytes.
+    pub header_size: u64,
+    /// The combined size of all segment data, in bytes.
+    pub data_size: u64,
+    /// The size of the data section, including padding for alignment, in bytes.
+    pub padded_data_size: u64,
+    /// Per-segment statistics, in segment order.
+    pub segments: Vec<SegmentStats>,
+    /// The number of segments signed with each [`SignatureKind`].
+    pub signature_kinds: BTreeMap<SignatureKind, usize>,
+}
+
+impl Stats {
+    /// The number of bytes that aren't segment data: the header and any padding.
+    #[must_use]
+    pub fn overhead(&self) -> u64 {
+        self.total_size.saturating_sub(self.data_size)
+    }
+
+    /// The share of the entire serialized PUP taken up by a number of bytes, from 0 to 100.
+    #[must_use]
+    pub fn percentage_of_total(&self, size: u64) -> f64 {
+        percentage(size, self.total_size)
+    }
+}
+
+/// Statistics for a single [`Segment`](crate::Segment), part of [`Stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SegmentStats {
+    /// The ID of the segment.
+    pub id: SegmentId,
+    /// The size of the segment data, in bytes.
+    pub size: u64,
+    /// The share of the entire serialized PUP taken up by this segment, from 0 to 100.
+    pub percentage: f64,
+}
@@ -0,0 +1,306 @@
+use crate::{
+    content::{self, ContentType},
+    header::Header,
+    Digest, FixedSize as _, Parallelism, ParseError, ParseOptions, Progress, Pup, SegmentId,
+    SegmentNameMap, HMAC_KEY,
+};
+
+use serde::ser::{Serialize, SerializeStruct as _, Serializer};
+
+use std::convert::TryInto as _;
+
+impl Pup {
+    /// Summarizes this [`Pup`] as `pupper print` does.
+    ///
+    /// The summary serializes (e.g., with `serde_json`) to the output of `pupper print`, with
+    /// kebab-case keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{
+    ///     content::ContentType, Pup, Segment, SegmentId, SegmentNameMap, SummaryOptions,
+    /// };
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88\n".to_vec()));
+    ///
+    /// let options = SummaryOptions {
+    ///     fingerprints: true,
+    ///     ..Default::default()
+    /// };
+    /// let summary = pup.summary(&SegmentNameMap::default(), &options);
+    /// let seg = &summary.segments[0];
+    ///
+    /// assert_eq!(Some("version.txt"), seg.file_name.as_deref());
+    /// assert_eq!(ContentType::Text, seg.content_type);
+    /// assert!(seg.fingerprint.is_some());
+    /// assert_eq!(None, seg.data);
+    /// ```
+    #[must_use]
+    pub fn summary(&self, names: &SegmentNameMap, options: &SummaryOptions) -> PupSummary {
+        let segments = self
+            .segments
+            .iter()
+            .map(|seg| SegmentSummary {
+                id: seg.id,
+                file_name: names.name_of(seg.id).map(Into::into),
+                size: seg.data().len() as u64,
+                signature: *seg.signature(),
+                content_type: content::sniff(seg.data()),
+                fingerprint: Some(seg.data())
+                    .filter(|_| options.fingerprints)
+                    .map(Digest::sha1_of),
+                data: Some(seg.data())
+                    .filter(|_| options.embed_data)
+                    .map(<[u8]>::to_vec),
+            })
+            .collect();
+
+        PupSummary {
+            image_version: self.image_version,
+            segments,
+        }
+    }
+}
+
+/// Options for [`Pup::summary`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SummaryOptions {
+    /// Whether to include the SHA-1 fingerprint of each segment's data.
+    pub fingerprints: bool,
+    /// Whether to include each segment's data.
+    pub embed_data: bool,
+}
+
+/// A summary of a [`Pup`], as returned by [`Pup::summary`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PupSummary {
+    /// The image version.
+    pub image_version: u64,
+    /// A summary of each segment, in order.
+    pub segments: Vec<SegmentSummary>,
+}
+
+impl Serialize for PupSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PupSummary", 2)?;
+        state.serialize_field("image-version", &self.image_version)?;
+        state.serialize_field("segments", &self.segments)?;
+        state.end()
+    }
+}
+
+/// A summary of a segment, as part of a [`PupSummary`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SegmentSummary {
+    /// The segment ID.
+    pub id: SegmentId,
+    /// The file name of the segment ID, if it has one.
+    pub file_name: Option<String>,
+    /// The size of the data, in bytes.
+    pub size: u64,
+    /// The signature.
+    pub signature: Digest,
+    /// The kind of data, as guessed by [`content::sniff`].
+    pub content_type: ContentType,
+    /// The SHA-1 digest of the data, if [`SummaryOptions::fingerprints`] is set.
+    pub fingerprint: Option<Digest>,
+    /// The data, if [`SummaryOptions::embed_data`] is set. It serializes as base64.
+    pub data: Option<Vec<u8>>,
+}
+
+impl Serialize for SegmentSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SegmentSummary", 7)?;
+        state.serialize_field("id", &format!("{:#x}", self.id.0))?;
+        state.serialize_field("file-name", &self.file_name)?;
+        state.serialize_field("size", &self.size)?;
+        state.serialize_field("signature", &self.signature.to_string())?;
+        state.serialize_field("content-type", &self.content_type.to_string())?;
+        // Optional fields are left out rather than null, as 'print' always has.
+        if let Some(fingerprint) = self.fingerprint {
+            state.serialize_field("fingerprint", &fingerprint.to_string())?;
+        }
+        if let Some(ref data) = self.data {
+            state.serialize_field("data", &base64::encode(data))?;
+        }
+        state.end()
+    }
+}
+
+/// Checks the signature of every segment of a serialized PUP, along with the header signature.
+///
+/// Unlike parsing with [`ValidationLevel::Cryptographic`], this doesn't give up at the first
+/// invalid signature; it only returns an error if the PUP can't be parsed at all. Segments are
+/// checked on as many threads as `parallelism` allows.
+///
+/// [`ValidationLevel::Cryptographic`]: crate::ValidationLevel::Cryptographic
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Parallelism, Pup, Segment, SegmentId};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+/// pup.segments.push(Segment::new(SegmentId(0x101), Vec::new()));
+/// pup.segments[1].sign();
+///
+/// let summary = pupper::verify(&Vec::<u8>::from(&pup), Parallelism::Auto).unwrap();
+///
+/// assert!(summary.header_signature_valid);
+/// assert_eq!(vec![0], summary.invalid_signatures);
+/// assert!(!summary.is_valid());
+/// ```
+pub fn verify(data: &[u8], parallelism: Parallelism) -> Result<VerificationSummary, ParseError> {
+    // The key is thread-local, but segments may be checked on other threads.
+    HMAC_KEY.with(|key| verify_with_key(data, key, parallelism))
+}
+
+/// Like [`verify`], but with the given HMAC key rather than [`HMAC_KEY`].
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{Parallelism, Pup, Segment, SegmentId};
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), b"4.88".to_vec()));
+/// pup.segments[0].sign();
+/// let data = Vec::<u8>::from(&pup);
+///
+/// let summary = pupper::verify_with_key(&data, b"not the key", Parallelism::Auto).unwrap();
+///
+/// assert!(!summary.header_signature_valid);
+/// assert_eq!(vec![0], summary.invalid_signatures);
+/// ```
+pub fn verify_with_key(
+    data: &[u8],
+    key: &[u8],
+    parallelism: Parallelism,
+) -> Result<VerificationSummary, ParseError> {
+    verify_with_progress(data, key, parallelism, &mut Progress::default())
+}
+
+/// Like [`verify_with_key`], but reports the number of segment bytes checked so far, and stops
+/// with [`ParseError::Cancelled`] once cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{CancellationToken, Parallelism, ParseError, Progress, Pup, Segment, SegmentId};
+/// use std::num::NonZeroUsize;
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+/// pup.segments.push(Segment::new(SegmentId(0x101), vec![0; 0x80]));
+/// pup.segments.iter_mut().for_each(Segment::sign);
+/// let data = Vec::<u8>::from(&pup);
+///
+/// let mut reports = Vec::new();
+/// let summary = pupper::HMAC_KEY.with(|key| {
+///     pupper::verify_with_progress(
+///         &data,
+///         key,
+///         Parallelism::Threads(NonZeroUsize::new(2).unwrap()),
+///         &mut Progress {
+///             on_progress: Some(Box::new(|done, total| reports.push((done, total)))),
+///             ..Default::default()
+///         },
+///     )
+/// });
+///
+/// assert!(summary.unwrap().is_valid());
+/// assert_eq!(Some(&(0x180, 0x180)), reports.last());
+///
+/// let cancellation = CancellationToken::new();
+/// cancellation.cancel();
+///
+/// let summary = pupper::verify_with_progress(
+///     &data,
+///     b"",
+///     Parallelism::Threads(NonZeroUsize::new(2).unwrap()),
+///     &mut Progress {
+///         cancellation: Some(cancellation),
+///         ..Default::default()
+///     },
+/// );
+///
+/// assert_eq!(Err(ParseError::Cancelled), summary);
+/// ```
+pub fn verify_with_progress(
+    data: &[u8],
+    key: &[u8],
+    parallelism: Parallelism,
+    progress: &mut Progress,
+) -> Result<VerificationSummary, ParseError> {
+    let pup = Pup::parse(data, &mut ParseOptions::default())?;
+    let header: Header = data.try_into()?;
+
+    let invalid_signatures = parallelism
+        .map_with_progress(
+            &pup.segments,
+            |seg| seg.data().len() as u64,
+            |seg| {
+                let expected = Digest::signature_with_key(seg.signature_kind(), key, seg.data());
+
+                *seg.signature() != expected
+            },
+            progress,
+        )?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, invalid)| *invalid)
+        .map(|(i, _)| i as u64)
+        .collect();
+
+    // The header signature covers everything in the header that precedes it.
+    let signed_size = (Header::unpadded_size(header.meta.seg_count) as usize) - Digest::SIZE;
+    let header_signature_valid =
+        *header.header_sig() == Digest::hmac_sha1_with_key(key, &data[..signed_size]);
+
+    Ok(VerificationSummary {
+        header_signature_valid,
+        invalid_signatures,
+    })
+}
+
+/// The outcome of checking the signatures of a serialized PUP, as returned by [`verify`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct VerificationSummary {
+    /// Whether the header signature is valid.
+    pub header_signature_valid: bool,
+    /// The indices of the segments whose signatures are invalid.
+    pub invalid_signatures: Vec<u64>,
+}
+
+impl VerificationSummary {
+    /// Whether every signature is valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.error().is_none()
+    }
+
+    /// The error parsing with [`ValidationLevel::Cryptographic`] would have failed with, if any.
+    ///
+    /// [`ValidationLevel::Cryptographic`]: crate::ValidationLevel::Cryptographic
+    #[must_use]
+    pub fn error(&self) -> Option<ParseError> {
+        match self.invalid_signatures.first() {
+            Some(i) => Some(ParseError::InvalidSignature(*i)),
+            None if !self.header_signature_valid => Some(ParseError::InvalidHeaderSignature),
+            None => None,
+        }
+    }
+}
+
+impl Serialize for VerificationSummary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("VerificationSummary", 3)?;
+        state.serialize_field("valid", &self.is_valid())?;
+        state.serialize_field("header-signature-valid", &self.header_signature_valid)?;
+        state.serialize_field("invalid-signatures", &self.invalid_signatures)?;
+        state.end()
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Digest, Pup};
+
+use std::fmt::{self, Display, Formatter};
+
+/// An update list (e.g., `ps3-updatelist.txt`), which Sony's update servers use to announce the
+/// system software they offer.
+///
+/// Each line is an entry of `;`-separated `key=value` fields:
+///
+/// ```text
+/// # US
+/// Dest=84;ImageVersion=0001ac7e;SystemSoftwareVersion=4.9100;CDN=http://…;CDN_Timeout=30;
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use pupper::UpdateList;
+///
+/// let list = UpdateList::parse(
+///     "# US\n\
+///      Dest=84;CompatibleSystemSoftwareVersion=4.9100-;\n\
+///      Dest=84;ImageVersion=0001ac7e;SystemSoftwareVersion=4.9100;CDN=http://x/PS3UPDAT.PUP;\n",
+/// );
+/// let image = list.image().unwrap();
+///
+/// assert_eq!(2, list.iter().count());
+/// assert_eq!(Some("4.91"), image.version());
+/// assert_eq!(Some(0x1ac7e), image.image_version);
+/// assert_eq!(Some("http://x/PS3UPDAT.PUP"), image.url.as_deref());
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UpdateList(Vec<UpdateListEntry>);
+
+impl UpdateList {
+    /// Parses an [`UpdateList`].
+    ///
+    /// Parsing never fails: blank lines and `#` comments are skipped, as are fields that aren't of
+    /// the form `key=value`. Fields of the wrong type (e.g., a non-hexadecimal image version) are
+    /// [`None`].
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let entries = text
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+            .map(UpdateListEntry::parse)
+            .collect();
+
+        Self(entries)
+    }
+
+    /// Iterates over all entries in the order they're listed.
+    pub fn iter(&self) -> impl Iterator<Item = &UpdateListEntry> {
+        self.0.iter()
+    }
+
+    /// The first entry that offers an image, i.e., has both a version and a URL.
+    #[must_use]
+    pub fn image(&self) -> Option<&UpdateListEntry> {
+        self.iter()
+            .find(|x| x.version().is_some() && x.url.is_some())
+    }
+
+    /// The first entry of a system software version.
+    ///
+    /// Trailing zeros don't matter, so `4.91` finds an entry listed as `4.9100`.
+    #[must_use]
+    pub fn find(&self, version: &str) -> Option<&UpdateListEntry> {
+        let version = normalize_version(version);
+
+        self.iter().find(|x| x.version() == Some(version))
+    }
+
+    /// Checks that a [`Pup`] matches the entry of the version named in its `version.txt`,
+    /// returning that entry.
+    ///
+    /// `data` is the serialization the [`Pup`] was parsed from. The image version is always
+    /// compared; the size and SHA-1 digest of `data` are only compared if the entry lists them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, Segment, SegmentId, UpdateList, UpdateListMismatch};
+    ///
+    /// let mut pup = Pup::new(Vec::new(), 0x1ac7e);
+    /// pup.segments.push(Segment::new(SegmentId(0x100), b"4.91\n".to_vec()));
+    /// let data = Vec::<u8>::from(&pup);
+    ///
+    /// let list = UpdateList::parse("ImageVersion=0001ac7e;SystemSoftwareVersion=4.9100;");
+    /// assert!(list.check(&pup, &data).is_ok());
+    ///
+    /// let list = UpdateList::parse("ImageVersion=0001ac7f;SystemSoftwareVersion=4.9100;");
+    /// assert_eq!(
+    ///     Err(UpdateListMismatch::ImageVersion {
+    ///         expected: 0x1ac7f,
+    ///         actual: 0x1ac7e,
+    ///     }),
+    ///     list.check(&pup, &data),
+    /// );
+    ///
+    /// let list = UpdateList::parse("ImageVersion=0001ac7e;SystemSoftwareVersion=4.9200;");
+    /// assert_eq!(
+    ///     Err(UpdateListMismatch::NotListed("4.91".into())),
+    ///     list.check(&pup, &data),
+    /// );
+    /// ```
+    pub fn check(&self, pup: &Pup, data: &[u8]) -> Result<&UpdateListEntry, UpdateListMismatch> {
+        let version = pup
+            .build_info()
+            .version
+            .ok_or(UpdateListMismatch::MissingVersion)?;
+        let entry = self
+            .find(&version)
+            .ok_or(UpdateListMismatch::NotListed(version))?;
+
+        match entry.image_version {
+            Some(expected) if expected != pup.image_version => {
+                return Err(UpdateListMismatch::ImageVersion {
+                    expected,
+                    actual: pup.image_version,
+                });
+            }
+            _ => {}
+        }
+        match entry.size {
+            Some(expected) if expected != data.len() as u64 => {
+                return Err(UpdateListMismatch::Size {
+                    expected,
+                    actual: data.len() as u64,
+                });
+            }
+            _ => {}
+        }
+        if let Some(expected) = entry.sha1 {
+            let actual = Digest::sha1_of(data);
+            if expected != actual {
+                return Err(UpdateListMismatch::Sha1 { expected, actual });
+            }
+        }
+
+        Ok(entry)
+    }
+}
+
+/// A line of an [`UpdateList`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UpdateListEntry {
+    /// The region code of the list (e.g., `84` for the US), from `Dest`.
+    pub dest: Option<String>,
+    /// The image version of the offered PUP, from `ImageVersion`.
+    pub image_version: Option<u64>,
+    /// The system software version as listed (e.g., `4.9100`), from `SystemSoftwareVersion`.
+    pub system_software_version: Option<String>,
+    /// The download URL of the offered PUP, from `CDN`.
+    pub url: Option<String>,
+    /// The size of the offered PUP, in bytes, from `Size`.
+    pub size: Option<u64>,
+    /// The SHA-1 digest of the offered PUP, from `SHA1`.
+    pub sha1: Option<Digest>,
+}
+
+impl UpdateListEntry {
+    fn parse(line: &str) -> Self {
+        let field = |name: &str| {
+            line.split(';').find_map(|x| {
+                x.split_once('=')
+                    .filter(|(key, _)| key.trim() == name)
+                    .map(|(_, value)| value.trim())
+            })
+        };
+
+        Self {
+            dest: field("Dest").map(Into::into),
+            image_version: field("ImageVersion").and_then(|x| u64::from_str_radix(x, 16).ok()),
+            system_software_version: field("SystemSoftwareVersion").map(Into::into),
+            url: field("CDN").map(Into::into),
+            size: field("Size").and_then(|x| x.parse().ok()),
+            sha1: field("SHA1").and_then(|x| x.parse().ok()),
+        }
+    }
+
+    /// The system software version without the zero padding of the list (e.g., `4.91`).
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.system_software_version
+            .as_deref()
+            .map(normalize_version)
+    }
+}
+
+// Update lists pad versions with zeros (e.g., '4.9100'), but people don't.
+fn normalize_version(version: &str) -> &str {
+    if version.contains('.') {
+        version.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        version
+    }
+}
+
+/// A way in which a [`Pup`] disagrees with an [`UpdateList`], as found by
+/// [`UpdateList::check`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum UpdateListMismatch {
+    /// The [`Pup`] has no `version.txt` to look up.
+    MissingVersion,
+    /// The list has no entry of the version named in `version.txt`.
+    NotListed(String),
+    /// The image version differs from the listed one.
+    ImageVersion {
+        /// The listed image version.
+        expected: u64,
+        /// The image version of the [`Pup`].
+        actual: u64,
+    },
+    /// The size of the serialization differs from the listed one.
+    Size {
+        /// The listed size, in bytes.
+        expected: u64,
+        /// The size of the serialization, in bytes.
+        actual: u64,
+    },
+    /// The SHA-1 digest of the serialization differs from the listed one.
+    Sha1 {
+        /// The listed digest.
+        expected: Digest,
+        /// The digest of the serialization.
+        actual: Digest,
+    },
+}
+
+impl Display for UpdateListMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MissingVersion => write!(f, "PUP has no version.txt"),
+            Self::NotListed(version) => write!(f, "update list has no entry for {}", version),
+            Self::ImageVersion { expected, actual } => write!(
+                f,
+                "image version is {:#x}, but the update list has {:#x}",
+                actual, expected
+            ),
+            Self::Size { expected, actual } => write!(
+                f,
+                "size is {} B, but the update list has {} B",
+                actual, expected
+            ),
+            Self::Sha1 { expected, actual } => write!(
+                f,
+                "SHA-1 is {}, but the update list has {}",
+                actual, expected
+            ),
+        }
+    }
+}
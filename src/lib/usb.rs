@@ -0,0 +1,125 @@
+use crate::{Digest, Pup};
+
+use std::{
+    convert::TryFrom as _,
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+/// The path, relative to the root of a USB drive, from which the PS3 installs updates.
+pub const USB_UPDATE_PATH: &str = "PS3/UPDATE/PS3UPDAT.PUP";
+
+/// The largest file FAT32 can hold.
+const FAT32_MAX_FILE_SIZE: u64 = 0xFFFF_FFFF;
+
+/// Copies a serialized PUP onto a USB drive, at [`USB_UPDATE_PATH`], so that a PS3 can install
+/// it.
+///
+/// The data are checked to be a PUP beforehand, and read back afterwards to make sure they
+/// arrived intact. On Linux, the drive is also checked to be formatted as FAT32, the only
+/// filesystem the PS3 reads updates from.
+///
+/// Returns the path of the copy.
+pub fn install_to_usb(root: &Path, data: &[u8]) -> Result<PathBuf, UsbError> {
+    Pup::try_from(data).map_err(UsbError::InvalidPup)?;
+
+    let size = data.len() as u64;
+    if size > FAT32_MAX_FILE_SIZE {
+        return Err(UsbError::TooLarge(size));
+    }
+
+    if let Some(fs_type) = filesystem_type(root) {
+        if fs_type != "vfat" {
+            return Err(UsbError::UnsupportedFilesystem(fs_type));
+        }
+    }
+
+    let path = root.join(USB_UPDATE_PATH);
+    // Writing to a temporary file first means a failed copy never leaves a corrupt package where
+    // the PS3 would find it.
+    let tmp_path = path.with_extension("PUP.tmp");
+
+    fs::create_dir_all(path.parent().unwrap()).map_err(UsbError::Io)?;
+    write_synced(&tmp_path, data).map_err(UsbError::Io)?;
+
+    let copy = fs::read(&tmp_path).map_err(UsbError::Io)?;
+    if Digest::sha1_of(&copy) != Digest::sha1_of(data) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(UsbError::VerificationFailed);
+    }
+
+    fs::rename(&tmp_path, &path).map_err(UsbError::Io)?;
+
+    Ok(path)
+}
+
+fn write_synced(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(data)?;
+
+    // USB sticks are notorious for caching writes.
+    file.sync_all()
+}
+
+// Finds the type of the filesystem mounted closest above the given path, if that's possible on
+// this platform.
+#[cfg(target_os = "linux")]
+fn filesystem_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as octal.
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn filesystem_type(_: &Path) -> Option<String> {
+    None
+}
+
+/// An erroneous result returned by [`install_to_usb`].
+#[derive(Debug)]
+pub enum UsbError {
+    /// The data aren't a valid PUP.
+    InvalidPup(crate::ParseError),
+    /// The PUP is too large for FAT32.
+    TooLarge(u64),
+    /// The drive has a filesystem other than FAT32.
+    UnsupportedFilesystem(String),
+    /// The copy differs from the original.
+    VerificationFailed,
+    /// Reading or writing failed.
+    Io(io::Error),
+}
+
+impl Display for UsbError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidPup(err) => write!(f, "data aren't a valid PUP: {}", err),
+            Self::TooLarge(size) => {
+                write!(f, "PUP is {} bytes, which is too large for FAT32", size)
+            }
+            Self::UnsupportedFilesystem(fs_type) => write!(
+                f,
+                "drive is formatted as '{}', but the PS3 only reads FAT32",
+                fs_type
+            ),
+            Self::VerificationFailed => write!(f, "copy differs from the original"),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
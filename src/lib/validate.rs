@@ -0,0 +1,299 @@
+use crate::{header::Header, ParseError, Pup, SegmentId};
+
+use std::{
+    convert::TryInto as _,
+    fmt::{self, Display, Formatter},
+};
+
+/// Checks the structure of a serialized PUP without verifying any signatures.
+///
+/// Unlike parsing a [`Pup`], this doesn't give up at the first inconsistency; it only returns an
+/// error if the header itself can't be read.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::Pup;
+///
+/// let data = Vec::<u8>::from(&Pup::default());
+///
+/// assert_eq!(Ok(Vec::new()), pupper::validate(&data));
+/// ```
+///
+/// Header fields are reported rather than trusted, however large they are:
+///
+/// ```
+/// use pupper::{Finding, ParseError, Pup};
+///
+/// let mut data = Vec::<u8>::from(&Pup::default());
+/// // A data size that overflows when added to the header size.
+/// data[0x28..0x30].copy_from_slice(&u64::MAX.to_be_bytes());
+///
+/// let findings = pupper::validate(&data).unwrap();
+/// assert!(findings.contains(&Finding::FileSizeMismatch {
+///     declared: u64::MAX,
+///     actual: data.len() as u64,
+/// }));
+///
+/// // A segment count whose tables can't fit in any file.
+/// data[0x18..0x20].copy_from_slice(&u64::MAX.to_be_bytes());
+///
+/// assert_eq!(Err(ParseError::Undersized), pupper::validate(&data));
+/// ```
+pub fn validate(data: &[u8]) -> Result<Vec<Finding>, ParseError> {
+    let header: Header = data.try_into()?;
+
+    Ok(check_header(&header, data.len() as u64))
+}
+
+impl Pup {
+    /// Checks the structure this [`Pup`] would have if it were serialized.
+    ///
+    /// As the header is derived from the segments, most checks can't fail here; the notable
+    /// exception is [`Finding::DuplicateSegmentId`].
+    #[must_use]
+    pub fn validate(&self) -> Vec<Finding> {
+        let header = Header::from(self);
+        let file_size = header.meta.header_size + header.meta.data_size;
+
+        check_header(&header, file_size)
+    }
+}
+
+pub(crate) fn check_header(header: &Header, file_size: u64) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let meta = &header.meta;
+
+    // The tables are decoded entry-by-entry, so any entry that failed to decode simply goes
+    // missing.
+    let seg_count = meta.seg_count;
+    if (header.seg_table.len() as u64 != seg_count)
+        || (header.digest_table.len() as u64 != seg_count)
+    {
+        findings.push(Finding::SegmentCountMismatch {
+            declared: seg_count,
+            seg_entries: header.seg_table.len() as u64,
+            sig_entries: header.digest_table.len() as u64,
+        });
+    }
+
+    let min_header_size = Header::unpadded_size(seg_count);
+    if meta.header_size < min_header_size {
+        findings.push(Finding::HeaderSizeMismatch {
+            declared: meta.header_size,
+            minimum: min_header_size,
+        });
+    }
+
+    let actual_data_size = header.padded_data_size();
+    if meta.data_size != actual_data_size {
+        findings.push(Finding::DataSizeMismatch {
+            declared: meta.data_size,
+            actual: actual_data_size,
+        });
+    }
+
+    // A sum too large for a u64 can't match any file size anyway.
+    let declared_file_size = meta.header_size.saturating_add(meta.data_size);
+    if declared_file_size != file_size {
+        findings.push(Finding::FileSizeMismatch {
+            declared: declared_file_size,
+            actual: file_size,
+        });
+    }
+
+    // Segment data should be laid out after the header, in table order, with no overlap.
+    let mut prev_end = meta.header_size;
+    for (i, entry) in header.seg_table.iter().enumerate() {
+        let i = i as u64;
+        let end = entry.offset.saturating_add(entry.size);
+
+        if entry.offset < prev_end {
+            findings.push(Finding::SegmentOverlap(i));
+        }
+        if end > file_size {
+            findings.push(Finding::SegmentOutOfBounds(i));
+        }
+
+        prev_end = prev_end.max(end);
+    }
+
+    // Every segment should be covered by exactly one digest entry, and every digest entry should
+    // refer to an existing segment.
+    for i in 0..(header.seg_table.len() as u64) {
+        match header
+            .digest_table
+            .iter()
+            .filter(|x| x.seg_index == i)
+            .count()
+        {
+            0 => findings.push(Finding::MissingSignature(i)),
+            1 => (),
+            _ => findings.push(Finding::DuplicateSignature(i)),
+        }
+    }
+    for entry in header.digest_table.iter() {
+        if entry.seg_index >= header.seg_table.len() as u64 {
+            findings.push(Finding::OrphanSignature(entry.seg_index));
+        }
+    }
+
+    for (i, entry) in header.seg_table.iter().enumerate() {
+        if let Some(first) = header.seg_table[..i].iter().position(|x| x.id == entry.id) {
+            findings.push(Finding::DuplicateSegmentId {
+                id: entry.id,
+                first: first as u64,
+                second: i as u64,
+            });
+        }
+    }
+
+    // The spkg segments only make sense alongside each other and the packages they describe.
+    for (i, entry) in header.seg_table.iter().enumerate() {
+        for missing in required_companions(entry.id) {
+            if !header.seg_table.iter().any(|x| x.id == *missing) {
+                findings.push(Finding::MissingCompanion {
+                    index: i as u64,
+                    missing: *missing,
+                });
+            }
+        }
+    }
+
+    if let Some(i) = header
+        .seg_table
+        .windows(2)
+        .position(|x| x[0].id.canonical_rank() > x[1].id.canonical_rank())
+    {
+        findings.push(Finding::NonCanonicalOrder((i as u64) + 1));
+    }
+
+    findings
+}
+
+// The segments that official packages always include alongside a segment with a specific ID.
+fn required_companions(id: SegmentId) -> &'static [SegmentId] {
+    match id.0 {
+        // spkg_hdr.tar holds the headers of the packages in update_files.tar, and is installed by
+        // ps3swu2.self.
+        0x501 => &[SegmentId(0x300), SegmentId(0x601)],
+        0x601 => &[SegmentId(0x501)],
+        _ => &[],
+    }
+}
+
+/// A structural inconsistency reported by [`validate`] or [`Pup::validate`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Finding {
+    /// The segment count disagrees with the number of decodable table entries.
+    SegmentCountMismatch {
+        /// The segment count declared in the header.
+        declared: u64,
+        /// The number of decodable segment table entries.
+        seg_entries: u64,
+        /// The number of decodable digest table entries.
+        sig_entries: u64,
+    },
+    /// The declared header size is too small to contain the header's own tables.
+    HeaderSizeMismatch {
+        /// The header size declared in the header.
+        declared: u64,
+        /// The smallest header size that fits the metadata, tables, and header signature.
+        minimum: u64,
+    },
+    /// The declared data size disagrees with the sum of all segment sizes, plus any padding
+    /// between segments.
+    DataSizeMismatch {
+        /// The data size declared in the header.
+        declared: u64,
+        /// The sum of all segment sizes, plus any padding between segments.
+        actual: u64,
+    },
+    /// The declared header and data sizes disagree with the actual file size.
+    FileSizeMismatch {
+        /// The sum of the declared header and data sizes.
+        declared: u64,
+        /// The actual size of the file.
+        actual: u64,
+    },
+    /// The segment at a specific index starts before the end of the header or previous segment.
+    SegmentOverlap(u64),
+    /// The segment at a specific index extends past the end of the file.
+    SegmentOutOfBounds(u64),
+    /// A segment at a specific index has no corresponding signature.
+    MissingSignature(u64),
+    /// A segment at a specific index has more than one corresponding signature.
+    DuplicateSignature(u64),
+    /// A signature refers to a segment index that doesn't exist.
+    OrphanSignature(u64),
+    /// Two segments share the same ID.
+    DuplicateSegmentId {
+        /// The shared segment ID.
+        id: SegmentId,
+        /// The index of the first segment with this ID.
+        first: u64,
+        /// The index of the later segment with this ID.
+        second: u64,
+    },
+    /// A segment requires another segment that is missing (e.g., `spkg_hdr.tar` without
+    /// `ps3swu2.self`).
+    MissingCompanion {
+        /// The index of the segment.
+        index: u64,
+        /// The ID of the missing segment.
+        missing: SegmentId,
+    },
+    /// The segment at a specific index is out of the order used by official firmware.
+    ///
+    /// Only the first such segment is reported. See [`Pup::sort_canonical`].
+    NonCanonicalOrder(u64),
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::SegmentCountMismatch {
+                declared,
+                seg_entries,
+                sig_entries,
+            } => write!(
+                f,
+                "segment count is {}, but there are {} segment entries and {} signature entries",
+                declared, seg_entries, sig_entries
+            ),
+            Self::HeaderSizeMismatch { declared, minimum } => write!(
+                f,
+                "header size is {}, but the header requires at least {}",
+                declared, minimum
+            ),
+            Self::DataSizeMismatch { declared, actual } => write!(
+                f,
+                "data size is {}, but segments total {}",
+                declared, actual
+            ),
+            Self::FileSizeMismatch { declared, actual } => write!(
+                f,
+                "header and data sizes total {}, but the file is {}",
+                declared, actual
+            ),
+            Self::SegmentOverlap(i) => write!(f, "segment {} overlaps preceding data", i),
+            Self::SegmentOutOfBounds(i) => write!(f, "segment {} extends past end of file", i),
+            Self::MissingSignature(i) => write!(f, "signature for segment {} is missing", i),
+            Self::DuplicateSignature(i) => {
+                write!(f, "segment {} has more than one signature", i)
+            }
+            Self::OrphanSignature(i) => {
+                write!(f, "signature refers to nonexistent segment {}", i)
+            }
+            Self::DuplicateSegmentId { id, first, second } => {
+                write!(f, "segments {} and {} share ID {}", first, second, id)
+            }
+            Self::MissingCompanion { index, missing } => {
+                write!(f, "segment {} requires missing segment {}", index, missing)
+            }
+            Self::NonCanonicalOrder(i) => {
+                write!(f, "segment {} is out of canonical order", i)
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// A system software version, such as `4.88`.
+///
+/// Versions are ordered numerically rather than as text, so `4.9` (i.e., `4.90`) is newer than
+/// `4.88`. Sony writes versions as `X.YZ`, optionally followed by a build number: update lists
+/// pad it to two more digits (e.g., `4.9100`), while other sources separate it with `.` or `-`
+/// (e.g., `4.91.1`). A missing build number is build 0.
+///
+/// # Examples
+///
+/// ```
+/// use pupper::FirmwareVersion;
+///
+/// let installed: FirmwareVersion = "4.88".parse().unwrap();
+/// let update: FirmwareVersion = "4.9100".parse().unwrap();
+///
+/// assert!(update.is_newer_than(&installed));
+/// assert_eq!("4.91", update.to_string());
+/// assert_eq!(update, "4.91".parse().unwrap());
+/// assert!("4.91.1".parse::<FirmwareVersion>().unwrap().is_newer_than(&update));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FirmwareVersion {
+    /// The `X` of `X.YZ`.
+    pub major: u32,
+    /// The `YZ` of `X.YZ`, in hundredths.
+    pub minor: u32,
+    /// The build number following `X.YZ`.
+    pub build: u32,
+}
+
+impl FirmwareVersion {
+    /// Creates a [`FirmwareVersion`] of build 0.
+    #[must_use]
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self {
+            major,
+            minor,
+            build: 0,
+        }
+    }
+
+    /// Whether this version is newer than `other`, e.g., whether a PUP of this version is an
+    /// upgrade of a console running `other`.
+    #[must_use]
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self > other
+    }
+}
+
+impl Display for FirmwareVersion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{:02}", self.major, self.minor)?;
+        if self.build != 0 {
+            write!(f, ".{}", self.build)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for FirmwareVersion {
+    type Err = String;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let err = || format!("'{}' is not a version of the form X.YZ", version);
+        let number = |x: &str| {
+            Some(x)
+                .filter(|x| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit()))
+                .and_then(|x| x.parse::<u32>().ok())
+        };
+
+        let (major, rest) = version.trim().split_once('.').ok_or_else(err)?;
+        let minor_len = rest.bytes().take_while(u8::is_ascii_digit).count().min(2);
+        let (minor, suffix) = rest.split_at(minor_len);
+
+        let build = match suffix.strip_prefix(|x| x == '.' || x == '-') {
+            Some(build) => number(build),
+            // Update lists pad versions with a two-digit build (e.g., '4.9100').
+            None if suffix.len() == 2 => number(suffix),
+            None if suffix.is_empty() => Some(0),
+            None => None,
+        };
+
+        Ok(Self {
+            major: number(major).ok_or_else(err)?,
+            // A single digit is tenths, as in '4.9'.
+            minor: number(minor).ok_or_else(err)? * if minor_len == 1 { 10 } else { 1 },
+            build: build.ok_or_else(err)?,
+        })
+    }
+}
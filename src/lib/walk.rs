@@ -0,0 +1,234 @@
+use crate::{
+    header::{self, Header},
+    Digest, FixedSize, ParseError, Pup, Segment, SegmentId, SignatureKind,
+};
+
+use std::{convert::TryFrom, ops::Range};
+
+impl Pup {
+    /// Walks the structure this [`Pup`] would have if it were serialized, calling back into the
+    /// given visitor.
+    ///
+    /// The visitor sees, in order: the metadata, each segment table entry, each digest table
+    /// entry, the header signature, and finally each segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pupper::{Pup, PupVisitor, Segment, SegmentId};
+    ///
+    /// #[derive(Default)]
+    /// struct Ranges(Vec<std::ops::Range<u64>>);
+    ///
+    /// impl PupVisitor for Ranges {
+    ///     fn visit_segment(&mut self, _: u64, _: &Segment, range: std::ops::Range<u64>) {
+    ///         self.0.push(range);
+    ///     }
+    /// }
+    ///
+    /// let mut pup = Pup::default();
+    /// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 4]));
+    /// pup.segments.push(Segment::new(SegmentId(0x101), vec![0; 8]));
+    ///
+    /// let mut ranges = Ranges::default();
+    /// pup.walk(&mut ranges);
+    ///
+    /// assert_eq!(4, ranges.0[0].end - ranges.0[0].start);
+    /// assert_eq!(ranges.0[0].end, ranges.0[1].start);
+    /// ```
+    pub fn walk<V: PupVisitor + ?Sized>(&self, visitor: &mut V) {
+        let header = Header::from(self);
+        RawHeader::from(&header).walk(visitor);
+
+        for (i, (seg, entry)) in self
+            .segments
+            .iter()
+            .zip(header.seg_table.iter())
+            .enumerate()
+        {
+            // [may_panic(Add)]
+            visitor.visit_segment(i as u64, seg, entry.offset..(entry.offset + entry.size));
+        }
+    }
+}
+
+/// A PUP header exactly as serialized.
+///
+/// Unlike [`Pup`], this can be read from just the start of a file, which is handy when the rest
+/// is expensive to get (e.g., over a network).
+///
+/// # Examples
+///
+/// ```
+/// use pupper::{FixedSize as _, HeaderMetadata, Pup, RawHeader, Segment, SegmentId};
+/// use std::convert::TryFrom as _;
+///
+/// let mut pup = Pup::default();
+/// pup.segments.push(Segment::new(SegmentId(0x100), vec![0; 0x100]));
+/// let data = Vec::<u8>::from(&pup);
+///
+/// // The metadata tell us how much more we need to read.
+/// let meta = HeaderMetadata::try_from(&data[..HeaderMetadata::SIZE]).unwrap();
+/// let header = RawHeader::try_from(&data[..(meta.header_size as usize)]).unwrap();
+///
+/// assert_eq!(0x100, header.segment_entries[0].size);
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RawHeader {
+    /// The metadata.
+    pub metadata: HeaderMetadata,
+    /// The segment table.
+    pub segment_entries: Vec<SegmentEntry>,
+    /// The digest table.
+    pub digest_entries: Vec<DigestEntry>,
+    /// The signature of everything above.
+    pub header_signature: Digest,
+}
+
+impl RawHeader {
+    /// Like [`Pup::walk`], but without visiting any segments.
+    pub fn walk<V: PupVisitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_metadata(&self.metadata);
+
+        for (i, entry) in self.segment_entries.iter().enumerate() {
+            visitor.visit_segment_entry(i as u64, entry);
+        }
+
+        for (i, entry) in self.digest_entries.iter().enumerate() {
+            visitor.visit_digest_entry(i as u64, entry);
+        }
+
+        visitor.visit_header_signature(&self.header_signature);
+    }
+}
+
+impl TryFrom<&[u8]> for RawHeader {
+    type Error = ParseError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Header::try_from(data).map(|x| Self::from(&x))
+    }
+}
+
+impl From<&Header> for RawHeader {
+    fn from(header: &Header) -> Self {
+        Self {
+            metadata: (&header.meta).into(),
+            segment_entries: header.seg_table.iter().map(Into::into).collect(),
+            digest_entries: header.digest_table.iter().map(Into::into).collect(),
+            header_signature: *header.header_sig(),
+        }
+    }
+}
+
+/// Callbacks for [`Pup::walk`].
+///
+/// Every method does nothing by default, so implementors need only override the ones they are
+/// interested in.
+pub trait PupVisitor {
+    /// Visits the header metadata.
+    fn visit_metadata(&mut self, _meta: &HeaderMetadata) {}
+
+    /// Visits the segment table entry at a specific index.
+    fn visit_segment_entry(&mut self, _index: u64, _entry: &SegmentEntry) {}
+
+    /// Visits the digest table entry at a specific index.
+    fn visit_digest_entry(&mut self, _index: u64, _entry: &DigestEntry) {}
+
+    /// Visits the header signature.
+    fn visit_header_signature(&mut self, _sig: &Digest) {}
+
+    /// Visits the segment at a specific index, along with the range of file offsets its data
+    /// occupies.
+    fn visit_segment(&mut self, _index: u64, _seg: &Segment, _range: Range<u64>) {}
+}
+
+/// The metadata at the start of a PUP header.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct HeaderMetadata {
+    /// The package version.
+    pub package_version: u64,
+    /// The image version.
+    pub image_version: u64,
+    /// The number of segments.
+    pub segment_count: u64,
+    /// The size of the header, including padding, in bytes.
+    pub header_size: u64,
+    /// The combined size of all segment data, in bytes.
+    pub data_size: u64,
+}
+
+impl TryFrom<&[u8]> for HeaderMetadata {
+    type Error = ParseError;
+
+    /// Reads the metadata from the start of a PUP.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data = data
+            .get(..Self::SIZE)
+            .ok_or(Self::Error::Undersized)
+            .map(|x| <&[u8; Self::SIZE]>::try_from(x).unwrap())?;
+
+        let meta = header::meta::Metadata::try_from(data)?;
+        meta.check_pkg_version()?;
+
+        Ok(Self::from(&meta))
+    }
+}
+
+impl FixedSize for HeaderMetadata {
+    const SIZE: usize = header::meta::Metadata::SIZE;
+}
+
+impl From<&header::meta::Metadata> for HeaderMetadata {
+    fn from(meta: &header::meta::Metadata) -> Self {
+        Self {
+            package_version: meta.pkg_version,
+            image_version: meta.img_version,
+            segment_count: meta.seg_count,
+            header_size: meta.header_size,
+            data_size: meta.data_size,
+        }
+    }
+}
+
+/// An entry in the segment table of a PUP header.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SegmentEntry {
+    /// The ID of the segment.
+    pub id: SegmentId,
+    /// The file offset of the segment data.
+    pub offset: u64,
+    /// The size of the segment data, in bytes.
+    pub size: u64,
+    /// The algorithm used to sign the segment.
+    pub signature_kind: SignatureKind,
+}
+
+impl From<&header::seg::Entry> for SegmentEntry {
+    fn from(entry: &header::seg::Entry) -> Self {
+        Self {
+            id: entry.id,
+            offset: entry.offset,
+            size: entry.size,
+            signature_kind: entry.sig_kind,
+        }
+    }
+}
+
+/// An entry in the digest table of a PUP header.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DigestEntry {
+    /// The index of the segment this entry signs.
+    pub segment_index: u64,
+    /// The signature of the segment.
+    pub digest: Digest,
+}
+
+impl From<&header::digest::Entry> for DigestEntry {
+    fn from(entry: &header::digest::Entry) -> Self {
+        Self {
+            segment_index: entry.seg_index,
+            digest: entry.digest,
+        }
+    }
+}
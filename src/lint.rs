@@ -0,0 +1,253 @@
+use pupper::{Digest, Finding, FirmwareDb, ParseOptions, Pup, SegmentId, SegmentNameMap};
+
+use serde_json::json;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+/// The rules, by ID, with their default severities.
+static RULES: [(&str, Severity); 7] = [
+    ("missing-version", Severity::Error),
+    ("non-canonical-order", Severity::Warning),
+    ("zero-digest", Severity::Error),
+    ("unknown-id", Severity::Warning),
+    // Serialization packs segment data back-to-back, so this one is opt-in.
+    ("misaligned-data", Severity::Off),
+    ("image-version-mismatch", Severity::Warning),
+    // Only checked against a firmware database.
+    ("official-layout", Severity::Warning),
+];
+
+// Segment data are expected to start on a boundary of this many bytes, unless '--alignment' says
+// otherwise.
+const ALIGNMENT: u64 = 0x10;
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let mut severities: Vec<(&str, Severity)> = RULES.to_vec();
+    for (rule, severity) in config.lint.iter() {
+        set_severity(&mut severities, rule, severity)
+            .map_err(|err| format!("invalid configuration: {}", err))?;
+    }
+    for value in args.values_of("rule").into_iter().flatten() {
+        let (rule, severity) = value
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' isn't of the form <rule>=<severity>", value))?;
+        set_severity(&mut severities, rule, severity)?;
+    }
+
+    let db = match args
+        .value_of_os("db")
+        .map(Path::new)
+        .or(config.db.as_deref())
+    {
+        Some(db_path) => Some(super::check_official::read_db_from_path(db_path)?),
+        None => None,
+    };
+
+    let alignment = super::alignment_option(args, config)?.unwrap_or(ALIGNMENT);
+
+    let data = super::read_pup_data(path, |_| true)?;
+    let pup = super::parse_pup(
+        &data,
+        path,
+        &mut ParseOptions {
+            record_provenance: true,
+            ..Default::default()
+        },
+    )?;
+
+    let problems: Vec<(Severity, Problem)> = check(&pup, names, db.as_ref(), alignment)
+        .into_iter()
+        .filter_map(|problem| {
+            let (_, severity) = severities.iter().find(|(x, _)| *x == problem.rule)?;
+
+            Some((*severity, problem)).filter(|(x, _)| *x != Severity::Off)
+        })
+        .collect();
+
+    if args.is_present("json") {
+        let json: Vec<_> = problems
+            .iter()
+            .map(|(severity, problem)| {
+                json!({
+                    "rule": problem.rule,
+                    "severity": severity.to_string(),
+                    "segment": problem.segment,
+                    "message": problem.message,
+                })
+            })
+            .collect();
+
+        println!("{:#}", serde_json::Value::from(json));
+    } else {
+        for (severity, problem) in problems.iter() {
+            let color = match severity {
+                Severity::Error => super::color::RED,
+                Severity::Warning => super::color::YELLOW,
+                _ => super::color::CYAN,
+            };
+            println!(
+                "{}[{}]: {}",
+                super::color::paint(severity, color),
+                problem.rule,
+                problem.message
+            );
+        }
+    }
+
+    let errors = problems
+        .iter()
+        .filter(|(x, _)| *x == Severity::Error)
+        .count();
+    if errors == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} lint error(s)", errors).into())
+    }
+}
+
+fn set_severity(
+    severities: &mut [(&str, Severity)],
+    rule: &str,
+    severity: &str,
+) -> Result<(), String> {
+    let entry = severities
+        .iter_mut()
+        .find(|(x, _)| *x == rule)
+        .ok_or_else(|| format!("'{}' is not a lint rule", rule))?;
+    entry.1 = severity.parse()?;
+
+    Ok(())
+}
+
+/// A violation of a lint rule.
+struct Problem {
+    rule: &'static str,
+    segment: Option<u64>,
+    message: String,
+}
+
+fn check(
+    pup: &Pup,
+    names: &SegmentNameMap,
+    db: Option<&FirmwareDb>,
+    alignment: u64,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut report = |rule, segment, message| {
+        problems.push(Problem {
+            rule,
+            segment,
+            message,
+        })
+    };
+
+    if !pup.segments.iter().any(|x| x.id == SegmentId(0x100)) {
+        report("missing-version", None, "version.txt is missing".into());
+    }
+
+    for finding in pup.validate() {
+        if let Finding::NonCanonicalOrder(i) = finding {
+            report("non-canonical-order", Some(i), finding.to_string());
+        }
+    }
+
+    for (i, seg) in pup.segments.iter().enumerate() {
+        let i = i as u64;
+
+        if *seg.signature() == Digest::default() {
+            report(
+                "zero-digest",
+                Some(i),
+                format!("segment {} has an all-zero signature", i),
+            );
+        }
+
+        if names.name_of(seg.id).is_none() {
+            report(
+                "unknown-id",
+                Some(i),
+                format!("segment {} has unknown ID {:#x}", i, seg.id.0),
+            );
+        }
+
+        if let Some(offset) = seg.provenance().map(|x| x.offset) {
+            if offset % alignment != 0 {
+                report(
+                    "misaligned-data",
+                    Some(i),
+                    format!(
+                        "data of segment {} start at offset {:#x}, which isn't {}-byte aligned",
+                        i, offset, alignment
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some(mismatch) = pup.check_image_version(db) {
+        report("image-version-mismatch", None, mismatch.to_string());
+    }
+
+    let reference = db
+        .zip(pup.build_info().firmware_version())
+        .and_then(|(db, version)| db.reference_for(version));
+    if let Some(release) = reference {
+        for deviation in release.compare_layout(pup) {
+            report(
+                "official-layout",
+                None,
+                format!("{} (compared to {})", deviation, release),
+            );
+        }
+    }
+
+    problems
+}
+
+/// How much a violation of a lint rule matters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Severity {
+    /// Not reported.
+    Off,
+    Info,
+    Warning,
+    /// Reported, and fails the command.
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(format!(
+                "'{}' is not a severity (off, info, warning, or error)",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use pupper::{RawHeader, SegmentNameMap};
+
+use std::{cmp::Reverse, convert::TryFrom as _};
+
+// Prints one line per segment: its index, ID, size in bytes, and file name.
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    // Only the header is needed.
+    let data = super::read_pup_data(path, |_| false)?;
+    let header = RawHeader::try_from(data.as_slice()).map_err(|err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    })?;
+
+    let mut entries: Vec<_> = header.segment_entries.iter().enumerate().collect();
+    match args.value_of("sort_by") {
+        Some("id") => entries.sort_by_key(|(_, x)| x.id.0),
+        // Largest first, as 'stat' lists them.
+        Some("size") => entries.sort_by_key(|(_, x)| Reverse(x.size)),
+        Some("name") => entries.sort_by_key(|(_, x)| super::sort::name_sort_key(x.id, names)),
+        _ => {}
+    }
+    if args.is_present("reverse") {
+        entries.reverse();
+    }
+
+    for (i, entry) in entries {
+        println!(
+            "{:>3} {:#06x} {:>12} {}",
+            i,
+            entry.id.0,
+            entry.size,
+            names.name_of(entry.id).unwrap_or("?")
+        );
+    }
+
+    Ok(())
+}
@@ -1,75 +1,669 @@
 #[macro_use]
 extern crate clap;
 
+mod apply;
+mod audit;
+mod carve;
+mod cat;
+mod check_official;
+mod check_update_list;
+mod checksums;
+mod color;
+#[cfg(feature = "compress")]
+mod compress;
+mod config;
 mod create;
+mod diff;
+mod doctor;
+mod error;
+mod explain;
+mod export_rpcs3;
+#[cfg(feature = "net")]
+mod fetch;
+mod from_json;
+mod glob;
+mod header;
+mod id;
+mod info;
+mod lint;
+mod list;
+mod map;
+#[cfg(feature = "net")]
+mod net;
+mod pack;
+mod prepare_usb;
 mod print;
+mod repair;
+mod schema;
+mod search;
 mod seg;
+mod shell;
+mod sort;
+mod stat;
+mod stream;
+mod tree;
+mod unpack;
+mod validate;
+mod verify;
 
-use pupper::Pup;
+use config::Config;
+use error::Error;
+use pupper::{ParseOptions, Pup, SegmentId, SegmentNameMap};
 
-use std::{convert::TryInto as _, fs, path::Path};
+use std::{
+    convert::TryFrom,
+    env, fs,
+    io::{self, Write as _},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
 
 fn main() {
-    let args = clap::clap_app!(pupper =>
+    let app = clap::clap_app!(pupper =>
         (version: clap::crate_version!())
         (about: clap::crate_description!())
-        (@arg pup: -f --file +required +takes_value "PUP file path")
+        (@arg pup: -f --file +takes_value +multiple number_of_values(1)
+            "PUP file path or glob pattern (may be repeated)")
+        (@arg config: --config +takes_value
+            "Configuration file (default: ~/.config/pupper/config.toml)")
+        (@arg names: --names +takes_value "Segment name map (TOML) extending the built-in names")
+        (@arg error_format: --error_format +takes_value possible_value[human json]
+            "Error output format (default: human)")
+        (@arg audit_log: --audit_log +takes_value
+            "Appends a JSON line to this file for every modification")
+        (@arg threads: -j --threads +takes_value
+            "Maximum number of threads for parallel work (default: one per CPU)")
+        (@arg max_memory: --max_memory +takes_value
+            "Memory budget for PUP data (e.g., 64M); larger PUPs are streamed or refused")
+        (@arg color: --color +takes_value possible_value[auto always never]
+            "Colors output (default: auto, i.e., only on a terminal)")
+        (@subcommand apply =>
+            (about: "Applies a patch made with 'diff --patch'")
+            (@arg patch: +required "Patch file path")
+            (@arg output: -o --output +takes_value "Output file path (default: the PUP itself)")
+        )
+        (@subcommand carve =>
+            (about: "Extracts every PUP embedded in a larger file, such as a flash dump")
+            (@arg dump: +required "File to search")
+            (@arg dir: -o --output +required +takes_value "Output directory path")
+        )
+        (@subcommand cat =>
+            (about: "Prints the contents of a segment")
+            (@arg segment: +required "Segment file name or ID")
+        )
+        (@subcommand check_official =>
+            (name: "check-official")
+            (about: "Compares a PUP against a database of official releases")
+            (@arg db: -d --db +takes_value
+                "Firmware database (TOML) (default: from the configuration)")
+        )
+        (@subcommand check_update_list =>
+            (name: "check-updatelist")
+            (about: "Checks a PUP against the entry of its version in an update list")
+            (@arg list: -l --list +required +takes_value
+                "Update list path (or URL, with the 'net' feature)")
+        )
+        (@subcommand checksums =>
+            (about: "Prints a manifest of segment hashes in the format of 'sha256sum'")
+            (@arg algo: --algo +takes_value possible_value[sha1 sha256]
+                "Hash algorithm (default: sha256)")
+            (@arg verify: --verify +takes_value
+                "Reports the segments that deviate from a manifest instead of printing one")
+        )
         (@subcommand create =>
             (about: "Creates an empty PUP")
             (@arg img_version: -g --image_version +takes_value "PUP image version (default: 0)")
+            (@arg template: -t --template +takes_value possible_value[standard]
+                "Pre-populates the well-known segments, empty, in canonical order")
+            (@arg stubs: --stubs +takes_value requires[template]
+                "Directory of files named after segments to use as their data")
+        )
+        (@subcommand diff =>
+            (about: "Lists the segments that differ from another PUP")
+            (@arg other: +required "PUP file path to compare against")
+            (@arg format: --format +takes_value possible_values(&["human", "json-patch"])
+                "Output format (default: human)")
+            (@arg patch: --patch +takes_value "Also writes a patch file for 'apply'")
+        )
+        (@subcommand doctor =>
+            (about: "Diagnoses common kinds of corruption and suggests fixes")
+        )
+        (@subcommand explain =>
+            (about: "Describes what lives at a file offset of a PUP")
+            (@arg offset: +required "File offset (e.g., 0x2_0040)")
+        )
+        (@subcommand export_rpcs3 =>
+            (name: "export-rpcs3")
+            (about: "Exports the dev_flash files of a PUP in the layout RPCS3 expects")
+            (@arg dir: -o --output +required +takes_value "Output directory path")
+            (@arg decryptor: --decryptor +required +takes_value
+                "Command that decrypts a package from stdin to stdout, given its name")
+        )
+        (@subcommand from_json =>
+            (name: "from-json")
+            (about: "Creates a PUP from the output of 'print --embed_data'")
+            (@arg json: +required "JSON file path")
+        )
+        (@subcommand header =>
+            (about: "Prints the raw fields of a PUP header")
+            (@arg raw: --raw "Writes the header bytes, padding included, instead")
+            (@arg output: -o --output +takes_value requires[raw]
+                "Output file path for '--raw' (default: stdout)")
+        )
+        (@subcommand id =>
+            (about: "Translates between segment IDs and file names")
+            (@arg segment: required_unless[list] "Segment file name or ID")
+            (@arg list: -l --list conflicts_with[segment] "Lists all known IDs and file names")
+        )
+        (@subcommand info =>
+            (about: "Prints build metadata of a PUP")
+        )
+        (@subcommand lint =>
+            (about: "Checks a PUP against conventions of official firmware")
+            (@arg rule: -r --rule +takes_value +multiple number_of_values(1)
+                "Sets the severity (off, info, warning, or error) of a rule, as <rule>=<severity>")
+            (@arg db: -d --db +takes_value
+                "Firmware database (TOML) to check the image version against")
+            (@arg json: --json "Prints the report as JSON")
+            (@arg alignment: -a --alignment +takes_value
+                "Boundary 'misaligned-data' expects segment data on (default: 0x10)")
+        )
+        (@subcommand list =>
+            (about: "Lists the index, ID, size, and file name of every segment of a PUP")
+            (@arg sort_by: -s --sort_by +takes_value possible_value[id size name]
+                "Sort key, with the largest segments first by size (default: PUP order)")
+            (@arg reverse: -r --reverse "Reverses the order")
+        )
+        (@subcommand map =>
+            (about: "Lists every structure of a PUP and the bytes it occupies")
+            (@arg json: --json "Prints the map as JSON")
+            (@arg format: --format +takes_value possible_value[imhex kaitai bt] conflicts_with[json]
+                "Prints an ImHex pattern, Kaitai Struct spec, or 010 Editor template instead")
+        )
+        (@subcommand prepare_usb =>
+            (name: "prepare-usb")
+            (about: "Copies a PUP onto a USB drive for installation on a PS3")
+            (@arg root: +required "Root directory of the USB drive")
+            (@arg pup: --pup +required +takes_value "PUP file path")
+        )
+        (@subcommand pack =>
+            (about: "Creates a PUP from a directory of segment files, like one written by 'unpack'")
+            (@arg dir: +required "Input directory")
+            (@arg img_version: -g --image_version +takes_value
+                "PUP image version (default: from pup.toml, or 0)")
+            (@arg skip_unknown: --skip_unknown
+                "Skips files that aren't named after a segment or listed in pup.toml")
+            (@arg alignment: -a --alignment +takes_value
+                "Aligns the data of segments that pup.toml doesn't align (default: 1)")
         )
         (@subcommand print =>
             (about: "Prints a textual representation of a PUP")
+            (@arg fingerprints: --fingerprints "Includes SHA-1 fingerprints of segment data")
+            (@arg embed_data: --embed_data "Includes base64-encoded segment data")
+            (@arg all: --all "Prints every PUP in a file of concatenated PUPs")
+            (@arg fields: --fields +takes_value
+                "Prints only these segment fields, in order (e.g., id,name,size,digest)")
+            (@arg only: --only +takes_value possible_value[segments meta]
+                "Prints only the segments or only the PUP metadata")
+        )
+        (@subcommand repair =>
+            (about: "Reconstructs a damaged PUP from whatever segments can be found")
+            (@arg output: +required "Repaired PUP file path")
+        )
+        (@subcommand schema =>
+            (about: "Prints the JSON Schema of a machine-readable output")
+            (@arg command: +required possible_value[diff map print errors verify]
+                "Output to describe ('errors' is '--error_format json')")
+        )
+        (@subcommand search =>
+            (about: "Searches the data of all segments for a byte pattern")
+            (@group pattern =>
+                (@attributes +required)
+                (@arg hex: --hex +takes_value "Pattern as hexadecimal bytes")
+                (@arg text: --text +takes_value "Pattern as text")
+            )
         )
         (@subcommand segment =>
             (about: "Segment-related subcommands")
-            (@arg index: -n --index +takes_value "Segment index (default: 0)")
+            (@arg index: -n --index +takes_value
+                "Segment index (default: 0, or for 'insert', the canonical position)")
+            (@subcommand digest =>
+                (about: "Compares the stored signature of a segment with digests of its data")
+                (@arg key: -k --key +takes_value
+                    "HMAC key file path (default: from the configuration)")
+            )
             (@subcommand extract =>
                 (about: "Extracts a segment from a PUP")
-                (@arg seg: -s --segment +required +takes_value "Segment file path")
+                (@group target =>
+                    (@attributes +required)
+                    (@arg seg: -s --segment +takes_value "Segment file path")
+                    (@arg to_stdout: --stdout "Writes segment data to stdout")
+                )
+                (@arg max_part_size: --max_part_size +takes_value requires[seg]
+                    "Splits the segment into numbered parts of at most this size (e.g., 4G)")
+                (@arg force: --force requires[to_stdout]
+                    "Writes to stdout even if it's a terminal")
             )
             (@subcommand insert =>
                 (about: "Inserts a segment into a PUP")
-                (@arg seg: -s --segment +required +takes_value "Segment file path")
-                (@arg id: -x --id +takes_value "Segment ID (default: 0)")
+                (@group source =>
+                    (@attributes +required)
+                    (@arg seg: -s --segment +takes_value "Segment file path")
+                    (@arg from_stdin: --from_stdin requires[id] "Reads segment data from stdin")
+                )
+                (@arg id: -x --id +takes_value "Segment ID, e.g., 0x200 (default: 0)")
+                (@arg join: --join requires[seg]
+                    "Reassembles the segment from parts written by 'extract --max_part_size'")
             )
             (@subcommand remove =>
                 (about: "Removes a segment from a PUP")
             )
+            (@subcommand strings =>
+                (about: "Prints the printable ASCII and UTF-16 strings in a segment")
+                (@arg min_len: --min_len +takes_value "Minimum string length (default: 4)")
+            )
         )
-    )
-    .get_matches();
+        (@subcommand shell =>
+            (about: "Edits a PUP interactively, parsing and serializing it only once")
+        )
+        (@subcommand sort =>
+            (about: "Sorts the segments of a PUP (default: canonical order)")
+            (@arg by: -b --by +takes_value possible_value[id size name] "Sort key")
+        )
+        (@subcommand stat =>
+            (about: "Prints a size summary of a PUP")
+            (@arg entropy: --entropy "Includes the entropy of each segment")
+        )
+        (@subcommand tree =>
+            (about: "Prints the nested contents of a PUP as a tree")
+        )
+        (@subcommand unpack =>
+            (about: "Extracts every segment of a PUP into a directory, with a pup.toml for 'pack'")
+            (@arg dir: +required "Output directory")
+            (@arg threads: -j --threads +takes_value
+                "Number of segments to write at once (default: one per CPU)")
+        )
+        (@subcommand validate =>
+            (about: "Checks the structure of a PUP")
+        )
+        (@subcommand verify =>
+            (about: "Verifies the signatures of a PUP")
+            (@arg fingerprints_only: --fingerprints_only requires[manifest]
+                "Checks fingerprints against the manifest instead of signatures")
+            (@arg manifest: -m --manifest +takes_value
+                "Fingerprint manifest (output of 'print --fingerprints', optionally with 'sha256' \
+                 entries)")
+            (@arg key: -k --key +takes_value conflicts_with[fingerprints_only]
+                "HMAC key file path (default: from the configuration, or the built-in key)")
+            (@arg json: --json conflicts_with[fingerprints_only]
+                "Prints the validity of every signature as JSON")
+        )
+    );
+    #[cfg(feature = "net")]
+    let app = app.subcommand(fetch::subcommand());
+    let args = app.get_matches();
 
-    let path = std::path::Path::new(args.value_of("pup").unwrap());
+    let config = Config::read(args.value_of("config"));
 
-    let result = match args.subcommand() {
-        ("create", Some(args)) => create::execute(path, args),
-        ("print", Some(_)) => print::execute(path),
-        ("segment", Some(args)) => seg::execute(path, args),
-        _ => Ok(()),
-    };
+    // Command-line options take precedence over the configuration.
+    let error_format = args
+        .value_of("error_format")
+        .or_else(|| config.as_ref().ok().and_then(|x| x.error_format.as_deref()));
+    let json_errors = error_format == Some("json");
+
+    let result = config.and_then(|mut config| {
+        if let Some(threads) = args.value_of("threads") {
+            config.threads = Some(parse_threads(threads)?);
+        }
+        if let Some(max_memory) = args.value_of("max_memory") {
+            config.max_memory = Some(seg::parse_size_option(max_memory)?);
+        }
+        if let Some(max_memory) = config.max_memory {
+            stream::set_budget(max_memory);
+        }
+        color::set_choice(args.value_of("color").or(config.color.as_deref()));
+        let config = &config;
+
+        let names = read_names_from_path(
+            args.value_of_os("names")
+                .map(Path::new)
+                .or(config.names.as_deref()),
+        )?;
+
+        // These subcommands don't operate on '-f'.
+        match args.subcommand() {
+            #[cfg(feature = "net")]
+            ("fetch", Some(args)) => return fetch::execute(args, config),
+            ("carve", Some(args)) => return carve::execute(args),
+            ("prepare-usb", Some(args)) => return prepare_usb::execute(args),
+            ("id", Some(args)) => return id::execute(args, &names),
+            ("schema", Some(args)) => return schema::execute(args),
+            _ => {}
+        }
+
+        // A batch runs each file in a process of its own (see execute_batch()).
+        if let Some(path) = env::var_os(BATCH_FILE_VAR) {
+            return execute(Path::new(&path), &args, &names, config);
+        }
+
+        let paths = args
+            .values_of("pup")
+            .ok_or("a PUP file path (-f) is required")?
+            .map(glob::expand)
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+
+        match paths.as_slice() {
+            [path] => execute(path, &args, &names, config),
+            paths => execute_batch(paths, config),
+        }
+    });
 
     if let Err(err) = result {
-        println!("error: {}", err);
+        let batch_file = env::var_os(BATCH_FILE_VAR);
+
+        if json_errors {
+            // One object per line, so that multiple problems remain easy to parse.
+            for mut value in err.to_json() {
+                if let Some(ref path) = batch_file {
+                    value["path"] = Path::new(path).display().to_string().into();
+                }
+                eprintln!("{}", value);
+            }
+        } else {
+            if let Error::Findings(ref findings) = err {
+                for finding in findings.iter() {
+                    println!("{}", finding);
+                }
+            }
+
+            println!("{}: {}", color::paint("error", color::RED), err);
+        }
+
+        // Tells execute_batch() that the error was reported, unlike a panic.
+        if batch_file.is_some() {
+            process::exit(BATCH_ERROR_CODE);
+        }
+    }
+}
+
+fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &Config,
+) -> Result<(), Error> {
+    match (audit_log_path(args, config), audit::output_path(path, args)) {
+        (Some(log_path), Some(output)) => audit::record(log_path, path, output, || {
+            execute_subcommand(path, args, names, config)
+        }),
+        _ => execute_subcommand(path, args, names, config),
+    }
+}
+
+fn audit_log_path<'a>(args: &'a clap::ArgMatches, config: &'a Config) -> Option<&'a Path> {
+    args.value_of_os("audit_log")
+        .map(Path::new)
+        .or(config.audit_log.as_deref())
+}
+
+fn execute_subcommand(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &Config,
+) -> Result<(), Error> {
+    match args.subcommand() {
+        ("apply", Some(args)) => apply::execute(path, args),
+        ("cat", Some(args)) => cat::execute(path, args, names),
+        ("check-official", Some(args)) => check_official::execute(path, args, config),
+        ("check-updatelist", Some(args)) => check_update_list::execute(path, args),
+        ("checksums", Some(args)) => checksums::execute(path, args, names),
+        ("create", Some(args)) => create::execute(path, args, names),
+        ("diff", Some(args)) => diff::execute(path, args, names),
+        ("doctor", Some(_)) => doctor::execute(path),
+        ("explain", Some(args)) => explain::execute(path, args),
+        ("export-rpcs3", Some(args)) => export_rpcs3::execute(path, args),
+        ("from-json", Some(args)) => from_json::execute(path, args),
+        ("header", Some(args)) => header::execute(path, args),
+        ("info", Some(_)) => info::execute(path),
+        ("lint", Some(args)) => lint::execute(path, args, names, config),
+        ("list", Some(args)) => list::execute(path, args, names),
+        ("map", Some(args)) => map::execute(path, args),
+        ("pack", Some(args)) => pack::execute(path, args, names, config),
+        ("print", Some(args)) => print::execute(path, args, names),
+        ("repair", Some(args)) => repair::execute(path, args, names),
+        ("search", Some(args)) => search::execute(path, args, names),
+        ("segment", Some(args)) => seg::execute(path, args, names, config),
+        ("shell", Some(_)) => shell::execute(path, names, audit_log_path(args, config)),
+        ("sort", Some(args)) => sort::execute(path, args, names),
+        ("stat", Some(args)) => stat::execute(path, args, names),
+        ("tree", Some(_)) => tree::execute(path, names),
+        ("unpack", Some(args)) => unpack::execute(path, args, names, config),
+        ("validate", Some(_)) => validate::execute(path),
+        ("verify", Some(args)) => verify::execute(path, args, config),
+        _ => Ok(()),
+    }
+}
+
+// The file a process started by execute_batch() operates on, in place of '-f'.
+const BATCH_FILE_VAR: &str = "PUPPER_BATCH_FILE";
+// The exit code of such a process that failed with an error, rather than a panic.
+const BATCH_ERROR_CODE: i32 = 2;
+
+// Executes the subcommand for each file in parallel, each in a process of its own, so that a panic
+// fails only that file and the output of each can be captured. The output is then printed in the
+// order the files were given, each under a line naming its file.
+fn execute_batch(paths: &[PathBuf], config: &Config) -> Result<(), Error> {
+    let exe = env::current_exe()
+        .map_err(|err| format!("failed to locate the pupper executable: {}", err))?;
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(paths.len()));
+
+    let thread_count = config.parallelism().thread_count().min(paths.len());
+    thread::scope(|scope| {
+        for _ in 0..thread_count {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let path = match paths.get(i) {
+                    Some(x) => x,
+                    None => break,
+                };
+
+                let output = Command::new(&exe)
+                    .args(env::args_os().skip(1))
+                    .env(BATCH_FILE_VAR, path)
+                    .stdin(Stdio::null())
+                    .output();
+                results.lock().unwrap().push((i, output));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(i, _)| *i);
+
+    let mut failures = 0;
+    for (i, output) in results {
+        let path = &paths[i];
+        println!("==> {} <==", path.display());
+
+        match output {
+            Ok(output) => {
+                let _ = io::stdout().write_all(&output.stdout);
+                let _ = io::stderr().write_all(&output.stderr);
+
+                if output.status.code() == Some(BATCH_ERROR_CODE) {
+                    failures += 1;
+                } else if !output.status.success() {
+                    failures += 1;
+                    println!(
+                        "error: processing '{}' crashed ({})",
+                        path.display(),
+                        output.status
+                    );
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                println!("error: failed to process '{}': {}", path.display(), err);
+            }
+        }
+    }
+
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} of {} file(s) failed", failures, paths.len()).into())
+    }
+}
+
+fn read_names_from_path(path: Option<&Path>) -> Result<SegmentNameMap, Error> {
+    let mut names = SegmentNameMap::default();
+
+    if let Some(path) = path {
+        let toml = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read from '{}': {}", path.display(), err))?;
+        names.extend_from_toml(&toml).map_err(|err| {
+            format!(
+                "failed to parse segment names at '{}': {}",
+                path.display(),
+                err
+            )
+        })?;
+    }
+
+    Ok(names)
+}
+
+// A segment may be given by file name or by ID.
+fn parse_segment(segment: &str, names: &SegmentNameMap) -> Result<SegmentId, String> {
+    if let Some(id) = names.id_of(segment) {
+        return Ok(id);
+    }
+
+    parse_number(segment, "segment ID")
+        .map(SegmentId)
+        .map_err(|_| format!("'{}' is neither a segment name nor an ID", segment))
+}
+
+// Numbers may be hexadecimal and may group digits with underscores, like Rust literals; segment
+// IDs and offsets are usually written in hex.
+fn parse_number<T: TryFrom<u64>>(number: &str, what: &str) -> Result<T, String> {
+    let digits = number.replace('_', "");
+
+    match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse(),
     }
+    .map_err(|err| format!("failed to parse {}: {}", what, err))
+    .and_then(|x| T::try_from(x).map_err(|_| format!("{} '{}' is too large", what, number)))
+}
+
+// Segment IDs in JSON are hexadecimal strings, as 'print' writes them, but plain integers are
+// accepted too.
+fn segment_id_from_json(json: &serde_json::Value) -> Option<SegmentId> {
+    match json.as_str() {
+        Some(id) => parse_number(id, "segment ID").ok().map(SegmentId),
+        None => json.as_u64().map(SegmentId),
+    }
+}
+
+fn read_pup_from_path(path: &Path) -> Result<Pup, Error> {
+    read_pup_data(path, |_| true).and_then(|x| parse_pup(&x, path, &mut ParseOptions::default()))
 }
 
-fn read_pup_from_path(path: &Path) -> Result<Pup, String> {
-    read_data_from_path(path).and_then(|x| {
-        x.as_slice()
-            .try_into()
-            .map_err(|err| format!("failed to parse PUP at '{}': {}", path.display(), err))
+fn parse_pup(data: &[u8], path: &Path, options: &mut ParseOptions) -> Result<Pup, Error> {
+    Pup::parse(data, options).map_err(|err| Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
     })
 }
 
-fn read_data_from_path(path: &Path) -> Result<Vec<u8>, String> {
-    fs::read(path).map_err(|err| format!("failed to read from '{}': {}", path.display(), err))
+// Like read_data_from_path(), but the PUP may also be at a URL, in which case the data of
+// unwanted segments are never downloaded, or compressed.
+fn read_pup_data(path: &Path, wanted: impl Fn(usize) -> bool) -> Result<Vec<u8>, Error> {
+    #[cfg(feature = "net")]
+    if let Some(url) = path.to_str().filter(|x| net::is_url(x)) {
+        return net::get_pup(url, wanted);
+    }
+    // Local files are read in full regardless.
+    let _ = wanted;
+
+    let data = read_data_from_path(path)?;
+    #[cfg(feature = "compress")]
+    let data = compress::decompress(data, path)?;
+    stream::check_budget(path, data.len() as u64)?;
+
+    Ok(data)
 }
 
-fn write_pup_to_path(pup: &Pup, path: &Path) -> Result<(), String> {
-    write_data_to_path(&Vec::<u8>::from(pup), path)
+fn parse_threads(threads: &str) -> Result<NonZeroUsize, String> {
+    parse_number(threads, "thread count")
+        .and_then(|x| NonZeroUsize::new(x).ok_or_else(|| "thread count must be nonzero".into()))
+}
+
+fn read_data_from_path(path: &Path) -> Result<Vec<u8>, Error> {
+    if let Ok(metadata) = path.metadata() {
+        stream::check_budget(path, metadata.len())?;
+    }
+
+    fs::read(path)
+        .map_err(|err| format!("failed to read from '{}': {}", path.display(), err).into())
+}
+
+fn modify_pup_at_path<F>(path: &Path, f: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut Pup) -> Result<(), Error>,
+{
+    read_pup_from_path(path).and_then(|ref mut pup| {
+        f(pup)?;
+        write_pup_to_path(pup, path)
+    })
+}
+
+fn write_pup_to_path(pup: &Pup, path: &Path) -> Result<(), Error> {
+    let data = pup
+        .serialize(&Default::default())
+        .map_err(|err| format!("failed to serialize PUP: {}", err))?;
+    #[cfg(feature = "compress")]
+    let data = compress::compress(data, path)?;
+
+    write_data_to_path(&data, path)
+}
+
+fn write_data_to_path(data: &[u8], path: &Path) -> Result<(), Error> {
+    fs::write(path, data)
+        .map_err(|err| format!("failed to write to '{}': {}", path.display(), err).into())
+}
+
+// The '--alignment' option, or its default from the configuration.
+fn alignment_option(args: &clap::ArgMatches, config: &Config) -> Result<Option<u64>, String> {
+    match args.value_of("alignment") {
+        Some(alignment) => parse_number(alignment, "alignment")
+            .and_then(|x: u64| {
+                Some(x)
+                    .filter(|x| *x != 0)
+                    .ok_or_else(|| "alignment must be nonzero".into())
+            })
+            .map(Some),
+        None => Ok(config.alignment),
+    }
 }
 
-fn write_data_to_path(data: &[u8], path: &Path) -> Result<(), String> {
-    fs::write(path, data).map_err(|err| format!("failed to write to '{}': {}", path.display(), err))
+// The '--key' option, or its default from the configuration.
+fn key_file_option<'a>(args: &'a clap::ArgMatches, config: &'a Config) -> Option<&'a Path> {
+    args.value_of_os("key")
+        .map(Path::new)
+        .or(config.key_file.as_deref())
 }
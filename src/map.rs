@@ -0,0 +1,73 @@
+mod template;
+
+use pupper::{RawHeader, Region};
+
+use serde_json::{json, Value};
+
+use std::{convert::TryFrom as _, path::Path};
+
+pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let data = super::read_pup_data(path, |_| true)?;
+    let header = RawHeader::try_from(data.as_slice()).map_err(|err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    })?;
+
+    let mut regions = header.annotate();
+    regions.sort_by_key(|x| x.start);
+    let regions = with_gaps(regions, data.len() as u64);
+
+    if let Some(format) = args.value_of("format") {
+        print!("{}", template::generate(format, &regions));
+    } else if args.is_present("json") {
+        let regions: Vec<Value> = regions
+            .iter()
+            .map(|x| {
+                json!({
+                    "name": x.name,
+                    "start": x.start,
+                    "length": x.length,
+                    "description": x.description,
+                })
+            })
+            .collect();
+        println!("{:#}", Value::from(regions));
+    } else {
+        for region in regions.iter() {
+            println!(
+                "{:#010x} {:>10} {:<32} {}",
+                region.start, region.length, region.name, region.description
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Adds a region for each run of bytes that no structure accounts for, so that the map covers the
+// whole file.
+fn with_gaps(regions: Vec<Region>, file_size: u64) -> Vec<Region> {
+    let mut covered = 0;
+    let mut all = Vec::with_capacity(regions.len());
+
+    let gap = |start: u64, end: u64| Region {
+        name: format!("unaccounted[{:#x}]", start),
+        start,
+        length: end - start,
+        description: "not part of any structure".into(),
+    };
+
+    for region in regions {
+        if region.start > covered {
+            all.push(gap(covered, region.start));
+        }
+        covered = covered.max(region.start.saturating_add(region.length));
+        all.push(region);
+    }
+    if file_size > covered {
+        all.push(gap(covered, file_size));
+    }
+
+    all
+}
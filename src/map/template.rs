@@ -0,0 +1,287 @@
+// Templates for binary-analysis tools, laid out with the offsets of one specific PUP. Structure
+// definitions are fixed; everything else comes from the map.
+
+use pupper::Region;
+
+use std::fmt::Write as _;
+
+pub fn generate(format: &str, regions: &[Region]) -> String {
+    let layout = Layout::of(regions);
+
+    match format {
+        "imhex" => imhex(&layout),
+        "kaitai" => kaitai(&layout),
+        "bt" => bt(&layout),
+        _ => unreachable!(),
+    }
+}
+
+struct Layout<'a> {
+    // The start and entry count of each table, if it has any entries.
+    segment_table: Option<(u64, usize)>,
+    digest_table: Option<(u64, usize)>,
+    header_signature: Option<u64>,
+    // Segment data, excluding empty segments, which tools tend to reject.
+    segments: Vec<&'a Region>,
+}
+
+impl<'a> Layout<'a> {
+    fn of(regions: &'a [Region]) -> Self {
+        let start_of = |name: &str| regions.iter().find(|x| x.name == name).map(|x| x.start);
+        let count = |prefix: &str| {
+            regions
+                .iter()
+                .filter(|x| x.name.starts_with(prefix) && x.name.ends_with("].padding"))
+                .count()
+        };
+
+        let segment_table = start_of("segment_table[0].id").map(|x| (x, count("segment_table[")));
+        let digest_table =
+            start_of("digest_table[0].segment_index").map(|x| (x, count("digest_table[")));
+
+        let segments = regions
+            .iter()
+            .filter(|x| x.name.starts_with("segment[") && x.length != 0)
+            .collect();
+
+        Self {
+            segment_table,
+            digest_table,
+            header_signature: start_of("header_signature"),
+            segments,
+        }
+    }
+}
+
+// Segment names are made of the index rather than the file name, which may not be a valid
+// identifier.
+fn segment_ident(region: &Region) -> String {
+    region.name.replace('[', "_").replace(']', "")
+}
+
+fn imhex(layout: &Layout) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "#pragma endian big
+
+struct Metadata {
+    char magic[8];
+    u64 package_version;
+    u64 image_version;
+    u64 segment_count;
+    u64 header_size;
+    u64 data_size;
+};
+
+struct SegmentEntry {
+    u64 id;
+    u64 offset;
+    u64 size;
+    u32 signature_kind;
+    padding[4];
+};
+
+struct DigestEntry {
+    u64 segment_index;
+    u8 digest[20];
+    padding[4];
+};
+
+Metadata metadata @ 0x00;
+",
+    );
+
+    if let Some((start, count)) = layout.segment_table {
+        let _ = writeln!(out, "SegmentEntry segment_table[{}] @ {:#x};", count, start);
+    }
+    if let Some((start, count)) = layout.digest_table {
+        let _ = writeln!(out, "DigestEntry digest_table[{}] @ {:#x};", count, start);
+    }
+    if let Some(start) = layout.header_signature {
+        let _ = writeln!(out, "u8 header_signature[20] @ {:#x};", start);
+    }
+    for region in layout.segments.iter() {
+        let _ = writeln!(
+            out,
+            "u8 {}[{:#x}] @ {:#x}; // {}",
+            segment_ident(region),
+            region.length,
+            region.start,
+            region.description
+        );
+    }
+
+    out
+}
+
+fn kaitai(layout: &Layout) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "meta:
+  id: pup
+  title: PS3 PUP
+  endian: be
+instances:
+  metadata:
+    pos: 0x0
+    type: metadata
+",
+    );
+
+    if let Some((start, count)) = layout.segment_table {
+        let _ = write!(
+            out,
+            "  segment_table:
+    pos: {:#x}
+    type: segment_entry
+    repeat: expr
+    repeat-expr: {}
+",
+            start, count
+        );
+    }
+    if let Some((start, count)) = layout.digest_table {
+        let _ = write!(
+            out,
+            "  digest_table:
+    pos: {:#x}
+    type: digest_entry
+    repeat: expr
+    repeat-expr: {}
+",
+            start, count
+        );
+    }
+    if let Some(start) = layout.header_signature {
+        let _ = write!(
+            out,
+            "  header_signature:
+    pos: {:#x}
+    size: 20
+",
+            start
+        );
+    }
+    for region in layout.segments.iter() {
+        let _ = write!(
+            out,
+            "  {}:
+    pos: {:#x}
+    size: {:#x}
+    doc: {:?}
+",
+            segment_ident(region),
+            region.start,
+            region.length,
+            region.description
+        );
+    }
+
+    out.push_str(
+        "types:
+  metadata:
+    seq:
+      - id: magic
+        contents: [0x53, 0x43, 0x45, 0x55, 0x46, 0, 0, 0]
+      - id: package_version
+        type: u8
+      - id: image_version
+        type: u8
+      - id: segment_count
+        type: u8
+      - id: header_size
+        type: u8
+      - id: data_size
+        type: u8
+  segment_entry:
+    seq:
+      - id: id
+        type: u8
+      - id: offset
+        type: u8
+      - id: size
+        type: u8
+      - id: signature_kind
+        type: u4
+      - id: padding
+        size: 4
+  digest_entry:
+    seq:
+      - id: segment_index
+        type: u8
+      - id: digest
+        size: 20
+      - id: padding
+        size: 4
+",
+    );
+
+    out
+}
+
+fn bt(layout: &Layout) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "BigEndian();
+
+typedef struct {
+    char magic[8];
+    uint64 package_version;
+    uint64 image_version;
+    uint64 segment_count;
+    uint64 header_size;
+    uint64 data_size;
+} METADATA;
+
+typedef struct {
+    uint64 id <format=hex>;
+    uint64 offset <format=hex>;
+    uint64 size;
+    uint32 signature_kind;
+    uchar padding[4];
+} SEGMENT_ENTRY;
+
+typedef struct {
+    uint64 segment_index;
+    uchar digest[20];
+    uchar padding[4];
+} DIGEST_ENTRY;
+
+FSeek(0x0);
+METADATA metadata;
+",
+    );
+
+    if let Some((start, count)) = layout.segment_table {
+        let _ = write!(
+            out,
+            "FSeek({:#x});\nSEGMENT_ENTRY segment_table[{}];\n",
+            start, count
+        );
+    }
+    if let Some((start, count)) = layout.digest_table {
+        let _ = write!(
+            out,
+            "FSeek({:#x});\nDIGEST_ENTRY digest_table[{}];\n",
+            start, count
+        );
+    }
+    if let Some(start) = layout.header_signature {
+        let _ = write!(out, "FSeek({:#x});\nuchar header_signature[20];\n", start);
+    }
+    for region in layout.segments.iter() {
+        let _ = write!(
+            out,
+            "FSeek({:#x});\nuchar {}[{:#x}] <comment={:?}>;\n",
+            region.start,
+            segment_ident(region),
+            region.length,
+            region.description
+        );
+    }
+
+    out
+}
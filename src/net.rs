@@ -0,0 +1,309 @@
+use pupper::{FixedSize as _, HeaderMetadata, RawHeader};
+
+use std::{
+    convert::TryFrom as _,
+    fs::{self, OpenOptions},
+    io::{Read as _, Write as _},
+    net::TcpStream,
+    ops::Range,
+    path::Path,
+    time::Duration,
+};
+
+// Sony's update servers speak plain HTTP, so a full-blown HTTP client would be overkill.
+
+/// Downloads the body at a URL, following redirects.
+///
+/// If a range is given, only those bytes are requested.
+pub fn get(url: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, crate::Error> {
+    let response = fetch(url, range.clone())?;
+    if response.ranged {
+        return Ok(response.body);
+    }
+
+    // The server ignored the range, so we apply it ourselves.
+    match range {
+        Some(range) => usize::try_from(range.start)
+            .ok()
+            .zip(usize::try_from(range.end).ok())
+            .and_then(|(start, end)| response.body.get(start..end))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| format!("'{}' is shorter than expected", url).into()),
+        None => Ok(response.body),
+    }
+}
+
+// A complete response to a GET request.
+struct Response {
+    body: Vec<u8>,
+    // The size of the whole body, if the server reports it.
+    size: Option<u64>,
+    // Whether the body is only the requested range; if not, it's the whole body.
+    ranged: bool,
+}
+
+// Like get(), but the body is the whole body if the server ignored the range.
+fn fetch(url: &str, range: Option<Range<u64>>) -> Result<Response, crate::Error> {
+    if let Some(ref range) = range {
+        if range.start >= range.end {
+            return Ok(Response {
+                body: Vec::new(),
+                size: None,
+                ranged: true,
+            });
+        }
+    }
+
+    let mut url = url.to_string();
+
+    // A handful of redirects is plenty for a CDN.
+    for _ in 0..5 {
+        let (host, path) = split_url(&url)?;
+        let response = request(host, path, range.clone())
+            .map_err(|err| format!("failed to download '{}': {}", url, err))?;
+        let (status, headers, body) = split_response(&response)
+            .ok_or_else(|| format!("failed to download '{}': malformed response", url))?;
+
+        // HTTP/1.0 bodies end when the connection does, so a dropped connection only shows as a
+        // body shorter than announced.
+        let expected_len = match status {
+            // e.g., 'Content-Range: bytes 0-0/206213120'
+            206 => header(headers, "content-range")
+                .and_then(|x| x.strip_prefix("bytes "))
+                .and_then(|x| x.split_once('/'))
+                .and_then(|(x, _)| x.split_once('-'))
+                .and_then(|(first, last)| {
+                    let len = last.parse::<u64>().ok()?.checked_sub(first.parse().ok()?)?;
+
+                    len.checked_add(1)
+                }),
+            _ => header(headers, "content-length").and_then(|x| x.parse().ok()),
+        };
+        if matches!(status, 200 | 206) {
+            if let Some(expected_len) = expected_len.filter(|x| body.len() as u64 != *x) {
+                return Err(format!(
+                    "failed to download '{}': received {} of {} B",
+                    url,
+                    body.len(),
+                    expected_len
+                )
+                .into());
+            }
+        }
+
+        match status {
+            200 => {
+                return Ok(Response {
+                    body: body.to_vec(),
+                    size: Some(body.len() as u64),
+                    ranged: range.is_none(),
+                })
+            }
+            206 => {
+                let size = header(headers, "content-range")
+                    .and_then(|x| x.rsplit_once('/'))
+                    .and_then(|(_, x)| x.parse().ok());
+
+                return Ok(Response {
+                    body: body.to_vec(),
+                    size,
+                    ranged: true,
+                });
+            }
+            301 | 302 | 303 | 307 | 308 => {
+                let location = header(headers, "location")
+                    .ok_or_else(|| format!("redirect from '{}' has no location", url))?;
+
+                url = if location.starts_with('/') {
+                    format!("http://{}{}", host, location)
+                } else {
+                    location.to_string()
+                };
+            }
+            _ => return Err(format!("failed to download '{}': status {}", url, status).into()),
+        }
+    }
+
+    Err(format!("failed to download '{}': too many redirects", url).into())
+}
+
+/// Downloads the body at a URL into a file, resuming where an earlier, interrupted download into
+/// the same file left off, and returns the whole body.
+///
+/// The body is requested a chunk at a time, and each chunk is appended to the file as soon as it
+/// arrives, so little is lost when a connection drops. Servers that don't support ranges send the
+/// whole body at once instead, which is then written in one go.
+pub fn download(url: &str, path: &Path) -> Result<Vec<u8>, crate::Error> {
+    const CHUNK_SIZE: u64 = 0x80_0000;
+
+    let write_err = |err| format!("failed to write to '{}': {}", path.display(), err);
+
+    let probe = fetch(url, Some(0..1))?;
+    if !probe.ranged {
+        fs::write(path, &probe.body).map_err(write_err)?;
+        return Ok(probe.body);
+    }
+    let size = probe
+        .size
+        .ok_or_else(|| format!("'{}' didn't report its size", url))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(write_err)?;
+    let mut done = file.metadata().map_err(write_err)?.len();
+    // The body changed since, so the earlier download is useless.
+    if done > size {
+        file.set_len(0).map_err(write_err)?;
+        done = 0;
+    }
+    if done != 0 {
+        println!("resuming '{}' at {} of {} B", url, done, size);
+    }
+
+    while done < size {
+        let chunk = get(url, Some(done..(done + CHUNK_SIZE).min(size)))?;
+        if chunk.is_empty() {
+            return Err(format!("'{}' is shorter than expected", url).into());
+        }
+
+        file.write_all(&chunk).map_err(write_err)?;
+        done += chunk.len() as u64;
+    }
+
+    fs::read(path)
+        .map_err(|err| format!("failed to read from '{}': {}", path.display(), err).into())
+}
+
+/// Whether a path given on the command line is actually a URL.
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Downloads the header of the PUP at a URL, along with the data of the wanted segments.
+///
+/// Data of all other segments are zeroed, so the result parses like the full PUP would. If the
+/// server doesn't support ranges, though, the whole PUP is downloaded at once and returned as is.
+///
+/// The sizes in the header come from the server, so the PUP they declare must fit within the body
+/// the server reports, and within '--max_memory'.
+pub fn get_pup(url: &str, wanted: impl Fn(usize) -> bool) -> Result<Vec<u8>, crate::Error> {
+    let probe = fetch(url, Some(0..(HeaderMetadata::SIZE as u64)))?;
+    if !probe.ranged {
+        crate::stream::check_budget(Path::new(url), probe.body.len() as u64)?;
+        return Ok(probe.body);
+    }
+    let meta_data = probe.body;
+    let file_size = probe
+        .size
+        .ok_or_else(|| format!("'{}' didn't report its size", url))?;
+    let parse_err = |err| crate::Error::Parse {
+        path: url.into(),
+        file_size,
+        err,
+    };
+
+    let meta = HeaderMetadata::try_from(meta_data.as_slice()).map_err(parse_err)?;
+    let size = meta
+        .header_size
+        .checked_add(meta.data_size)
+        .filter(|x| *x <= file_size)
+        .ok_or_else(|| {
+            format!(
+                "the PUP at '{}' declares more data than the {} B of its body",
+                url, file_size
+            )
+        })?;
+    crate::stream::check_budget(Path::new(url), size)?;
+    let size = usize::try_from(size)
+        .map_err(|_| format!("the PUP at '{}' is too large to hold in memory", url))?;
+
+    let header_data = get(url, Some(0..meta.header_size))?;
+    let header = RawHeader::try_from(header_data.as_slice()).map_err(parse_err)?;
+
+    let mut data = header_data;
+    data.resize(size, 0);
+
+    for (i, entry) in header.segment_entries.iter().enumerate() {
+        if !wanted(i) {
+            continue;
+        }
+
+        let outside = || format!("segment {} lies outside of the PUP at '{}'", i, url);
+        let end = entry.offset.checked_add(entry.size).ok_or_else(outside)?;
+        let seg = usize::try_from(entry.offset)
+            .ok()
+            .zip(usize::try_from(end).ok())
+            .and_then(|(start, end)| data.get_mut(start..end))
+            .ok_or_else(outside)?;
+        let seg_data = get(url, Some(entry.offset..end))?;
+        if seg_data.len() != seg.len() {
+            return Err(format!("'{}' is shorter than expected", url).into());
+        }
+
+        seg.copy_from_slice(&seg_data);
+    }
+
+    Ok(data)
+}
+
+fn split_url(url: &str) -> Result<(&str, &str), crate::Error> {
+    let rest = match url.strip_prefix("http://") {
+        Some(x) => x,
+        None if url.starts_with("https://") => {
+            return Err(format!("'{}' uses HTTPS, which isn't supported", url).into())
+        }
+        None => return Err(format!("'{}' isn't an HTTP URL", url).into()),
+    };
+
+    Ok(match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    })
+}
+
+fn request(host: &str, path: &str, range: Option<Range<u64>>) -> std::io::Result<Vec<u8>> {
+    let mut stream = if host.contains(':') {
+        TcpStream::connect(host)
+    } else {
+        TcpStream::connect((host, 80))
+    }?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+
+    // HTTP/1.0 saves us from having to deal with chunked responses.
+    let mut request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n", path, host);
+    if let Some(range) = range {
+        request += &format!("Range: bytes={}-{}\r\n", range.start, range.end - 1);
+    }
+    request += "\r\n";
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+
+    Ok(response)
+}
+
+fn split_response(response: &[u8]) -> Option<(u16, &str, &[u8])> {
+    let head_size = response.windows(4).position(|x| x == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&response[..head_size]).ok()?;
+    let body = &response[(head_size + 4)..];
+
+    let (status_line, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+    let status = status_line.split(' ').nth(1)?.parse().ok()?;
+
+    Some((status, headers, body))
+}
+
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.split("\r\n").find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+
+        if key.eq_ignore_ascii_case(name) {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,32 @@
+use pupper::{FromDirOptions, Pup, SegmentNameMap};
+
+use std::path::Path;
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let alignment = super::alignment_option(args, config)?;
+    let dir = Path::new(args.value_of("dir").unwrap());
+    let options = FromDirOptions {
+        names: names.clone(),
+        image_version: super::create::parse_img_version_option(args.value_of("img_version"))?,
+        skip_unknown: args.is_present("skip_unknown"),
+    };
+
+    let mut pup = Pup::from_dir(dir, &options)
+        .map_err(|err| format!("failed to pack '{}': {}", dir.display(), err))?;
+    // '-g' takes precedence over the manifest 'unpack' writes.
+    if args.is_present("img_version") {
+        pup.image_version = options.image_version;
+    }
+    if let Some(alignment) = alignment {
+        for seg in pup.segments.iter_mut().filter(|x| x.alignment() == 1) {
+            seg.set_alignment(alignment);
+        }
+    }
+
+    super::write_pup_to_path(&pup, path)
+}
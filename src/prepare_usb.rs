@@ -0,0 +1,19 @@
+use std::path::Path;
+
+pub fn execute(args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let root = Path::new(args.value_of("root").unwrap());
+    let pup_path = Path::new(args.value_of("pup").unwrap());
+
+    let data = super::read_pup_data(pup_path, |_| true)?;
+    let path = pupper::install_to_usb(root, &data).map_err(|err| {
+        format!(
+            "failed to prepare USB drive at '{}': {}",
+            root.display(),
+            err
+        )
+    })?;
+
+    println!("copied to '{}'", path.display());
+
+    Ok(())
+}
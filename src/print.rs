@@ -1,35 +1,187 @@
-use pupper::Pup;
+use pupper::{ParseOptions, Pup, PupStream, PupSummary, SegmentNameMap, SummaryOptions};
 
-use std::convert::TryFrom as _;
+use serde::ser::{Serialize, SerializeMap as _, Serializer};
 
-pub fn execute(path: &std::path::Path) -> Result<(), String> {
-    super::read_pup_from_path(path).map(|ref pup| print_pup(pup))
+// The fields of a segment in the output, in order.
+const FIELDS: [&str; 7] = [
+    "id",
+    "file-name",
+    "size",
+    "signature",
+    "content-type",
+    "fingerprint",
+    "data",
+];
+
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let fields = args.value_of("fields").map(parse_fields).transpose()?;
+    let selected = |field| fields.as_ref().is_some_and(|x| x.contains(&field));
+    let selection = Selection {
+        fields: fields
+            .as_deref()
+            .or(args.value_of("only").map(|_| &FIELDS[..])),
+        only: args.value_of("only"),
+    };
+
+    // Selecting a field implies the option that includes it.
+    let fingerprints = args.is_present("fingerprints") || selected("fingerprint");
+    let embed_data = args.is_present("embed_data") || selected("data");
+
+    let all = args.is_present("all");
+
+    let data = super::read_pup_data(path, |_| fingerprints || embed_data || all)?;
+
+    if all {
+        // One object per PUP, in the order they're concatenated.
+        for pup in PupStream::new(&data) {
+            let pup = pup.map_err(|err| crate::Error::Parse {
+                path: path.into(),
+                file_size: data.len() as u64,
+                err,
+            })?;
+            print_pup(&pup, names, fingerprints, embed_data, &selection);
+        }
+
+        return Ok(());
+    }
+
+    let mut warnings = Vec::new();
+    let pup = super::parse_pup(
+        &data,
+        path,
+        &mut ParseOptions {
+            on_warning: Some(Box::new(|x| warnings.push(x))),
+            names: Some(names),
+            ..Default::default()
+        },
+    )?;
+
+    for warning in warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+
+    print_pup(&pup, names, fingerprints, embed_data, &selection);
+
+    Ok(())
 }
 
-fn print_pup(pup: &Pup) {
-    println!("{{");
-    println!("  \"image-version\": {},", pup.image_version);
-    println!("  \"segments\": [");
+// What '--fields' and '--only' leave of the output.
+struct Selection<'a> {
+    fields: Option<&'a [&'static str]>,
+    only: Option<&'a str>,
+}
+
+// Fields are given by their names in the output or, more briefly, as 'name' and 'digest'.
+fn parse_fields(fields: &str) -> Result<Vec<&'static str>, String> {
+    fields
+        .split(',')
+        .map(|field| {
+            let field = match field.trim() {
+                "name" => "file-name",
+                "digest" => "fingerprint",
+                x => x,
+            };
+
+            FIELDS.iter().copied().find(|x| *x == field).ok_or_else(|| {
+                format!(
+                    "'{}' isn't a segment field (expected one of: name, digest, {})",
+                    field,
+                    FIELDS.join(", ")
+                )
+            })
+        })
+        .collect()
+}
 
-    for (i, seg) in pup.segments.iter().enumerate() {
-        println!("    {{");
-        println!("      \"id\": {},", seg.id.0);
+fn print_pup(
+    pup: &Pup,
+    names: &SegmentNameMap,
+    fingerprints: bool,
+    embed_data: bool,
+    selection: &Selection,
+) {
+    let summary = pup.summary(
+        names,
+        &SummaryOptions {
+            fingerprints,
+            embed_data,
+        },
+    );
 
-        let file_name = <&'static str>::try_from(seg.id)
-            .map_or_else(|_| "null".into(), |x| format!("\"{}\"", x));
-        println!("      \"file-name\": {},", file_name);
+    // Serializing plain data to a string can't fail.
+    let json = match selection.fields {
+        Some(fields) => serde_json::to_string_pretty(&Selected {
+            summary: &summary,
+            fields,
+            only: selection.only,
+        }),
+        None => serde_json::to_string_pretty(&summary),
+    };
+    println!("{}", json.unwrap());
+}
+
+// A summary with only the selected fields of each segment, in the order they were selected.
+struct Selected<'a> {
+    summary: &'a PupSummary,
+    fields: &'a [&'static str],
+    only: Option<&'a str>,
+}
 
-        println!("      \"size\": {},", seg.data.len());
-        println!("      \"signature\": \"{}\"", seg.signature());
-        print!("    }}");
+impl Serialize for Selected<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut meta =
+            match serde_json::to_value(self.summary).map_err(serde::ser::Error::custom)? {
+                serde_json::Value::Object(x) => x,
+                _ => unreachable!(),
+            };
+        meta.remove("segments");
+        let segments = self
+            .summary
+            .segments
+            .iter()
+            .map(|seg| {
+                let seg = serde_json::to_value(seg).map_err(serde::ser::Error::custom)?;
 
-        if i == (pup.segments.len() - 1) {
-            println!();
-        } else {
-            println!(",");
+                Ok(SelectedFields {
+                    segment: seg,
+                    fields: self.fields,
+                })
+            })
+            .collect::<Result<Vec<_>, S::Error>>()?;
+
+        match self.only {
+            Some("segments") => segments.serialize(serializer),
+            Some(_) => meta.serialize(serializer),
+            None => {
+                let mut map = serializer.serialize_map(Some(meta.len() + 1))?;
+                for (key, value) in meta.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+                map.serialize_entry("segments", &segments)?;
+                map.end()
+            }
         }
     }
+}
+
+struct SelectedFields<'a> {
+    segment: serde_json::Value,
+    fields: &'a [&'static str],
+}
 
-    println!("  ]");
-    println!("}}");
+impl Serialize for SelectedFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for field in self.fields.iter() {
+            // Optional fields are left out, as they are otherwise.
+            if let Some(value) = self.segment.get(field) {
+                map.serialize_entry(field, value)?;
+            }
+        }
+        map.end()
+    }
 }
@@ -0,0 +1,31 @@
+use pupper::SegmentNameMap;
+
+use std::path::Path;
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let data = super::read_pup_data(path, |_| true)?;
+    let recovery = pupper::recover(&data);
+
+    for note in recovery.notes.iter() {
+        println!("note: {}", note);
+    }
+    for (i, seg) in recovery.pup.segments.iter().enumerate() {
+        println!(
+            "{}: {:#06x} {} ({} B)",
+            i,
+            seg.id.0,
+            names.name_of(seg.id).unwrap_or("?"),
+            seg.data().len()
+        );
+    }
+
+    if recovery.pup.segments.is_empty() {
+        return Err("no segments could be recovered".to_string().into());
+    }
+
+    super::write_pup_to_path(&recovery.pup, Path::new(args.value_of("output").unwrap()))
+}
@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+
+pub fn execute(args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let schema = match args.value_of("command").unwrap() {
+        "diff" => diff_schema(),
+        "map" => map_schema(),
+        "print" => print_schema(),
+        "errors" => errors_schema(),
+        "verify" => verify_schema(),
+        _ => unreachable!(),
+    };
+
+    println!("{:#}", schema);
+
+    Ok(())
+}
+
+// The output of 'print', which 'from-json' also reads.
+fn print_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "pupper print",
+        "type": "object",
+        "required": ["image-version", "segments"],
+        "additionalProperties": false,
+        "properties": {
+            "image-version": { "type": "integer", "minimum": 0 },
+            "segments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["id", "file-name", "size", "signature", "content-type"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "id": { "$ref": "#/definitions/segment-id" },
+                        "file-name": { "type": ["string", "null"] },
+                        "size": { "type": "integer", "minimum": 0 },
+                        "signature": { "$ref": "#/definitions/sha1" },
+                        "content-type": {
+                            "enum": ["tar", "SELF", "package", "SCE", "XML", "text", "unknown"]
+                        },
+                        "fingerprint": {
+                            "$ref": "#/definitions/sha1",
+                            "description": "Present with '--fingerprints'"
+                        },
+                        "data": {
+                            "type": "string",
+                            "contentEncoding": "base64",
+                            "description": "Present with '--embed_data'"
+                        }
+                    }
+                }
+            }
+        },
+        "definitions": {
+            "sha1": { "type": "string", "pattern": "^[0-9a-f]{40}$" },
+            "segment-id": { "type": "string", "pattern": "^0x[0-9a-f]+$" }
+        }
+    })
+}
+
+// The output of 'diff --format json-patch'.
+fn diff_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "pupper diff",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["op", "path"],
+            "properties": {
+                "op": { "enum": ["add", "remove", "replace"] },
+                "path": {
+                    "type": "string",
+                    "pattern": "^/(image-version|package-version|segments/0x[0-9a-f]+)$"
+                },
+                "old": { "type": "integer", "minimum": 0 },
+                "new": { "type": "integer", "minimum": 0 },
+                "id": { "$ref": "#/definitions/segment-id" },
+                "old-size": { "type": ["integer", "null"], "minimum": 0 },
+                "new-size": { "type": ["integer", "null"], "minimum": 0 },
+                "old-digest": { "$ref": "#/definitions/nullable-sha1" },
+                "new-digest": { "$ref": "#/definitions/nullable-sha1" }
+            }
+        },
+        "definitions": {
+            "segment-id": { "type": "string", "pattern": "^0x[0-9a-f]+$" },
+            "nullable-sha1": {
+                "anyOf": [
+                    { "type": "string", "pattern": "^[0-9a-f]{40}$" },
+                    { "type": "null" }
+                ]
+            }
+        }
+    })
+}
+
+// The output of 'map --json'.
+fn map_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "pupper map",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["name", "start", "length", "description"],
+            "additionalProperties": false,
+            "properties": {
+                "name": { "type": "string" },
+                "start": { "type": "integer", "minimum": 0 },
+                "length": { "type": "integer", "minimum": 0 },
+                "description": { "type": "string" }
+            }
+        }
+    })
+}
+
+// The output of 'verify --json'.
+fn verify_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "pupper verify",
+        "type": "object",
+        "required": ["valid", "header-signature-valid", "invalid-signatures"],
+        "additionalProperties": false,
+        "properties": {
+            "valid": { "type": "boolean" },
+            "header-signature-valid": { "type": "boolean" },
+            "invalid-signatures": {
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0 }
+            }
+        }
+    })
+}
+
+// Each line written to stderr with '--error_format json'.
+fn errors_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "pupper error",
+        "type": "object",
+        "required": ["code", "message", "offset", "segment"],
+        "additionalProperties": false,
+        "properties": {
+            "code": { "type": "string", "pattern": "^[a-z]+(-[a-z]+)*$" },
+            "message": { "type": "string" },
+            "offset": { "type": ["integer", "null"], "minimum": 0 },
+            "segment": { "type": ["integer", "null"], "minimum": 0 },
+            "path": {
+                "type": "string",
+                "description": "Present when more than one PUP is given"
+            }
+        }
+    })
+}
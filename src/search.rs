@@ -0,0 +1,45 @@
+use pupper::SegmentNameMap;
+
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    let pattern = match (args.value_of("hex"), args.value_of("text")) {
+        (Some(hex), _) => parse_hex(hex)?,
+        (_, Some(text)) => text.as_bytes().to_vec(),
+        _ => return Err("a pattern (--hex or --text) is required".into()),
+    };
+    if pattern.is_empty() {
+        return Err("pattern is empty".into());
+    }
+
+    let pup = super::read_pup_from_path(path)?;
+
+    for found in pup.search(&pattern) {
+        let seg = &pup.segments[found.segment];
+        let file_name = names.name_of(seg.id).unwrap_or("?");
+
+        println!(
+            "segment {} ({}): offset {:#x}",
+            found.segment, file_name, found.offset
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, crate::Error> {
+    let hex: String = hex.chars().filter(|x| !x.is_whitespace()).collect();
+
+    hex.as_bytes()
+        .chunks(2)
+        .map(|x| {
+            std::str::from_utf8(x)
+                .ok()
+                .filter(|x| x.len() == 2)
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .ok_or_else(|| format!("'{}' is not a hexadecimal string", hex).into())
+        })
+        .collect()
+}
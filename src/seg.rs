@@ -1,58 +1,85 @@
+mod digest;
 mod extract;
 mod insert;
 mod remove;
+mod strings;
 
-use pupper::{Pup, SegmentId};
+use pupper::{SegmentId, SegmentNameMap};
 
-use std::{convert::TryFrom as _, path::Path};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
 
-pub fn execute(path: &Path, args: &clap::ArgMatches) -> Result<(), String> {
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
     let index = parse_index_option(args.value_of("index"))?;
 
     match args.subcommand() {
+        ("insert", Some(sub_args)) => {
+            // An explicit index overrides the canonical position.
+            let index = args.value_of("index").map(|_| index);
+            insert::execute(path, index, sub_args, names)
+        }
+        ("digest", Some(args)) => digest::execute(path, index, args, names, config),
         ("extract", Some(args)) => extract::execute(path, index, args),
-        ("insert", Some(args)) => insert::execute(path, index, args),
         ("remove", Some(_)) => remove::execute(path, index),
+        ("strings", Some(args)) => strings::execute(path, index, args),
         _ => Ok(()),
     }
 }
 
+// The path of a specific part of a segment split by 'extract --max_part_size' (e.g.,
+// 'update_files.tar.001').
+fn part_path(path: &Path, i: usize) -> PathBuf {
+    let mut part_path = OsString::from(path);
+    part_path.push(format!(".{:03}", i));
+
+    part_path.into()
+}
+
+// Parses a size like '4G'. Suffixes are decimal, so that '4G' fits on FAT32, whose files are at
+// most 4 GiB - 1 B.
+pub fn parse_size_option(size: &str) -> Result<usize, String> {
+    let (digits, multiplier) = match size.as_bytes().last() {
+        Some(b'K') | Some(b'k') => (&size[..(size.len() - 1)], 1_000),
+        Some(b'M') => (&size[..(size.len() - 1)], 1_000_000),
+        Some(b'G') => (&size[..(size.len() - 1)], 1_000_000_000),
+        _ => (size, 1),
+    };
+
+    crate::parse_number::<usize>(digits, "size")
+        .ok()
+        .and_then(|x| x.checked_mul(multiplier))
+        .filter(|x| *x != 0)
+        .ok_or_else(|| format!("'{}' isn't a valid size (e.g., 4G)", size))
+}
+
 fn parse_index_option(index: Option<&str>) -> Result<usize, String> {
-    index.map_or(Ok(0), |index| {
-        index
-            .parse()
-            .map_err(|err| format!("failed to parse segment index: {}", err))
-    })
+    index.map_or(Ok(0), |index| crate::parse_number(index, "segment index"))
 }
 
-fn parse_id_option(id: Option<&str>, path: &Path) -> Result<SegmentId, String> {
+fn parse_id_option(
+    id: Option<&str>,
+    path: &Path,
+    names: &SegmentNameMap,
+) -> Result<SegmentId, String> {
     id.map_or_else(
         || {
             // Let's try to derive the segment ID from the file name.
-            let file_name = path.file_stem();
+            let file_name = path.file_name();
 
             let id = file_name
                 .and_then(std::ffi::OsStr::to_str)
-                .map(SegmentId::try_from)
-                .and_then(Result::ok)
-                .unwrap_or_else(SegmentId::default);
+                .and_then(|x| names.id_of(x))
+                .unwrap_or_default();
 
             Ok(id)
         },
-        |id| {
-            id.parse()
-                .map(SegmentId)
-                .map_err(|err| format!("failed to parse segment ID: {}", err))
-        },
+        |id| crate::parse_number(id, "segment ID").map(SegmentId),
     )
 }
-
-fn modify_pup_at_path<F>(path: &Path, f: F) -> Result<(), String>
-where
-    F: FnOnce(&mut Pup) -> Result<(), String>,
-{
-    super::read_pup_from_path(path).and_then(|ref mut pup| {
-        f(pup)?;
-        super::write_pup_to_path(pup, path)
-    })
-}
@@ -0,0 +1,51 @@
+use pupper::{Digest, ParseOptions, SegmentNameMap};
+
+use std::path::Path;
+
+pub fn execute(
+    pup_path: &Path,
+    index: usize,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let key = match crate::key_file_option(args, config) {
+        Some(key_path) => Some(crate::read_data_from_path(key_path)?),
+        None => None,
+    };
+
+    let data = crate::read_pup_data(pup_path, |i| i == index)?;
+    let pup = crate::parse_pup(&data, pup_path, &mut ParseOptions::default())?;
+    let seg = pup
+        .segments
+        .get(index)
+        .ok_or_else(|| format!("index '{}' is out-of-bounds", index))?;
+
+    let sha1 = Digest::sha1_of(seg.data());
+    let kind = seg.signature_kind();
+    let hmac = key.map(|key| Digest::signature_with_key(kind, &key, seg.data()));
+
+    println!("segment {}: {}", index, seg.id.display(names));
+    println!("  stored:      {} ({})", seg.signature(), kind);
+    println!("  SHA-1:       {}", sha1);
+    if let Some(hmac) = hmac {
+        println!("  {:<13}{}", format!("{}:", kind), hmac);
+    }
+
+    match hmac {
+        Some(x) if x == *seg.signature() => {
+            println!("signature matches");
+            Ok(())
+        }
+        Some(_) => Err("signature doesn't match".to_string().into()),
+        None if sha1 == *seg.signature() => {
+            // Some tools sign with a plain hash; that's worth pointing out.
+            println!("signature is the plain SHA-1 of the data, not an HMAC");
+            Ok(())
+        }
+        None => {
+            println!("signature can't be checked without a key (--key)");
+            Ok(())
+        }
+    }
+}
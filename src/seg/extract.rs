@@ -1,12 +1,59 @@
-use std::path::Path;
+use pupper::ParseOptions;
 
-pub fn execute(pup_path: &Path, index: usize, args: &clap::ArgMatches) -> Result<(), String> {
-    let seg_path = Path::new(args.value_of("seg").unwrap());
+use std::{
+    io::{IsTerminal as _, Write as _},
+    path::Path,
+};
 
-    crate::read_pup_from_path(pup_path).and_then(|pup| {
-        pup.segments
+pub fn execute(pup_path: &Path, index: usize, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let to_stdout = args.is_present("to_stdout");
+    // Binary data would garble the terminal.
+    if to_stdout && !args.is_present("force") && std::io::stdout().is_terminal() {
+        return Err("refusing to write segment data to a terminal (use --force)".into());
+    }
+
+    let seg_path = args.value_of_os("seg").map(Path::new);
+    let max_part_size = args
+        .value_of("max_part_size")
+        .map(super::parse_size_option)
+        .transpose()?;
+
+    let data = crate::read_pup_data(pup_path, |i| i == index)?;
+    crate::parse_pup(&data, pup_path, &mut ParseOptions::default()).and_then(|pup| {
+        let seg = pup
+            .segments
             .get(index)
-            .ok_or_else(|| format!("index '{}' is out-of-bounds", index))
-            .and_then(|seg| crate::write_data_to_path(&seg.data, seg_path))
+            .ok_or_else(|| format!("index '{}' is out-of-bounds", index))?;
+
+        match (seg_path, max_part_size) {
+            (None, _) => write_to_stdout(seg.data()),
+            (Some(seg_path), Some(max_part_size)) => {
+                write_parts(seg.data(), seg_path, max_part_size)
+            }
+            (Some(seg_path), None) => crate::write_data_to_path(seg.data(), seg_path),
+        }
     })
 }
+
+fn write_to_stdout(data: &[u8]) -> Result<(), crate::Error> {
+    let mut stdout = std::io::stdout().lock();
+
+    stdout
+        .write_all(data)
+        .and_then(|_| stdout.flush())
+        .map_err(|err| format!("failed to write to stdout: {}", err).into())
+}
+
+// Writes the data in numbered parts of at most the given size, which 'insert --join' reassembles.
+// There's always at least one part, even if the data are empty.
+fn write_parts(data: &[u8], seg_path: &Path, max_part_size: usize) -> Result<(), crate::Error> {
+    // chunks() yields nothing for empty data.
+    let parts = data
+        .chunks(max_part_size)
+        .chain(data.is_empty().then_some(data));
+    for (i, part) in parts.enumerate() {
+        crate::write_data_to_path(part, &super::part_path(seg_path, i))?;
+    }
+
+    Ok(())
+}
@@ -1,19 +1,65 @@
-use pupper::Segment;
+use pupper::{Segment, SegmentNameMap};
 
-use std::path::Path;
+use std::{io::Read as _, path::Path};
 
-pub fn execute(pup_path: &Path, index: usize, args: &clap::ArgMatches) -> Result<(), String> {
-    let seg_path = Path::new(args.value_of("seg").unwrap());
-    let id = super::parse_id_option(args.value_of("id"), seg_path)?;
+// Without an index, the segment goes where official firmware would put it.
+pub fn execute(
+    pup_path: &Path,
+    index: Option<usize>,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    // Without a file name, there's nothing to derive the ID from, so '--id' is required.
+    let seg_path = Path::new(args.value_of("seg").unwrap_or_default());
+    let id = super::parse_id_option(args.value_of("id"), seg_path, names)?;
 
-    super::modify_pup_at_path(pup_path, |pup| {
-        if !(0..=pup.segments.len()).contains(&index) {
-            return Err(format!("index '{}' is out-of-bounds", index));
+    let stdin_data = if args.is_present("from_stdin") {
+        let mut data = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|err| format!("failed to read from stdin: {}", err))?;
+
+        Some(data)
+    } else {
+        None
+    };
+
+    crate::modify_pup_at_path(pup_path, |pup| {
+        if let Some(index) = index.filter(|x| *x > pup.segments.len()) {
+            return Err(format!("index '{}' is out-of-bounds", index).into());
+        }
+
+        let data = match stdin_data {
+            Some(data) => data,
+            None if args.is_present("join") => read_parts(seg_path)?,
+            None => crate::read_data_from_path(seg_path)?,
+        };
+        let seg = Segment::new(id, data);
+
+        // insert_segment() appends unless inserting canonically, so the segment is rotated from
+        // the end into place.
+        pup.insert_segment(seg, index.is_none())
+            .map_err(|err| format!("failed to insert segment: {}", err))?;
+        if let Some(index) = index {
+            pup.segments[index..].rotate_right(1);
         }
 
-        crate::read_data_from_path(seg_path).map(|data| {
-            let seg = Segment::new(id, data);
-            pup.segments.insert(index, seg);
-        })
+        Ok(())
     })
 }
+
+// Reassembles the parts written by 'extract --max_part_size', stopping at the first missing part.
+fn read_parts(seg_path: &Path) -> Result<Vec<u8>, crate::Error> {
+    let mut data = crate::read_data_from_path(&super::part_path(seg_path, 0))?;
+
+    for i in 1.. {
+        let part_path = super::part_path(seg_path, i);
+        if !part_path.exists() {
+            break;
+        }
+
+        data.append(&mut crate::read_data_from_path(&part_path)?);
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,77 @@
+use pupper::ParseOptions;
+
+use std::path::Path;
+
+pub fn execute(pup_path: &Path, index: usize, args: &clap::ArgMatches) -> Result<(), crate::Error> {
+    let min_len = args
+        .value_of("min_len")
+        .map_or(Ok(4), |x| crate::parse_number(x, "minimum length"))?;
+
+    let data = crate::read_pup_data(pup_path, |i| i == index)?;
+    let pup = crate::parse_pup(&data, pup_path, &mut ParseOptions::default())?;
+    let seg = pup
+        .segments
+        .get(index)
+        .ok_or_else(|| format!("index '{}' is out-of-bounds", index))?;
+
+    let mut strings = find_ascii(seg.data(), min_len);
+    strings.extend(find_utf16(seg.data(), min_len));
+    strings.sort_by_key(|(offset, _)| *offset);
+
+    for (offset, string) in strings {
+        println!("{:#010x} {}", offset, string);
+    }
+
+    Ok(())
+}
+
+fn is_printable(c: u32) -> bool {
+    (0x20..0x7F).contains(&c) || c == u32::from(b'\t')
+}
+
+fn find_ascii(data: &[u8], min_len: usize) -> Vec<(usize, String)> {
+    let mut strings = Vec::new();
+    let mut start = 0;
+
+    for (i, x) in data.iter().chain(std::iter::once(&0)).enumerate() {
+        if !is_printable(u32::from(*x)) {
+            if i - start >= min_len {
+                strings.push((start, String::from_utf8_lossy(&data[start..i]).into_owned()));
+            }
+            start = i + 1;
+        }
+    }
+
+    strings
+}
+
+// Only finds strings of printable ASCII characters, as encoded in big-endian UTF-16 like
+// everything else on the PS3. (Little-endian strings are found too, just one byte early.)
+fn find_utf16(data: &[u8], min_len: usize) -> Vec<(usize, String)> {
+    let mut strings = Vec::new();
+
+    // Strings may start at either parity.
+    for parity in 0..2 {
+        let mut string = String::new();
+        let mut start = parity;
+
+        let units = data[parity.min(data.len())..]
+            .chunks_exact(2)
+            .map(|x| u16::from_be_bytes([x[0], x[1]]))
+            .chain(std::iter::once(0));
+        for (i, unit) in units.enumerate() {
+            if is_printable(u32::from(unit)) {
+                string.push(char::from(unit as u8));
+                continue;
+            }
+
+            if string.len() >= min_len {
+                strings.push((start, std::mem::take(&mut string)));
+            }
+            string.clear();
+            start = parity + (i + 1) * 2;
+        }
+    }
+
+    strings
+}
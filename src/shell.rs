@@ -0,0 +1,163 @@
+use pupper::{DigestCache, Pup, SegmentNameMap};
+
+use std::{
+    io::{self, BufRead as _, Write as _},
+    path::Path,
+};
+
+const HELP: &str = "\
+ls                        lists the segments
+cat <segment>             prints the contents of a segment
+extract <segment> <path>  writes the data of a segment to a file
+replace <segment> <path>  replaces the data of a segment with a file, and signs it
+verify                    checks the structure and every segment signature
+save [path]               writes the PUP (default: where it was read from)
+quit                      exits";
+
+// Operates on one in-memory Pup until stdin ends, so that a series of edits only parses and
+// serializes the PUP once.
+pub fn execute(
+    path: &Path,
+    names: &SegmentNameMap,
+    audit_log: Option<&Path>,
+) -> Result<(), crate::Error> {
+    let mut pup = super::read_pup_from_path(path)?;
+    // Repeated 'verify's only hash the segments that were replaced in between.
+    let mut digests = DigestCache::new();
+    let mut modified = false;
+    let mut quitting = false;
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("pupper> ");
+        io::stdout()
+            .flush()
+            .map_err(|err| format!("failed to write to stdout: {}", err))?;
+
+        let line = match lines.next() {
+            Some(x) => x.map_err(|err| format!("failed to read from stdin: {}", err))?,
+            None => {
+                println!();
+                break;
+            }
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let result = match words.as_slice() {
+            [] => Ok(()),
+            ["help"] => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            ["quit"] | ["exit"] => {
+                // Unsaved changes are only discarded when asked twice.
+                if modified && !quitting {
+                    quitting = true;
+                    println!(
+                        "there are unsaved changes; 'save' them, or 'quit' again to discard them"
+                    );
+                    continue;
+                }
+                break;
+            }
+            ["ls"] => {
+                list(&pup, names);
+                Ok(())
+            }
+            ["cat", segment] => find(&pup, segment, names).map(|i| {
+                // The prompt should start on its own line.
+                let text = super::cat::decode(pup.segments[i].data());
+                if text.is_empty() || text.ends_with('\n') {
+                    print!("{}", text);
+                } else {
+                    println!("{}", text);
+                }
+            }),
+            ["extract", segment, seg_path] => find(&pup, segment, names).and_then(|i| {
+                super::write_data_to_path(pup.segments[i].data(), Path::new(seg_path))
+            }),
+            ["replace", segment, seg_path] => find(&pup, segment, names).and_then(|i| {
+                let data = super::read_data_from_path(Path::new(seg_path))?;
+                pup.segments[i].set_data(data);
+                digests.sign(&mut pup.segments[i]);
+                modified = true;
+
+                Ok(())
+            }),
+            ["verify"] => verify(&pup, &mut digests),
+            ["save"] => save(&pup, path, path, audit_log).map(|_| modified = false),
+            ["save", save_path] => {
+                save(&pup, path, Path::new(save_path), audit_log).map(|_| modified = false)
+            }
+            [command, ..] => Err(format!("'{}' isn't a command; try 'help'", command).into()),
+        };
+
+        quitting = false;
+        if let Err(err) = result {
+            println!("error: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn list(pup: &Pup, names: &SegmentNameMap) {
+    for (i, seg) in pup.segments.iter().enumerate() {
+        println!(
+            "{:>2} {:#06x} {:<20} {:>10} B",
+            i,
+            seg.id.0,
+            names.name_of(seg.id).unwrap_or("?"),
+            seg.data().len()
+        );
+    }
+}
+
+fn find(pup: &Pup, segment: &str, names: &SegmentNameMap) -> Result<usize, crate::Error> {
+    let id = super::parse_segment(segment, names)?;
+
+    pup.segments
+        .iter()
+        .position(|x| x.id == id)
+        .ok_or_else(|| format!("PUP has no segment '{}'", segment).into())
+}
+
+// Writes the PUP like any other modifying subcommand, including the entry in the audit log.
+fn save(
+    pup: &Pup,
+    input: &Path,
+    output: &Path,
+    audit_log: Option<&Path>,
+) -> Result<(), crate::Error> {
+    let write = || super::write_pup_to_path(pup, output);
+
+    match audit_log {
+        Some(log_path) => super::audit::record(log_path, input, output, write),
+        None => write(),
+    }
+}
+
+fn verify(pup: &Pup, digests: &mut DigestCache) -> Result<(), crate::Error> {
+    let findings = pup.validate();
+    for finding in findings.iter() {
+        println!("{}", finding);
+    }
+
+    let mut unsigned = 0;
+    for (i, seg) in pup.segments.iter().enumerate() {
+        if !digests.verify(seg) {
+            println!("segment {}: signature doesn't match", i);
+            unsigned += 1;
+        }
+    }
+
+    if findings.is_empty() && unsigned == 0 {
+        println!("ok");
+        Ok(())
+    } else if findings.is_empty() {
+        Err(format!("{} segment signature(s) don't match", unsigned).into())
+    } else {
+        Err(crate::Error::Findings(findings))
+    }
+}
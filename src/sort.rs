@@ -0,0 +1,26 @@
+use pupper::{SegmentId, SegmentNameMap};
+
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    super::modify_pup_at_path(path, |pup| {
+        match args.value_of("by") {
+            Some("id") => pup.segments.sort_by_key(|x| x.id.0),
+            Some("size") => pup.segments.sort_by_key(|x| x.data().len()),
+            Some("name") => pup.segments.sort_by_key(|x| name_sort_key(x.id, names)),
+            _ => pup.sort_canonical(),
+        }
+
+        Ok(())
+    })
+}
+
+pub fn name_sort_key(id: SegmentId, names: &SegmentNameMap) -> (bool, String, u64) {
+    // Segments without a known file name go last, ordered by ID.
+    match names.name_of(id) {
+        Some(file_name) => (false, file_name.to_string(), id.0),
+        None => (true, String::new(), id.0),
+    }
+}
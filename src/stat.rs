@@ -0,0 +1,81 @@
+use pupper::{SegmentAnalysis, SegmentNameMap, SegmentStats, Stats};
+
+use std::cmp::Reverse;
+
+pub fn execute(
+    path: &std::path::Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+) -> Result<(), crate::Error> {
+    super::read_pup_from_path(path).map(|pup| {
+        let analyses = if args.is_present("entropy") {
+            Some(pup.analyze())
+        } else {
+            None
+        };
+
+        print_stats(&pup.stats(), analyses.as_deref(), names)
+    })
+}
+
+fn print_stats(stats: &Stats, analyses: Option<&[SegmentAnalysis]>, names: &SegmentNameMap) {
+    // The overhead includes any padding between segments.
+    let overhead = stats.overhead();
+
+    println!("total size:  {}", format_size(stats.total_size));
+    println!("data size:   {}", format_size(stats.data_size));
+    println!(
+        "overhead:    {} ({:.2}%)",
+        format_size(overhead),
+        stats.percentage_of_total(overhead)
+    );
+
+    for (kind, count) in stats.signature_kinds.iter() {
+        println!("signed with {}: {} segment(s)", kind, count);
+    }
+
+    let mut segments: Vec<(usize, &SegmentStats)> = stats.segments.iter().enumerate().collect();
+    segments.sort_by_key(|(_, x)| Reverse(x.size));
+
+    if !segments.is_empty() {
+        println!();
+        println!("segments (largest first):");
+    }
+
+    for (i, seg) in segments {
+        let file_name = names.name_of(seg.id).unwrap_or("?");
+        // Close to 8 bits per byte means encrypted or compressed.
+        let entropy =
+            analyses.map_or_else(String::new, |x| format!(" {:>5.2} bits/byte", x[i].entropy));
+
+        println!(
+            "  {:#06x} {:<18} {:>12} {:>7.2}%{}",
+            seg.id.0,
+            file_name,
+            format_size(seg.size),
+            seg.percentage,
+            entropy
+        );
+    }
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut size = size as f64;
+    let mut unit = "B";
+    for next_unit in UNITS.iter() {
+        if size < 1024.0 {
+            break;
+        }
+
+        size /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{:.1} {}", size, unit)
+}
@@ -0,0 +1,138 @@
+use pupper::{FixedSize as _, HeaderMetadata, RawHeader, SegmentEntry};
+
+use std::{
+    convert::TryFrom as _,
+    fs::File,
+    io::{Read as _, Seek as _, SeekFrom},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// With '--max_memory', commands that can stream segment data from the file hold only a bounded
+// buffer of it at a time; all others refuse PUPs larger than the budget up front, rather than
+// risk running out of memory halfway through.
+
+/// The largest buffer worth using, budget permitting.
+const MAX_BUFFER_SIZE: usize = 0x10_0000;
+
+// The budget in bytes, or 0 for none.
+static MAX_MEMORY: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_budget(max_memory: usize) {
+    MAX_MEMORY.store(max_memory, Ordering::Relaxed);
+}
+
+/// The memory budget, if '--max_memory' was given.
+pub fn budget() -> Option<usize> {
+    Some(MAX_MEMORY.load(Ordering::Relaxed)).filter(|x| *x != 0)
+}
+
+/// Fails if reading this many bytes of the file at a path into memory would exceed the budget.
+pub fn check_budget(path: &Path, size: u64) -> Result<(), crate::Error> {
+    match budget() {
+        Some(budget) if size > budget as u64 => Err(format!(
+            "reading '{}' takes {} B, but --max_memory allows {} B (only 'unpack' and 'checksums' \
+             stream uncompressed local PUPs)",
+            path.display(),
+            size,
+            budget
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Whether to stream the PUP at a path rather than read it into memory: only if there is a budget,
+/// and only if the PUP is an uncompressed local file.
+pub fn streams(path: &Path) -> bool {
+    #[cfg(feature = "net")]
+    if path.to_str().is_some_and(crate::net::is_url) {
+        return false;
+    }
+
+    let mut magic = [0; 0x10];
+    budget().is_some()
+        && File::open(path)
+            .and_then(|mut x| x.read_exact(&mut magic))
+            .is_ok()
+        && pupper::sniff(&magic).is_some()
+}
+
+/// Reads only the header of the PUP at a local path.
+pub fn read_header(path: &Path) -> Result<RawHeader, crate::Error> {
+    let mut file = open(path)?;
+    let parse_err = |err| crate::Error::Parse {
+        path: path.into(),
+        file_size: file_size(path),
+        err,
+    };
+
+    let mut meta_data = vec![0; HeaderMetadata::SIZE];
+    file.read_exact(&mut meta_data)
+        .map_err(|err| read_err(path, err))?;
+    let meta = HeaderMetadata::try_from(meta_data.as_slice()).map_err(parse_err)?;
+
+    check_budget(path, meta.header_size)?;
+    // A header can't end before its own metadata does.
+    let header_size = usize::try_from(meta.header_size)
+        .ok()
+        .filter(|x| *x >= HeaderMetadata::SIZE)
+        .ok_or(pupper::ParseError::Undersized)
+        .map_err(parse_err)?;
+    let mut header_data = meta_data;
+    header_data.resize(header_size, 0);
+    file.read_exact(&mut header_data[HeaderMetadata::SIZE..])
+        .map_err(|err| read_err(path, err))?;
+
+    RawHeader::try_from(header_data.as_slice()).map_err(parse_err)
+}
+
+/// Hands the data of a segment of the PUP at a local path to a function, a buffer at a time.
+///
+/// Each buffer is at most a share of the budget, so that `shares` callers may run at once.
+pub fn for_each_chunk(
+    path: &Path,
+    entry: &SegmentEntry,
+    shares: usize,
+    mut f: impl FnMut(&[u8]) -> Result<(), crate::Error>,
+) -> Result<(), crate::Error> {
+    let buffer_size = budget()
+        .map_or(MAX_BUFFER_SIZE, |x| x / shares.max(1))
+        .clamp(1, MAX_BUFFER_SIZE);
+
+    let mut file = open(path)?;
+    file.seek(SeekFrom::Start(entry.offset))
+        .map_err(|err| read_err(path, err))?;
+    let mut file = file.take(entry.size);
+
+    let mut buffer = vec![0; buffer_size];
+    let mut remaining = entry.size;
+    while remaining != 0 {
+        let len = file.read(&mut buffer).map_err(|err| read_err(path, err))?;
+        if len == 0 {
+            return Err(format!(
+                "data of segment {:#x} in '{}' are out of bounds",
+                entry.id.0,
+                path.display()
+            )
+            .into());
+        }
+
+        f(&buffer[..len])?;
+        remaining -= len as u64;
+    }
+
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<File, crate::Error> {
+    File::open(path).map_err(|err| read_err(path, err))
+}
+
+fn file_size(path: &Path) -> u64 {
+    path.metadata().map_or(0, |x| x.len())
+}
+
+fn read_err(path: &Path, err: std::io::Error) -> crate::Error {
+    format!("failed to read from '{}': {}", path.display(), err).into()
+}
@@ -0,0 +1,55 @@
+use pupper::{
+    content::{self, NoDecryptor, Node},
+    SegmentNameMap,
+};
+
+pub fn execute(path: &std::path::Path, names: &SegmentNameMap) -> Result<(), crate::Error> {
+    let pup = super::read_pup_from_path(path)?;
+
+    println!("{}", path.display());
+    for (i, seg) in pup.segments.iter().enumerate() {
+        // Unknown segments are at least given a fitting extension.
+        let name = names.name_of(seg.id).map_or_else(
+            || match seg.suggested_extension() {
+                Some(extension) => format!("{:#x}.{}", seg.id.0, extension),
+                None => format!("{:#x}", seg.id.0),
+            },
+            |x| x.to_string(),
+        );
+        let node = content::extract(&name, seg.data(), &NoDecryptor);
+
+        print_node(&node, "", i + 1 == pup.segments.len());
+    }
+
+    Ok(())
+}
+
+fn print_node(node: &Node, indent: &str, is_last: bool) {
+    let (branch, child_indent) = if is_last {
+        ("└── ", "    ")
+    } else {
+        ("├── ", "│   ")
+    };
+    let encrypted = if node.encrypted { " (encrypted)" } else { "" };
+
+    // Plugged-in handlers know better than sniffing.
+    let kind = node
+        .description
+        .clone()
+        .unwrap_or_else(|| content::sniff(&node.data).to_string());
+
+    println!(
+        "{}{}{} [{} B, {}]{}",
+        indent,
+        branch,
+        node.name,
+        node.data.len(),
+        kind,
+        encrypted
+    );
+
+    let indent = format!("{}{}", indent, child_indent);
+    for (i, child) in node.children.iter().enumerate() {
+        print_node(child, &indent, i + 1 == node.children.len());
+    }
+}
@@ -0,0 +1,160 @@
+use pupper::{Digest, DirManifest, RawHeader, SegmentEntry, SegmentId, SegmentNameMap};
+
+use sha1::Digest as _;
+
+use std::{
+    collections::HashSet,
+    convert::TryFrom as _,
+    fs::{self, File},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    names: &SegmentNameMap,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let dir = Path::new(args.value_of("dir").unwrap());
+    // '-j' after 'unpack' predates the global one, and still takes precedence.
+    let thread_count = match args.value_of("threads") {
+        Some(x) => super::parse_threads(x)?.get(),
+        None => config.parallelism().thread_count(),
+    };
+
+    // Under '--max_memory', segments are copied from the file a buffer at a time instead.
+    let streams = crate::stream::streams(path);
+    let data = if streams {
+        Vec::new()
+    } else {
+        super::read_pup_data(path, |_| true)?
+    };
+    let header = if streams {
+        crate::stream::read_header(path)?
+    } else {
+        RawHeader::try_from(data.as_slice()).map_err(|err| crate::Error::Parse {
+            path: path.into(),
+            file_size: data.len() as u64,
+            err,
+        })?
+    };
+
+    // Segment data are written straight from the file data rather than copied into a Pup first.
+    let file_names = file_names(header.segment_entries.iter().map(|x| x.id), names);
+    let jobs = header
+        .segment_entries
+        .iter()
+        .zip(file_names.iter())
+        .enumerate()
+        .map(|(i, (entry, file_name))| {
+            if streams {
+                return Ok((dir.join(file_name), Source::File(entry)));
+            }
+
+            let seg_data = usize::try_from(entry.offset)
+                .ok()
+                .zip(usize::try_from(entry.size).ok())
+                .and_then(|(start, size)| data.get(start..start.checked_add(size)?))
+                .ok_or_else(|| format!("data of segment {} are out of bounds", i))?;
+
+            Ok((dir.join(file_name), Source::Memory(seg_data)))
+        })
+        .collect::<Result<Vec<(PathBuf, Source)>, String>>()?;
+
+    fs::create_dir_all(dir)
+        .map_err(|err| format!("failed to create '{}': {}", dir.display(), err))?;
+
+    let next = AtomicUsize::new(0);
+    let failure = Mutex::new(None);
+    let fingerprints = Mutex::new(vec![Digest::default(); jobs.len()]);
+    thread::scope(|scope| {
+        for _ in 0..thread_count.min(jobs.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let (seg_path, source) = match jobs.get(i) {
+                    Some(x) => x,
+                    None => break,
+                };
+
+                match write_segment(path, source, seg_path, thread_count) {
+                    Ok(fingerprint) => fingerprints.lock().unwrap()[i] = fingerprint,
+                    Err(err) => {
+                        failure.lock().unwrap().get_or_insert(err);
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(err) = failure.into_inner().unwrap() {
+        return Err(err);
+    }
+
+    // The manifest lets 'pack' restore what the files alone can't.
+    let fingerprints = fingerprints.into_inner().unwrap();
+    let manifest = DirManifest::from_header(&header, &file_names, &fingerprints);
+    super::write_data_to_path(
+        manifest.to_toml().as_bytes(),
+        &dir.join(DirManifest::FILE_NAME),
+    )
+}
+
+// Where the data of a segment come from.
+enum Source<'a> {
+    Memory(&'a [u8]),
+    File(&'a SegmentEntry),
+}
+
+// Writes the data of a segment to a file, returning their SHA-1 fingerprint.
+fn write_segment(
+    path: &Path,
+    source: &Source,
+    seg_path: &Path,
+    thread_count: usize,
+) -> Result<Digest, crate::Error> {
+    let entry = match source {
+        Source::Memory(seg_data) => {
+            super::write_data_to_path(seg_data, seg_path)?;
+            return Ok(Digest::sha1_of(seg_data));
+        }
+        Source::File(x) => x,
+    };
+
+    let write_err =
+        |err: io::Error| format!("failed to write to '{}': {}", seg_path.display(), err);
+    let mut file = File::create(seg_path).map_err(write_err)?;
+    let mut hasher = sha1::Sha1::new();
+    // Each thread gets an equal share of the budget.
+    crate::stream::for_each_chunk(path, entry, thread_count, |chunk| {
+        hasher.update(chunk);
+        file.write_all(chunk).map_err(|x| write_err(x).into())
+    })?;
+
+    Ok(Digest(hasher.finalize().into()))
+}
+
+// The file name of each segment in a PUP. These are also the names 'checksums' lists.
+pub fn file_names(ids: impl Iterator<Item = SegmentId>, names: &SegmentNameMap) -> Vec<String> {
+    let mut used_names = HashSet::new();
+
+    ids.enumerate()
+        .map(|(i, id)| {
+            let file_name = names
+                .name_of(id)
+                .map_or_else(|| format!("{:#x}.bin", id.0), Into::into);
+            // Segments may share an ID.
+            if used_names.insert(file_name.clone()) {
+                file_name
+            } else {
+                format!("{}.{}", file_name, i)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,14 @@
+pub fn execute(path: &std::path::Path) -> Result<(), crate::Error> {
+    let data = super::read_pup_data(path, |_| true)?;
+    let findings = pupper::validate(&data).map_err(|err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    })?;
+
+    if findings.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::Error::Findings(findings))
+    }
+}
@@ -0,0 +1,144 @@
+use pupper::{Digest, ParseOptions, Pup, SegmentId};
+
+use serde_json::Value;
+
+use sha2::Digest as _;
+
+use std::path::Path;
+
+// What the manifest expects of a segment. Each entry has at least one of the digests.
+struct ManifestEntry {
+    id: SegmentId,
+    sha1: Option<Digest>,
+    sha256: Option<String>,
+}
+
+pub fn execute(
+    path: &Path,
+    args: &clap::ArgMatches,
+    config: &crate::Config,
+) -> Result<(), crate::Error> {
+    let data = super::read_pup_data(path, |_| true)?;
+    let parse_err = |err| crate::Error::Parse {
+        path: path.into(),
+        file_size: data.len() as u64,
+        err,
+    };
+
+    // Without the HMAC key, signatures are meaningless, but fingerprints still aren't.
+    let pup = super::parse_pup(&data, path, &mut ParseOptions::default())?;
+    if !args.is_present("fingerprints_only") {
+        let summary = match super::key_file_option(args, config) {
+            Some(key_path) => {
+                let key = super::read_data_from_path(key_path)?;
+                pupper::verify_with_key(&data, &key, config.parallelism())
+            }
+            None => pupper::verify(&data, config.parallelism()),
+        }
+        .map_err(parse_err)?;
+        if args.is_present("json") {
+            // Serializing plain data to a string can't fail.
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        }
+        if let Some(err) = summary.error() {
+            return Err(parse_err(err));
+        }
+    }
+
+    if let Some(manifest_path) = args.value_of("manifest").map(Path::new) {
+        let manifest = read_manifest_from_path(manifest_path)?;
+        compare_fingerprints(&pup, &manifest)?;
+    }
+
+    Ok(())
+}
+
+// Reads the segment IDs and fingerprints from the output of 'print --fingerprints'. Segments may
+// also have a 'sha256' entry, in hexadecimal, in addition to or instead of the SHA-1 fingerprint.
+fn read_manifest_from_path(path: &Path) -> Result<Vec<ManifestEntry>, crate::Error> {
+    let json: Value = super::read_data_from_path(path).and_then(|x| {
+        serde_json::from_slice(&x).map_err(|err| {
+            format!("failed to parse manifest at '{}': {}", path.display(), err).into()
+        })
+    })?;
+
+    json.get("segments")
+        .and_then(Value::as_array)
+        .ok_or("'segments' is missing or invalid")?
+        .iter()
+        .enumerate()
+        .map(|(i, seg)| {
+            let id = seg
+                .get("id")
+                .and_then(super::segment_id_from_json)
+                .ok_or_else(|| format!("'id' of segment {} is missing or invalid", i))?;
+            let field = |key| {
+                seg.get(key)
+                    .map(|x| {
+                        x.as_str()
+                            .ok_or_else(|| format!("'{}' of segment {} is invalid", key, i))
+                    })
+                    .transpose()
+            };
+
+            let sha1 = field("fingerprint")?.map(str::parse).transpose()?;
+            let sha256 = field("sha256")?
+                .map(|x| {
+                    Some(x.to_ascii_lowercase())
+                        .filter(|x| x.len() == 64 && x.bytes().all(|x| x.is_ascii_hexdigit()))
+                        .ok_or_else(|| format!("'sha256' of segment {} is invalid", i))
+                })
+                .transpose()?;
+            if sha1.is_none() && sha256.is_none() {
+                return Err(format!("segment {} has neither 'fingerprint' nor 'sha256'", i).into());
+            }
+
+            Ok(ManifestEntry { id, sha1, sha256 })
+        })
+        .collect()
+}
+
+fn compare_fingerprints(pup: &Pup, manifest: &[ManifestEntry]) -> Result<(), crate::Error> {
+    if pup.segments.len() != manifest.len() {
+        return Err(format!(
+            "PUP has {} segment(s), but the manifest lists {}",
+            pup.segments.len(),
+            manifest.len()
+        )
+        .into());
+    }
+
+    let mut mismatches = 0;
+    for (i, (seg, entry)) in pup.segments.iter().zip(manifest.iter()).enumerate() {
+        if seg.id != entry.id {
+            println!(
+                "segment {}: ID is {}, but the manifest expects {}",
+                i, seg.id, entry.id
+            );
+            mismatches += 1;
+        } else if entry.sha1.is_some_and(|x| x != Digest::sha1_of(seg.data())) {
+            println!(
+                "segment {}: SHA-1 fingerprint doesn't match the manifest",
+                i
+            );
+            mismatches += 1;
+        } else if entry
+            .sha256
+            .as_ref()
+            .is_some_and(|x| *x != to_hex(&sha2::Sha256::digest(seg.data())))
+        {
+            println!("segment {}: SHA-256 digest doesn't match the manifest", i);
+            mismatches += 1;
+        }
+    }
+
+    if mismatches == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} segment(s) don't match the manifest", mismatches).into())
+    }
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|x| format!("{:02x}", x)).collect()
+}